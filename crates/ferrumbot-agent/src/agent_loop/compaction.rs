@@ -0,0 +1,125 @@
+use anyhow::{Result, bail};
+use ferrumbot_core::{SessionMessage, Usage, UsageRecord};
+use ferrumbot_providers::ChatOptions;
+use serde_json::json;
+use tracing::{info, warn};
+
+use super::AgentLoop;
+
+const SUMMARY_PROMPT: &str = "You maintain a running summary of a conversation between a user and an AI assistant. Merge the previous summary with the new messages into one updated summary. Keep facts, names, decisions, preferences, open tasks and anything the user asked to remember; drop small talk. Write plain prose or short bullets, under 400 words, and reply with the summary only.";
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+impl AgentLoop {
+    pub async fn compact(&self, session_key: &str) -> Result<Option<usize>> {
+        self.compact_session(session_key, true).await
+    }
+
+    pub(super) async fn compact_session(
+        &self,
+        session_key: &str,
+        force: bool,
+    ) -> Result<Option<usize>> {
+        let (previous, transcript, start, cut) = {
+            let mut sessions = self.sessions.lock().await;
+            let session = sessions.get_or_create(session_key)?;
+            if !force && !self.context.needs_compaction(session) {
+                return Ok(None);
+            }
+            let start = session.compacted();
+            let Some(cut) = compaction_cut(&session.messages, start, self.compaction.keep_recent)
+            else {
+                return Ok(None);
+            };
+            (
+                session.summary().map(ToString::to_string),
+                transcript(&session.messages[start..cut]),
+                start,
+                cut,
+            )
+        };
+
+        let request = format!(
+            "Previous summary:\n{}\n\nNew messages:\n{transcript}",
+            previous.as_deref().unwrap_or("(none)")
+        );
+        let options = ChatOptions {
+            max_tokens: Some(1024),
+            temperature: Some(0.2),
+            ..Default::default()
+        };
+        let resp = self
+            .provider
+            .chat(
+                vec![
+                    json!({"role": "system", "content": SUMMARY_PROMPT}),
+                    json!({"role": "user", "content": request}),
+                ],
+                None,
+                Some(&self.model),
+                &options,
+            )
+            .await?;
+        let summary = resp.content.as_deref().unwrap_or_default().trim();
+        if summary.is_empty() {
+            bail!("model returned an empty summary");
+        }
+
+        let model = resp.model.as_deref().unwrap_or(&self.model);
+        let usage = Usage::from_counts(&resp.usage, self.pricing.get(model));
+        {
+            let mut sessions = self.sessions.lock().await;
+            let session = sessions.get_or_create(session_key)?;
+            session.set_summary(summary, cut);
+            let mut session_usage = Usage::from_metadata(&session.metadata);
+            session_usage.add(&usage);
+            session
+                .metadata
+                .insert("usage".to_string(), serde_json::to_value(&session_usage)?);
+            sessions.save(session_key)?;
+        }
+        if let Err(err) = self
+            .usage
+            .record(&UsageRecord::new(session_key, model, usage))
+        {
+            warn!("failed to record usage: {err:#}");
+        }
+
+        info!(
+            "compacted {} messages of session {session_key}",
+            cut - start
+        );
+        Ok(Some(cut - start))
+    }
+}
+
+fn compaction_cut(messages: &[SessionMessage], start: usize, keep_recent: usize) -> Option<usize> {
+    let mut cut = messages.len().saturating_sub(keep_recent);
+    while cut > start && cut < messages.len() && messages[cut].role != "user" {
+        cut -= 1;
+    }
+    (cut > start).then_some(cut)
+}
+
+fn transcript(messages: &[SessionMessage]) -> String {
+    messages
+        .iter()
+        .map(|message| {
+            let mut content: String = message.content.chars().take(MAX_MESSAGE_CHARS).collect();
+            if content.len() < message.content.len() {
+                content.push_str(" [...]");
+            }
+            for call in &message.tool_calls {
+                content.push_str(&format!(
+                    "\n[called {}({})]",
+                    call["function"]["name"].as_str().unwrap_or_default(),
+                    call["function"]["arguments"].as_str().unwrap_or_default()
+                ));
+            }
+            match &message.name {
+                Some(name) => format!("{}({name}): {content}", message.role),
+                None => format!("{}: {content}", message.role),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
@@ -1,19 +1,23 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::Result;
-use ferrumbot_core::{MessageBus, SessionManager};
+use ferrumbot_config::{
+    CompactionConfig, MemoryToolConfig, ModelCapabilities, PricingTable, SubagentsConfig,
+    WebToolsConfig,
+};
+use ferrumbot_core::{MessageBus, SessionManager, TokenEstimator, ToolReplay, UsageLedger};
 use ferrumbot_cron::CronService;
-use ferrumbot_providers::{LlmProvider, OpenAiCompatibleProvider};
-use ferrumbot_tools::default_registry;
+use ferrumbot_providers::{LlmProvider, capabilities_for, provider_from_config};
+use ferrumbot_tools::{SubagentManager, default_registry};
 use tokio::sync::Mutex;
 
-use super::AgentLoop;
+use super::{AgentLoop, GenerationSettings};
 use crate::context::ContextBuilder;
 
 pub struct ToolingConfig {
-    pub brave_api_key: Option<String>,
-    pub web_max_results: usize,
+    pub web: WebToolsConfig,
+    pub memory: MemoryToolConfig,
     pub exec_timeout: u64,
     pub restrict_to_workspace: bool,
 }
@@ -28,17 +32,20 @@ impl AgentLoop {
         cron: Option<CronService>,
         tooling: ToolingConfig,
     ) -> Result<Self> {
-        let context = ContextBuilder::new(workspace.clone());
+        let mut context = ContextBuilder::new(workspace.clone());
+        context.restrict_media_to(tooling.restrict_to_workspace.then(|| workspace.clone()));
         let sessions = SessionManager::new()?;
+        let usage = UsageLedger::new()?;
+        let subagents = SubagentsConfig::default();
         let tools = default_registry(
             workspace.clone(),
             Some(bus.clone()),
             cron.clone(),
-            tooling.brave_api_key,
-            tooling.web_max_results,
+            &tooling.web,
+            &tooling.memory,
             tooling.exec_timeout,
             tooling.restrict_to_workspace,
-        );
+        )?;
 
         Ok(Self {
             bus,
@@ -47,33 +54,137 @@ impl AgentLoop {
             model,
             max_iterations,
             context,
-            sessions: Mutex::new(sessions),
+            sessions: Arc::new(Mutex::new(sessions)),
+            usage,
+            pricing: PricingTable::default(),
+            generation: GenerationSettings::default(),
+            persist_reasoning: false,
+            capabilities: ModelCapabilities::default(),
             tools,
             cron,
+            compaction: CompactionConfig::default(),
+            subagents: SubagentManager::new(subagents.max_concurrent),
+            subagent_limits: subagents,
+            open_turns: Arc::default(),
             running: Mutex::new(false),
         })
     }
 
+    pub fn with_history_budget(
+        mut self,
+        max_messages: usize,
+        context_window: usize,
+        max_tokens: usize,
+    ) -> Self {
+        self.context.set_history_limits(
+            max_messages,
+            Some(context_window.saturating_sub(max_tokens)),
+        );
+        self
+    }
+
+    pub fn with_data_dir(mut self, data_dir: &Path) -> Result<Self> {
+        self.sessions = Arc::new(Mutex::new(SessionManager::open(data_dir.join("sessions"))?));
+        self.usage = UsageLedger::open(data_dir.join("usage"))?;
+        Ok(self)
+    }
+
+    pub fn with_generation(mut self, generation: GenerationSettings) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    pub fn with_pricing(mut self, pricing: PricingTable) -> Self {
+        self.pricing = pricing;
+        self
+    }
+
+    pub fn with_media_limit(mut self, max_bytes: u64) -> Self {
+        self.context.set_media_limit(max_bytes);
+        self
+    }
+
+    pub fn with_reasoning_persistence(mut self, enabled: bool) -> Self {
+        self.persist_reasoning = enabled;
+        self
+    }
+
+    pub fn with_tool_replay(mut self, replay: ToolReplay) -> Self {
+        self.context.set_tool_replay(replay);
+        self
+    }
+
+    pub fn with_compaction(mut self, compaction: CompactionConfig) -> Self {
+        self.compaction = compaction;
+        self
+    }
+
+    pub fn with_subagents(mut self, limits: SubagentsConfig) -> Self {
+        self.subagents = SubagentManager::new(limits.max_concurrent);
+        self.subagent_limits = limits;
+        self
+    }
+
+    pub fn with_capabilities(mut self, capabilities: ModelCapabilities) -> Self {
+        self.context.set_vision(capabilities.supports_vision());
+        self.capabilities = capabilities;
+        self
+    }
+
+    pub fn with_token_estimator(mut self, estimator: Arc<dyn TokenEstimator>) -> Self {
+        self.context.set_estimator(estimator);
+        self
+    }
+
     pub fn from_config(
         bus: MessageBus,
         config: &ferrumbot_config::Config,
         cron: Option<CronService>,
     ) -> Result<Self> {
-        let provider = Arc::new(OpenAiCompatibleProvider::from_config(config)?);
-        Self::new(
+        let provider = provider_from_config(config)?;
+        let defaults = &config.agents.defaults;
+        let capabilities = capabilities_for(config, &defaults.model);
+        let max_tokens = capabilities
+            .max_output_tokens
+            .map_or(defaults.max_tokens, |cap| defaults.max_tokens.min(cap));
+        let agent = Self::new(
             bus,
             provider,
             config.workspace_path(),
-            config.agents.defaults.model.clone(),
-            config.agents.defaults.max_tool_iterations,
+            defaults.model.clone(),
+            defaults.max_tool_iterations,
             cron,
             ToolingConfig {
-                brave_api_key: Some(config.tools.web.search.api_key.clone())
-                    .filter(|x| !x.is_empty()),
-                web_max_results: config.tools.web.search.max_results as usize,
+                web: WebToolsConfig {
+                    http: config.http.overlay(&config.tools.web.http),
+                    ..config.tools.web.clone()
+                },
+                memory: config.tools.memory.clone(),
                 exec_timeout: config.tools.exec.timeout,
                 restrict_to_workspace: config.tools.restrict_to_workspace,
             },
-        )
+        )?;
+        let tool_replay = match (
+            defaults.replay_tool_calls,
+            defaults.tool_result_replay_chars,
+        ) {
+            (false, _) => ToolReplay::Off,
+            (true, 0) => ToolReplay::Full,
+            (true, limit) => ToolReplay::Truncated(limit),
+        };
+        Ok(agent
+            .with_history_budget(
+                defaults.max_history_messages,
+                defaults.context_window_for(&defaults.model, &capabilities),
+                max_tokens,
+            )
+            .with_media_limit(defaults.max_media_bytes)
+            .with_reasoning_persistence(defaults.persist_reasoning)
+            .with_tool_replay(tool_replay)
+            .with_pricing(config.pricing.clone())
+            .with_generation(GenerationSettings::from_config(&config.agents))
+            .with_compaction(config.agents.compaction.clone())
+            .with_subagents(config.agents.subagents.clone())
+            .with_capabilities(capabilities))
     }
 }
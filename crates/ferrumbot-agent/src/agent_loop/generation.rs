@@ -0,0 +1,71 @@
+use std::collections::{BTreeMap, HashMap};
+
+use ferrumbot_config::{AgentsConfig, GenerationProfile};
+use ferrumbot_core::InboundMessage;
+use ferrumbot_providers::ChatOptions;
+use serde_json::Value;
+use tracing::warn;
+
+#[derive(Debug, Clone, Default)]
+pub struct GenerationSettings {
+    pub base: GenerationProfile,
+    pub default_profile: Option<String>,
+    pub channel_profiles: BTreeMap<String, String>,
+    pub profiles: BTreeMap<String, GenerationProfile>,
+}
+
+impl GenerationSettings {
+    pub fn from_config(agents: &AgentsConfig) -> Self {
+        Self {
+            base: agents.base_generation(),
+            default_profile: agents.defaults.profile.clone(),
+            channel_profiles: agents.defaults.channel_profiles.clone(),
+            profiles: agents.profiles.clone(),
+        }
+    }
+
+    pub fn profile(&self, name: &str) -> Option<GenerationProfile> {
+        self.profiles
+            .get(name)
+            .cloned()
+            .or_else(|| GenerationProfile::preset(name))
+    }
+
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = GenerationProfile::PRESETS
+            .iter()
+            .map(ToString::to_string)
+            .chain(self.profiles.keys().cloned())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    pub(super) fn select(
+        &self,
+        msg: &InboundMessage,
+        session_metadata: &HashMap<String, Value>,
+    ) -> Option<String> {
+        [msg.metadata.get("profile"), session_metadata.get("profile")]
+            .into_iter()
+            .flatten()
+            .find_map(Value::as_str)
+            .map(ToString::to_string)
+            .or_else(|| self.channel_profiles.get(&msg.channel).cloned())
+            .or_else(|| self.default_profile.clone())
+    }
+
+    pub(super) fn options(&self, profile: Option<&str>) -> ChatOptions {
+        let Some(name) = profile else {
+            return ChatOptions::from(&self.base);
+        };
+        match self.profile(name) {
+            Some(profile) => ChatOptions::from(&self.base.overlay(&profile)),
+            None => {
+                warn!("unknown generation profile {name}, using defaults");
+                ChatOptions::from(&self.base)
+            }
+        }
+    }
+}
@@ -1,9 +1,12 @@
-use anyhow::Result;
-use chrono::Utc;
+use std::collections::HashMap;
+
+use anyhow::{Result, bail};
 use ferrumbot_core::{InboundMessage, OutboundMessage};
+use ferrumbot_providers::ProviderError;
+use serde_json::Value;
 use tracing::{error, info};
 
-use super::AgentLoop;
+use super::{AgentLoop, StreamSink};
 
 impl AgentLoop {
     pub async fn run(&self) {
@@ -13,6 +16,7 @@ impl AgentLoop {
             let Some(msg) = self.bus.consume_inbound().await else {
                 continue;
             };
+            let (channel, chat_id) = (msg.channel.clone(), msg.chat_id.clone());
 
             match self.process_message(msg).await {
                 Ok(Some(outbound)) => {
@@ -23,6 +27,17 @@ impl AgentLoop {
                 Ok(None) => {}
                 Err(err) => {
                     error!("error processing message: {err:#}");
+                    let notice = OutboundMessage {
+                        channel,
+                        chat_id,
+                        content: error_reply(&err),
+                        reply_to: None,
+                        media: Vec::new(),
+                        metadata: Default::default(),
+                    };
+                    if let Err(err) = self.bus.publish_outbound(notice).await {
+                        error!("failed to publish error notice: {err:#}");
+                    }
                 }
             }
         }
@@ -39,22 +54,103 @@ impl AgentLoop {
         channel: &str,
         chat_id: &str,
     ) -> Result<String> {
-        let msg = InboundMessage {
-            channel: channel.to_string(),
-            sender_id: "user".to_string(),
-            chat_id: chat_id.to_string(),
-            content: content.to_string(),
-            timestamp: Utc::now(),
-            media: Vec::new(),
-            metadata: Default::default(),
-        };
-
-        let response = self.process_message_with_session(msg, session_key).await?;
+        self.process_direct_with_metadata(content, session_key, channel, chat_id, HashMap::new())
+            .await
+    }
+
+    pub async fn process_direct_with_metadata(
+        &self,
+        content: &str,
+        session_key: &str,
+        channel: &str,
+        chat_id: &str,
+        metadata: HashMap<String, Value>,
+    ) -> Result<String> {
+        let mut msg = InboundMessage::new(channel, "user", chat_id, content);
+        msg.metadata = metadata;
+        self.process_inbound(msg, session_key, None).await
+    }
+
+    pub async fn process_direct_stream(
+        &self,
+        content: &str,
+        session_key: &str,
+        channel: &str,
+        chat_id: &str,
+        sink: StreamSink,
+    ) -> Result<String> {
+        let msg = InboundMessage::new(channel, "user", chat_id, content);
+        self.process_inbound(msg, session_key, Some(sink)).await
+    }
+
+    pub async fn process_inbound(
+        &self,
+        msg: InboundMessage,
+        session_key: &str,
+        sink: Option<StreamSink>,
+    ) -> Result<String> {
+        let response = self
+            .process_message_with_session(msg, session_key, sink.as_ref())
+            .await?;
         Ok(response.map(|m| m.content).unwrap_or_default())
     }
 
+    pub async fn session_metadata(&self, session_key: &str) -> Result<HashMap<String, Value>> {
+        let mut sessions = self.sessions.lock().await;
+        Ok(sessions.get_or_create(session_key)?.metadata.clone())
+    }
+
+    pub fn profile_names(&self) -> Vec<String> {
+        self.generation.profile_names()
+    }
+
+    pub async fn set_session_profile(
+        &self,
+        session_key: &str,
+        profile: Option<&str>,
+    ) -> Result<()> {
+        if let Some(name) = profile
+            && self.generation.profile(name).is_none()
+        {
+            bail!(
+                "unknown profile '{name}' (available: {})",
+                self.profile_names().join(", ")
+            );
+        }
+
+        let mut sessions = self.sessions.lock().await;
+        let session = sessions.get_or_create(session_key)?;
+        match profile {
+            Some(name) => {
+                session
+                    .metadata
+                    .insert("profile".to_string(), Value::String(name.to_string()));
+            }
+            None => {
+                session.metadata.remove("profile");
+            }
+        }
+        sessions.save(session_key)
+    }
+
     async fn process_message(&self, msg: InboundMessage) -> Result<Option<OutboundMessage>> {
         let session_key = msg.session_key();
-        self.process_message_with_session(msg, &session_key).await
+        self.process_message_with_session(msg, &session_key, None)
+            .await
+    }
+}
+
+fn error_reply(err: &anyhow::Error) -> String {
+    match err.downcast_ref::<ProviderError>() {
+        Some(ProviderError::RateLimited { .. }) => {
+            "Sorry, the model provider is rate limiting requests right now. Please try again shortly."
+                .to_string()
+        }
+        Some(ProviderError::ContextLengthExceeded(_)) => {
+            "Sorry, this conversation is too long for the model. Please start a new session."
+                .to_string()
+        }
+        Some(provider_err) => format!("Sorry, I couldn't reach the model: {provider_err}"),
+        None => "Sorry, something went wrong while processing your message.".to_string(),
     }
 }
@@ -1,14 +1,21 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use ferrumbot_core::{MessageBus, SessionManager};
+use ferrumbot_config::{CompactionConfig, ModelCapabilities, PricingTable, SubagentsConfig};
+use ferrumbot_core::{MessageBus, SessionManager, UsageLedger};
 use ferrumbot_cron::CronService;
-use ferrumbot_providers::LlmProvider;
-use ferrumbot_tools::ToolRegistry;
+use ferrumbot_providers::{LlmProvider, StreamEvent};
+use ferrumbot_tools::{SubagentManager, ToolRegistry};
 use tokio::sync::Mutex;
 
 use crate::context::ContextBuilder;
 
+pub use generation::GenerationSettings;
+use subagents::OpenTurn;
+
+pub type StreamSink = Arc<dyn Fn(&StreamEvent) + Send + Sync>;
+
 pub struct AgentLoop {
     pub(super) bus: MessageBus,
     pub(super) provider: Arc<dyn LlmProvider>,
@@ -16,12 +23,27 @@ pub struct AgentLoop {
     pub(super) model: String,
     pub(super) max_iterations: usize,
     pub(super) context: ContextBuilder,
-    pub(super) sessions: Mutex<SessionManager>,
+    pub(super) sessions: Arc<Mutex<SessionManager>>,
+    pub(super) usage: UsageLedger,
+    pub(super) pricing: PricingTable,
+    pub(super) generation: GenerationSettings,
+    pub(super) persist_reasoning: bool,
+    pub(super) capabilities: ModelCapabilities,
     pub(super) tools: ToolRegistry,
     pub(super) cron: Option<CronService>,
+    pub(super) compaction: CompactionConfig,
+    pub(super) subagents: SubagentManager,
+    pub(super) subagent_limits: SubagentsConfig,
+    pub(super) open_turns: Arc<Mutex<HashMap<String, OpenTurn>>>,
     pub(super) running: Mutex<bool>,
 }
 
+mod compaction;
 mod constructors;
+mod generation;
 mod lifecycle;
 mod processing;
+mod subagents;
+
+#[cfg(test)]
+mod tests;
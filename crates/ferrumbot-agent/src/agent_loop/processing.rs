@@ -1,43 +1,90 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
-use ferrumbot_core::{InboundMessage, OutboundMessage};
+use ferrumbot_core::{InboundMessage, OutboundMessage, Usage, UsageRecord};
+use ferrumbot_providers::{ChatOptions, LlmResponse, StreamAccumulator, fit_options};
 use ferrumbot_tools::ToolContext;
+use futures::StreamExt;
 use serde_json::{Value, json};
+use tracing::warn;
 
-use super::AgentLoop;
+use super::{AgentLoop, StreamSink};
 
 impl AgentLoop {
     pub(super) async fn process_message_with_session(
         &self,
         msg: InboundMessage,
         session_key: &str,
+        sink: Option<&StreamSink>,
     ) -> Result<Option<OutboundMessage>> {
-        let history = {
+        self.begin_turn(session_key).await;
+        let outcome = self.run_turn(msg, session_key, sink).await;
+        if let Err(err) = self.end_turn(session_key).await {
+            warn!("failed to save subagent reports for {session_key}: {err:#}");
+        }
+        outcome
+    }
+
+    async fn run_turn(
+        &self,
+        msg: InboundMessage,
+        session_key: &str,
+        sink: Option<&StreamSink>,
+    ) -> Result<Option<OutboundMessage>> {
+        if self.compaction.enabled
+            && let Err(err) = self.compact_session(session_key, false).await
+        {
+            warn!("failed to compact session {session_key}: {err:#}");
+        }
+
+        let (mut messages, options) = {
             let mut sessions = self.sessions.lock().await;
             let session = sessions.get_or_create(session_key)?;
-            session.get_history(50)
+            let profile = self.generation.select(&msg, &session.metadata);
+            let messages = self.context.build_messages(
+                session,
+                &msg.content,
+                &msg.media,
+                Some(&msg.channel),
+                Some(&msg.chat_id),
+            );
+            (messages, self.generation.options(profile.as_deref()))
         };
+        let turn_start = messages.len();
+        let options = fit_options(&self.capabilities, options);
 
-        let mut messages = self.context.build_messages(
-            history,
-            &msg.content,
-            Some(&msg.channel),
-            Some(&msg.chat_id),
-        );
-
-        let tool_defs = self.tools.definitions();
+        let tool_defs = self
+            .capabilities
+            .supports_tools()
+            .then(|| self.tools.definitions());
         let mut final_content = None;
+        let mut served_model = None;
+        let mut turn_usage = Usage::default();
+        let mut reasoning = Vec::new();
 
         for _ in 0..self.max_iterations {
-            let resp = self
-                .provider
-                .chat(
-                    messages.clone(),
-                    Some(tool_defs.clone()),
-                    Some(&self.model),
-                    None,
-                    None,
-                )
-                .await?;
+            let resp = match sink {
+                Some(sink) => {
+                    self.chat_streaming(messages.clone(), tool_defs.clone(), &options, sink)
+                        .await?
+                }
+                None => {
+                    self.provider
+                        .chat(
+                            messages.clone(),
+                            tool_defs.clone(),
+                            Some(&self.model),
+                            &options,
+                        )
+                        .await?
+                }
+            };
+            if resp.model.is_some() {
+                served_model = resp.model.clone();
+            }
+            let model = resp.model.as_deref().unwrap_or(&self.model);
+            turn_usage.add(&Usage::from_counts(&resp.usage, self.pricing.get(model)));
+            reasoning.extend(resp.reasoning.clone());
 
             if resp.has_tool_calls() {
                 let tool_calls: Vec<Value> = resp
@@ -57,6 +104,11 @@ impl AgentLoop {
 
                 self.context
                     .add_assistant(&mut messages, resp.content.clone(), Some(tool_calls));
+                if !resp.thinking_blocks.is_empty()
+                    && let Some(last) = messages.last_mut()
+                {
+                    last["thinking_blocks"] = Value::Array(resp.thinking_blocks.clone());
+                }
 
                 for call in resp.tool_calls {
                     let result = self
@@ -70,6 +122,8 @@ impl AgentLoop {
                                 current_chat_id: Some(msg.chat_id.clone()),
                                 bus: Some(self.bus.clone()),
                                 cron: self.cron.clone(),
+                                session_key: Some(session_key.to_string()),
+                                subagents: Some(self.subagents.clone()),
                             },
                         )
                         .await;
@@ -85,22 +139,81 @@ impl AgentLoop {
         let final_content = final_content.unwrap_or_else(|| {
             "I've completed processing but have no response to give.".to_string()
         });
+        let served_model = served_model.unwrap_or_else(|| self.model.clone());
 
         {
             let mut sessions = self.sessions.lock().await;
             let session = sessions.get_or_create(session_key)?;
-            session.add_message("user", &msg.content);
-            session.add_message("assistant", &final_content);
+            session.add_message_with_media("user", &msg.content, &msg.media);
+            for step in &messages[turn_start..] {
+                let content = step["content"].as_str().unwrap_or_default();
+                match step["tool_calls"].as_array() {
+                    Some(calls) => session.add_tool_calls(
+                        content,
+                        calls.clone(),
+                        step["thinking_blocks"]
+                            .as_array()
+                            .cloned()
+                            .unwrap_or_default(),
+                    ),
+                    None => session.add_tool_result(
+                        step["tool_call_id"].as_str().unwrap_or_default(),
+                        step["name"].as_str().unwrap_or_default(),
+                        content,
+                    ),
+                }
+            }
+            let reasoning = reasoning.join("\n\n");
+            let persisted =
+                (self.persist_reasoning && !reasoning.is_empty()).then_some(reasoning.as_str());
+            session.add_assistant_message(&final_content, persisted);
+            session
+                .metadata
+                .insert("model".to_string(), Value::String(served_model.clone()));
+            let mut session_usage = Usage::from_metadata(&session.metadata);
+            session_usage.add(&turn_usage);
+            session
+                .metadata
+                .insert("usage".to_string(), serde_json::to_value(&session_usage)?);
             sessions.save(session_key)?;
         }
 
+        let record = UsageRecord::new(session_key, &served_model, turn_usage.clone());
+        if let Err(err) = self.usage.record(&record) {
+            warn!("failed to record usage: {err:#}");
+        }
+
         Ok(Some(OutboundMessage {
             channel: msg.channel,
             chat_id: msg.chat_id,
             content: final_content,
             reply_to: None,
             media: Vec::new(),
-            metadata: Default::default(),
+            metadata: HashMap::from([
+                ("model".to_string(), Value::String(served_model)),
+                ("usage".to_string(), serde_json::to_value(&turn_usage)?),
+            ]),
         }))
     }
+
+    async fn chat_streaming(
+        &self,
+        messages: Vec<Value>,
+        tool_defs: Option<Vec<Value>>,
+        options: &ChatOptions,
+        sink: &StreamSink,
+    ) -> Result<LlmResponse> {
+        let mut stream = self
+            .provider
+            .chat_stream(messages, tool_defs, Some(&self.model), options)
+            .await?;
+
+        let mut acc = StreamAccumulator::default();
+        while let Some(event) = stream.next().await {
+            let event = event?;
+            sink(&event);
+            acc.push(&event);
+        }
+        Ok(acc.finish())
+    }
 }
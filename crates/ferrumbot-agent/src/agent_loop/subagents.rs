@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use ferrumbot_core::{InboundMessage, OutboundMessage};
+use ferrumbot_tools::{SubagentManager, SubagentTask, boxed_runner};
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+use super::AgentLoop;
+
+#[derive(Default)]
+pub(crate) struct OpenTurn {
+    depth: usize,
+    reports: Vec<String>,
+}
+
+impl AgentLoop {
+    pub fn subagents(&self) -> &SubagentManager {
+        &self.subagents
+    }
+
+    pub fn enable_subagents(self: &Arc<Self>) {
+        let agent = Arc::downgrade(self);
+        self.subagents.set_runner(boxed_runner(move |task| {
+            let agent = agent.clone();
+            async move {
+                let agent = agent
+                    .upgrade()
+                    .ok_or_else(|| anyhow!("agent is shutting down"))?;
+                agent.run_subagent(task).await
+            }
+        }));
+    }
+
+    async fn run_subagent(&self, task: SubagentTask) -> Result<String> {
+        info!("subagent {} started: {}", task.id, task.label);
+        let child = self.subagent_loop();
+        let session_key = format!("subagent:{}", task.id);
+        let msg = InboundMessage::new(&task.channel, "subagent", &task.chat_id, &task.task);
+        let timeout = Duration::from_secs(self.subagent_limits.timeout_secs);
+
+        let mut cancelled = false;
+        let outcome = tokio::select! {
+            outcome = tokio::time::timeout(timeout, child.process_inbound(msg, &session_key, None)) => {
+                outcome.unwrap_or_else(|_| Err(anyhow!("timed out after {}s", timeout.as_secs())))
+            }
+            _ = task.cancel.notified() => {
+                cancelled = true;
+                Err(anyhow!("cancelled"))
+            }
+        };
+        let report = match &outcome {
+            Ok(result) => format!("Background task '{}' finished:\n\n{result}", task.label),
+            Err(_) if cancelled => format!("Background task '{}' was cancelled.", task.label),
+            Err(err) => format!("Background task '{}' failed: {err:#}", task.label),
+        };
+        info!("subagent {} ended: ok={}", task.id, outcome.is_ok());
+
+        if let Err(err) = self.deliver_subagent_report(&task, &report).await {
+            error!("failed to deliver subagent {} result: {err:#}", task.id);
+        }
+        outcome
+    }
+
+    fn subagent_loop(&self) -> AgentLoop {
+        let limits = &self.subagent_limits;
+        let mut tools = self.tools.clone();
+        if !limits.allow_spawn {
+            tools.unregister("spawn");
+        }
+        if !limits.tools.is_empty() {
+            tools.retain(|name| limits.tools.iter().any(|allowed| allowed == name));
+        }
+
+        AgentLoop {
+            bus: self.bus.clone(),
+            provider: self.provider.clone(),
+            workspace: self.workspace.clone(),
+            model: self.model.clone(),
+            max_iterations: limits.max_iterations,
+            context: self.context.clone(),
+            sessions: self.sessions.clone(),
+            usage: self.usage.clone(),
+            pricing: self.pricing.clone(),
+            generation: self.generation.clone(),
+            persist_reasoning: self.persist_reasoning,
+            capabilities: self.capabilities.clone(),
+            tools,
+            cron: self.cron.clone(),
+            compaction: self.compaction.clone(),
+            subagents: self.subagents.clone(),
+            subagent_limits: limits.clone(),
+            open_turns: self.open_turns.clone(),
+            running: Mutex::new(false),
+        }
+    }
+
+    pub(super) async fn begin_turn(&self, session_key: &str) {
+        let mut turns = self.open_turns.lock().await;
+        turns.entry(session_key.to_string()).or_default().depth += 1;
+    }
+
+    pub(super) async fn end_turn(&self, session_key: &str) -> Result<()> {
+        let mut sessions = self.sessions.lock().await;
+        let mut turns = self.open_turns.lock().await;
+        let Some(turn) = turns.get_mut(session_key) else {
+            return Ok(());
+        };
+        turn.depth -= 1;
+        if turn.depth > 0 {
+            return Ok(());
+        }
+        let reports = turns.remove(session_key).unwrap_or_default().reports;
+        if reports.is_empty() {
+            return Ok(());
+        }
+        let session = sessions.get_or_create(session_key)?;
+        for report in &reports {
+            session.add_assistant_message(report, None);
+        }
+        sessions.save(session_key)
+    }
+
+    pub(super) async fn deliver_subagent_report(
+        &self,
+        task: &SubagentTask,
+        report: &str,
+    ) -> Result<()> {
+        {
+            let mut sessions = self.sessions.lock().await;
+            let mut turns = self.open_turns.lock().await;
+            match turns.get_mut(&task.session_key) {
+                Some(turn) => turn.reports.push(report.to_string()),
+                None => {
+                    sessions
+                        .get_or_create(&task.session_key)?
+                        .add_assistant_message(report, None);
+                    sessions.save(&task.session_key)?;
+                }
+            }
+        }
+
+        self.bus
+            .publish_outbound(OutboundMessage {
+                channel: task.channel.clone(),
+                chat_id: task.chat_id.clone(),
+                content: report.to_string(),
+                reply_to: None,
+                media: Vec::new(),
+                metadata: HashMap::from([("subagent".to_string(), Value::String(task.id.clone()))]),
+            })
+            .await
+    }
+}
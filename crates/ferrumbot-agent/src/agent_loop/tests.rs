@@ -0,0 +1,465 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ferrumbot_config::{CompactionConfig, GenerationProfile, ModelCapabilities};
+use ferrumbot_core::{InboundMessage, MessageBus, SessionManager, Usage};
+use ferrumbot_cron::{CronService, boxed_callback};
+use ferrumbot_providers::{
+    LlmProvider, LlmResponse, RecordingProvider, ReplayProvider, ScriptedProvider, ToolCallRequest,
+};
+use ferrumbot_tools::SubagentTask;
+use serde_json::json;
+
+use super::constructors::ToolingConfig;
+use super::{AgentLoop, GenerationSettings};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ferrumbot-agent-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("workspace")).unwrap();
+    dir
+}
+
+fn agent(dir: &Path, provider: Arc<dyn LlmProvider>, cron: Option<CronService>) -> AgentLoop {
+    AgentLoop::new(
+        MessageBus::new(16),
+        provider,
+        dir.join("workspace"),
+        "test/model".to_string(),
+        5,
+        cron,
+        ToolingConfig {
+            web: Default::default(),
+            memory: Default::default(),
+            exec_timeout: 5,
+            restrict_to_workspace: true,
+        },
+    )
+    .unwrap()
+    .with_data_dir(&dir.join("data"))
+    .unwrap()
+}
+
+fn tool_call(id: &str, name: &str, arguments: serde_json::Value) -> LlmResponse {
+    let mut resp = LlmResponse::with_tool_calls(vec![ToolCallRequest {
+        id: id.to_string(),
+        name: name.to_string(),
+        arguments,
+    }]);
+    resp.usage = BTreeMap::from([
+        ("prompt_tokens".to_string(), 120),
+        ("completion_tokens".to_string(), 15),
+    ]);
+    resp
+}
+
+#[tokio::test]
+async fn tool_loop_runs_and_persists_session() {
+    let dir = scratch_dir("tool-loop");
+    let note = dir.join("workspace").join("note.txt");
+    std::fs::write(&note, "hello from disk").unwrap();
+    let provider = Arc::new(ScriptedProvider::new(vec![
+        tool_call("call_1", "read_file", json!({"path": note})),
+        LlmResponse::text("The note says hello."),
+        LlmResponse::text("It was note.txt."),
+    ]));
+    let agent = agent(&dir, provider.clone(), None);
+
+    let reply = agent
+        .process_direct("What is in note.txt?", "cli:test", "cli", "direct")
+        .await
+        .unwrap();
+    assert_eq!(reply, "The note says hello.");
+
+    let requests = provider.requests();
+    assert_eq!(requests.len(), 2);
+    let tool_result = requests[1].last().unwrap();
+    assert_eq!(tool_result["role"], "tool");
+    assert!(
+        tool_result["content"]
+            .as_str()
+            .unwrap()
+            .contains("hello from disk")
+    );
+
+    let mut sessions = SessionManager::open(dir.join("data").join("sessions")).unwrap();
+    let session = sessions.get_or_create("cli:test").unwrap();
+    let roles: Vec<&str> = session.messages.iter().map(|m| m.role.as_str()).collect();
+    assert_eq!(roles, ["user", "assistant", "tool", "assistant"]);
+    assert_eq!(session.messages[1].tool_calls[0]["id"], "call_1");
+    assert_eq!(session.messages[2].name.as_deref(), Some("read_file"));
+    assert!(session.messages[2].content.contains("hello from disk"));
+    assert_eq!(session.messages[3].content, "The note says hello.");
+    assert_eq!(session.metadata["model"], "test/model");
+    let usage = Usage::from_metadata(&session.metadata);
+    assert_eq!(usage.requests, 2);
+    assert_eq!(usage.prompt_tokens, 120);
+
+    agent
+        .process_direct("Which file was that?", "cli:test", "cli", "direct")
+        .await
+        .unwrap();
+    let requests = provider.requests();
+    let replayed: Vec<&str> = requests[2]
+        .iter()
+        .map(|m| m["role"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        replayed,
+        ["system", "user", "assistant", "tool", "assistant", "user"]
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn cron_jobs_added_by_tools_run_through_the_agent() {
+    let dir = scratch_dir("cron");
+    let cron = CronService::new(dir.join("cron").join("jobs.json"))
+        .await
+        .unwrap();
+    let provider = Arc::new(ScriptedProvider::new(vec![
+        tool_call(
+            "call_1",
+            "cron",
+            json!({"action": "add", "name": "standup", "message": "post standup", "every": 3600}),
+        ),
+        LlmResponse::text("Scheduled."),
+        LlmResponse::text("Standup posted."),
+    ]));
+    let agent = Arc::new(agent(&dir, provider.clone(), Some(cron.clone())));
+
+    agent
+        .process_direct("remind me hourly", "cli:test", "cli", "direct")
+        .await
+        .unwrap();
+    let jobs = cron.list_jobs(true).await;
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].payload.message, "post standup");
+
+    let callback_agent = agent.clone();
+    cron.set_on_job(boxed_callback(move |job| {
+        let agent = callback_agent.clone();
+        async move {
+            let response = agent
+                .process_direct(
+                    &job.payload.message,
+                    &format!("cron:{}", job.id),
+                    "cli",
+                    "direct",
+                )
+                .await?;
+            Ok(Some(response))
+        }
+    }))
+    .await;
+    assert!(cron.run_job(&jobs[0].id, true).await.unwrap());
+    assert_eq!(provider.remaining(), 0);
+
+    let mut sessions = SessionManager::open(dir.join("data").join("sessions")).unwrap();
+    let session = sessions
+        .get_or_create(&format!("cron:{}", jobs[0].id))
+        .unwrap();
+    assert_eq!(session.messages[0].content, "post standup");
+    assert_eq!(session.messages[1].content, "Standup posted.");
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn spawned_subagents_report_back_to_the_parent_session() {
+    let dir = scratch_dir("subagents");
+    let provider = Arc::new(ScriptedProvider::new(vec![
+        tool_call(
+            "call_1",
+            "spawn",
+            json!({"task": "Count the files in the workspace", "label": "count files"}),
+        ),
+        LlmResponse::text("Started a background task."),
+        LlmResponse::text("There are 3 files."),
+    ]));
+    let agent = Arc::new(agent(&dir, provider.clone(), None));
+    agent.enable_subagents();
+
+    agent
+        .process_direct(
+            "count my files in the background",
+            "cli:test",
+            "cli",
+            "direct",
+        )
+        .await
+        .unwrap();
+    let spawned = agent.subagents().list(Some("cli:test"));
+    assert_eq!(spawned.len(), 1);
+    let id = spawned[0].task.id.clone();
+
+    let report = tokio::time::timeout(Duration::from_secs(5), agent.bus.consume_outbound())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        (report.channel.as_str(), report.chat_id.as_str()),
+        ("cli", "direct")
+    );
+    assert!(report.content.contains("There are 3 files."));
+    assert_eq!(report.metadata["subagent"], id.as_str());
+    assert_eq!(
+        provider.requests()[2].last().unwrap()["content"],
+        "Count the files in the workspace"
+    );
+
+    let mut sessions = agent.sessions.lock().await;
+    let parent = sessions.get_or_create("cli:test").unwrap();
+    assert_eq!(parent.messages.len(), 5);
+    assert!(
+        parent.messages[4]
+            .content
+            .contains("'count files' finished")
+    );
+    let child = sessions.get_or_create(&format!("subagent:{id}")).unwrap();
+    assert_eq!(child.messages[1].content, "There are 3 files.");
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn subagent_reports_wait_for_the_open_parent_turn() {
+    let dir = scratch_dir("subagent-order");
+    let agent = agent(&dir, Arc::new(ScriptedProvider::new(Vec::new())), None);
+    let task = SubagentTask {
+        id: "abcd1234".to_string(),
+        label: "count files".to_string(),
+        task: "Count the files".to_string(),
+        channel: "cli".to_string(),
+        chat_id: "direct".to_string(),
+        session_key: "cli:test".to_string(),
+        cancel: Default::default(),
+    };
+
+    agent.begin_turn("cli:test").await;
+    agent
+        .deliver_subagent_report(&task, "Background task 'count files' finished")
+        .await
+        .unwrap();
+    {
+        let mut sessions = agent.sessions.lock().await;
+        let session = sessions.get_or_create("cli:test").unwrap();
+        assert!(session.messages.is_empty());
+        session.add_message("user", "count my files in the background");
+        session.add_assistant_message("Started a background task.", None);
+    }
+    agent.end_turn("cli:test").await.unwrap();
+
+    let mut sessions = agent.sessions.lock().await;
+    let contents: Vec<String> = sessions
+        .get_or_create("cli:test")
+        .unwrap()
+        .messages
+        .iter()
+        .map(|m| m.content.clone())
+        .collect();
+    assert_eq!(
+        contents,
+        [
+            "count my files in the background",
+            "Started a background task.",
+            "Background task 'count files' finished",
+        ]
+    );
+    drop(sessions);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn long_sessions_are_compacted_into_a_summary() {
+    let dir = scratch_dir("compaction");
+    {
+        let mut sessions = SessionManager::open(dir.join("data").join("sessions")).unwrap();
+        let session = sessions.get_or_create("cli:test").unwrap();
+        for turn in 0..6 {
+            session.add_message("user", &format!("question {turn}"));
+            session.add_message("assistant", &format!("answer {turn}"));
+        }
+        sessions.save("cli:test").unwrap();
+    }
+    let provider = Arc::new(ScriptedProvider::new(vec![
+        LlmResponse::text("The user asked six numbered questions."),
+        LlmResponse::text("You started with question 0."),
+    ]));
+    let agent = agent(&dir, provider.clone(), None)
+        .with_history_budget(4, 100_000, 1_000)
+        .with_compaction(CompactionConfig {
+            enabled: true,
+            keep_recent: 2,
+        });
+
+    agent
+        .process_direct("what did I ask first?", "cli:test", "cli", "direct")
+        .await
+        .unwrap();
+
+    let requests = provider.requests();
+    let summarize = requests[0][1]["content"].as_str().unwrap();
+    assert!(summarize.contains("user: question 0"));
+    assert!(!summarize.contains("question 5"));
+
+    let turn = &requests[1];
+    let system = turn[0]["content"].as_array().unwrap();
+    assert!(
+        system[2]["text"]
+            .as_str()
+            .unwrap()
+            .contains("six numbered questions")
+    );
+    assert_eq!(turn.len(), 4);
+    assert_eq!(turn[1]["content"], "question 5");
+
+    let metadata = agent.session_metadata("cli:test").await.unwrap();
+    assert_eq!(metadata["compacted"], 10);
+    assert_eq!(provider.remaining(), 0);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn recorded_cassette_replays_the_same_turn() {
+    let dir = scratch_dir("cassette");
+    let note = dir.join("workspace").join("note.txt");
+    std::fs::write(&note, "replayed").unwrap();
+    let cassette = dir.join("turn.jsonl");
+    let scripted = Arc::new(ScriptedProvider::new(vec![
+        tool_call("call_1", "read_file", json!({"path": note})),
+        LlmResponse::text("It says replayed."),
+    ]));
+    let recorder = Arc::new(RecordingProvider::new(scripted, &cassette));
+    let recorded = agent(&dir, recorder, None)
+        .process_direct("Read note.txt", "cli:record", "cli", "direct")
+        .await
+        .unwrap();
+
+    let replay = Arc::new(ReplayProvider::load(&cassette, "test/model").unwrap());
+    let replayed = agent(&dir, replay, None)
+        .process_direct("Read note.txt", "cli:replay", "cli", "direct")
+        .await
+        .unwrap();
+    assert_eq!(recorded, replayed);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn generation_profiles_follow_message_session_and_channel() {
+    let dir = scratch_dir("profiles");
+    let provider = Arc::new(ScriptedProvider::new(vec![
+        LlmResponse::text("one"),
+        LlmResponse::text("two"),
+        LlmResponse::text("three"),
+        LlmResponse::text("four"),
+    ]));
+    let agent = agent(&dir, provider.clone(), None).with_generation(GenerationSettings {
+        base: GenerationProfile {
+            max_tokens: Some(8192),
+            temperature: Some(0.7),
+            ..Default::default()
+        },
+        channel_profiles: BTreeMap::from([("telegram".to_string(), "precise".to_string())]),
+        ..Default::default()
+    });
+
+    agent
+        .process_direct("hi", "cli:a", "cli", "direct")
+        .await
+        .unwrap();
+    agent
+        .process_direct("hi", "telegram:1", "telegram", "1")
+        .await
+        .unwrap();
+    agent
+        .set_session_profile("cli:a", Some("creative"))
+        .await
+        .unwrap();
+    agent
+        .process_direct("hi", "cli:a", "cli", "direct")
+        .await
+        .unwrap();
+    agent
+        .process_direct_with_metadata(
+            "hi",
+            "cli:a",
+            "cli",
+            "direct",
+            HashMap::from([("profile".to_string(), json!("precise"))]),
+        )
+        .await
+        .unwrap();
+    assert!(
+        agent
+            .set_session_profile("cli:a", Some("nope"))
+            .await
+            .is_err()
+    );
+
+    let temperatures: Vec<Option<f32>> = provider.options().iter().map(|o| o.temperature).collect();
+    assert_eq!(
+        temperatures,
+        vec![Some(0.7), Some(0.1), Some(1.0), Some(0.1)]
+    );
+    assert!(
+        provider
+            .options()
+            .iter()
+            .all(|o| o.max_tokens == Some(8192))
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn capabilities_shape_request_options() {
+    let dir = scratch_dir("capabilities");
+    let provider = Arc::new(ScriptedProvider::new(vec![LlmResponse::text("ok")]));
+    let agent = agent(&dir, provider.clone(), None)
+        .with_generation(GenerationSettings {
+            base: GenerationProfile {
+                max_tokens: Some(8192),
+                reasoning_effort: Some("high".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_capabilities(ModelCapabilities {
+            max_output_tokens: Some(4096),
+            parallel_tools: Some(false),
+            reasoning: Some(false),
+            tools: Some(false),
+            vision: Some(false),
+            ..Default::default()
+        });
+    let image = dir.join("workspace").join("photo.png");
+    std::fs::write(&image, b"\x89PNG\r\n\x1a\nrest").unwrap();
+    let mut msg = InboundMessage::new("cli", "user", "direct", "what is this?");
+    msg.media = vec![image.display().to_string()];
+
+    agent.process_inbound(msg, "cli:caps", None).await.unwrap();
+
+    let options = &provider.options()[0];
+    assert_eq!(options.max_tokens, Some(4096));
+    assert_eq!(options.reasoning_effort, None);
+    assert_eq!(options.parallel_tool_calls, Some(false));
+    assert!(provider.tools()[0].is_none());
+    let parts = provider.requests()[0].last().unwrap()["content"].clone();
+    let parts = parts.as_array().unwrap();
+    assert!(parts.iter().all(|part| part["type"] == "text"));
+    assert!(
+        parts[1]["text"]
+            .as_str()
+            .unwrap()
+            .contains("does not accept images")
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
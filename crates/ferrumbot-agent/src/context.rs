@@ -1,22 +1,81 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 
+use ferrumbot_core::{HeuristicEstimator, Session, TokenEstimator, ToolReplay};
 use serde_json::{Value, json};
 
+use crate::media::media_parts;
+
+#[derive(Clone)]
 pub struct ContextBuilder {
     workspace: PathBuf,
+    estimator: Arc<dyn TokenEstimator>,
+    max_history: usize,
+    token_budget: Option<usize>,
+    tool_replay: ToolReplay,
+    max_media_bytes: u64,
+    media_dir: Option<PathBuf>,
+    vision: bool,
 }
 
 impl ContextBuilder {
     pub fn new(workspace: PathBuf) -> Self {
-        Self { workspace }
+        Self {
+            workspace,
+            estimator: Arc::new(HeuristicEstimator),
+            max_history: 50,
+            token_budget: None,
+            tool_replay: ToolReplay::Truncated(2000),
+            max_media_bytes: 5 * 1024 * 1024,
+            media_dir: None,
+            vision: true,
+        }
+    }
+
+    pub fn set_media_limit(&mut self, max_bytes: u64) {
+        self.max_media_bytes = max_bytes;
+    }
+
+    pub fn restrict_media_to(&mut self, dir: Option<PathBuf>) {
+        self.media_dir = dir;
+    }
+
+    pub fn set_vision(&mut self, enabled: bool) {
+        self.vision = enabled;
     }
 
-    pub fn build_system_prompt(&self, channel: Option<&str>, chat_id: Option<&str>) -> String {
-        let now = chrono::Local::now()
-            .format("%Y-%m-%d %H:%M (%A)")
-            .to_string();
+    pub fn set_history_limits(&mut self, max_messages: usize, token_budget: Option<usize>) {
+        self.max_history = max_messages;
+        self.token_budget = token_budget;
+    }
+
+    pub fn set_tool_replay(&mut self, replay: ToolReplay) {
+        self.tool_replay = replay;
+    }
+
+    pub fn set_estimator(&mut self, estimator: Arc<dyn TokenEstimator>) {
+        self.estimator = estimator;
+    }
+
+    pub fn needs_compaction(&self, session: &Session) -> bool {
+        let pending = &session.messages[session.compacted()..];
+        if pending.len() > self.max_history {
+            return true;
+        }
+        let Some(budget) = self.token_budget else {
+            return false;
+        };
+        let used: usize = session
+            .get_history_replaying(pending.len(), self.tool_replay)
+            .iter()
+            .map(|message| self.estimator.estimate_message(message))
+            .sum();
+        used > budget * 3 / 4
+    }
+
+    fn static_prompt(&self) -> String {
         let mut parts = vec![format!(
-            "# ferrum-bot\n\nYou are ferrum-bot, a helpful Rust AI assistant.\n\nCurrent Time: {now}\nWorkspace: {}",
+            "# ferrum-bot\n\nYou are ferrum-bot, a helpful Rust AI assistant.\n\nWorkspace: {}",
             self.workspace.display()
         )];
 
@@ -51,6 +110,7 @@ impl ContextBuilder {
                     }
                 }
             }
+            summary.sort();
             if !summary.is_empty() {
                 parts.push(format!(
                     "## Skills\n\nUse read_file to load full skill docs when needed.\n{}",
@@ -59,28 +119,66 @@ impl ContextBuilder {
             }
         }
 
-        if let (Some(channel), Some(chat_id)) = (channel, chat_id) {
-            parts.push(format!(
-                "## Current Session\nChannel: {channel}\nChat ID: {chat_id}"
-            ));
-        }
-
         parts.join("\n\n---\n\n")
     }
 
     pub fn build_messages(
         &self,
-        history: Vec<Value>,
+        session: &Session,
         current_message: &str,
+        media: &[String],
         channel: Option<&str>,
         chat_id: Option<&str>,
     ) -> Vec<Value> {
-        let mut messages = vec![json!({
+        let mut system = json!({
             "role": "system",
-            "content": self.build_system_prompt(channel, chat_id),
-        })];
+            "content": [
+                {
+                    "type": "text",
+                    "text": self.static_prompt(),
+                    "cache_control": {"type": "ephemeral"},
+                },
+                {"type": "text", "text": session_prompt(channel, chat_id)},
+            ],
+        });
+        if let Some(summary) = session.summary()
+            && let Some(parts) = system["content"].as_array_mut()
+        {
+            parts.push(json!({
+                "type": "text",
+                "text": format!("## Earlier Conversation (summary)\n{summary}"),
+            }));
+        }
+        let current = if media.is_empty() {
+            json!({"role": "user", "content": current_message})
+        } else {
+            let mut parts = vec![json!({"type": "text", "text": current_message})];
+            parts.extend(media_parts(
+                media,
+                self.max_media_bytes,
+                self.vision,
+                self.media_dir.as_deref(),
+            ));
+            json!({"role": "user", "content": parts})
+        };
+
+        let history = match self.token_budget {
+            Some(budget) => {
+                let reserved = self.estimator.estimate_message(&system)
+                    + self.estimator.estimate_message(&current);
+                session.get_history_within(
+                    self.max_history,
+                    budget.saturating_sub(reserved),
+                    self.tool_replay,
+                    self.estimator.as_ref(),
+                )
+            }
+            None => session.get_history_replaying(self.max_history, self.tool_replay),
+        };
+
+        let mut messages = vec![system];
         messages.extend(history);
-        messages.push(json!({"role": "user", "content": current_message}));
+        messages.push(current);
         messages
     }
 
@@ -112,3 +210,14 @@ impl ContextBuilder {
         }));
     }
 }
+
+fn session_prompt(channel: Option<&str>, chat_id: Option<&str>) -> String {
+    let now = chrono::Local::now()
+        .format("%Y-%m-%d %H:%M (%A)")
+        .to_string();
+    let mut prompt = format!("## Current Session\nCurrent Time: {now}");
+    if let (Some(channel), Some(chat_id)) = (channel, chat_id) {
+        prompt.push_str(&format!("\nChannel: {channel}\nChat ID: {chat_id}"));
+    }
+    prompt
+}
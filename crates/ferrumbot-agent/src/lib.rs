@@ -1,6 +1,7 @@
 mod agent_loop;
 mod context;
+mod media;
 mod memory;
 
-pub use agent_loop::AgentLoop;
+pub use agent_loop::{AgentLoop, GenerationSettings, StreamSink};
 pub use memory::ensure_memory_files;
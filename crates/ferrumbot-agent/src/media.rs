@@ -0,0 +1,156 @@
+use std::path::Path;
+
+use ferrumbot_tools::resolve_path;
+
+use anyhow::{Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::{Value, json};
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "gif", "webp"];
+const TEXT_EXTENSIONS: [&str; 9] = [
+    "txt", "md", "csv", "json", "log", "yaml", "yml", "toml", "xml",
+];
+
+pub(crate) fn media_parts(
+    media: &[String],
+    max_bytes: u64,
+    images: bool,
+    allowed_dir: Option<&Path>,
+) -> Vec<Value> {
+    media
+        .iter()
+        .map(|reference| {
+            let part = media_part(reference, max_bytes, allowed_dir).and_then(|part| {
+                if !images && part["type"] == "image_url" {
+                    bail!("the model does not accept images");
+                }
+                Ok(part)
+            });
+            part.unwrap_or_else(|err| {
+                json!({"type": "text", "text": format!("[attachment {reference} skipped: {err}]")})
+            })
+        })
+        .collect()
+}
+
+fn media_part(reference: &str, max_bytes: u64, allowed_dir: Option<&Path>) -> Result<Value> {
+    if reference.starts_with("http://") || reference.starts_with("https://") {
+        return url_part(reference);
+    }
+
+    let path = resolve_path(reference, allowed_dir)?;
+    let size = std::fs::metadata(&path)?.len();
+    if size > max_bytes {
+        bail!("{size} bytes exceeds the {max_bytes} byte limit");
+    }
+    let bytes = std::fs::read(&path)?;
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| reference.to_string());
+
+    match sniff_mime(&bytes, &path) {
+        Some(mime) if mime.starts_with("image/") => Ok(json!({
+            "type": "image_url",
+            "image_url": {"url": format!("data:{mime};base64,{}", STANDARD.encode(&bytes))},
+        })),
+        Some("application/pdf") => Ok(json!({
+            "type": "file",
+            "file": {
+                "filename": name,
+                "file_data": format!("data:application/pdf;base64,{}", STANDARD.encode(&bytes)),
+            },
+        })),
+        Some(_) => Ok(json!({
+            "type": "text",
+            "text": format!("[file {name}]\n{}", String::from_utf8_lossy(&bytes)),
+        })),
+        None => bail!("unsupported file type"),
+    }
+}
+
+fn url_part(url: &str) -> Result<Value> {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let extension = extension_of(Path::new(path));
+    if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        return Ok(json!({"type": "image_url", "image_url": {"url": url}}));
+    }
+    Ok(json!({"type": "text", "text": format!("[linked document: {url}]")}))
+}
+
+pub(crate) fn sniff_mime(bytes: &[u8], path: &Path) -> Option<&'static str> {
+    let magic: [(&[u8], &str); 5] = [
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF8", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"RIFF", "image/webp"),
+    ];
+    for (prefix, mime) in magic {
+        if bytes.starts_with(prefix) && (mime != "image/webp" || bytes.get(8..12) == Some(b"WEBP"))
+        {
+            return Some(mime);
+        }
+    }
+
+    let text = std::str::from_utf8(bytes).is_ok() && !bytes.contains(&0);
+    if text && TEXT_EXTENSIONS.contains(&extension_of(path).as_str()) {
+        return Some("text/plain");
+    }
+    None
+}
+
+fn extension_of(path: &Path) -> String {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_local_and_remote_media() {
+        let dir = std::env::temp_dir().join(format!("ferrumbot-media-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let png = dir.join("pixel.png");
+        std::fs::write(&png, b"\x89PNG\r\n\x1a\n0000").unwrap();
+        let notes = dir.join("notes.md");
+        std::fs::write(&notes, "# todo").unwrap();
+        let blob = dir.join("blob.bin");
+        std::fs::write(&blob, [0u8, 159, 146, 150]).unwrap();
+        let secret = dir.join(".env");
+        std::fs::write(&secret, "API_KEY=sk-123").unwrap();
+
+        let media = [
+            png.display().to_string(),
+            notes.display().to_string(),
+            blob.display().to_string(),
+            "https://example.com/cat.JPG?size=large".to_string(),
+            secret.display().to_string(),
+        ];
+        let parts = media_parts(&media, 1024, true, None);
+        assert!(
+            parts[0]["image_url"]["url"]
+                .as_str()
+                .unwrap()
+                .starts_with("data:image/png;base64,")
+        );
+        assert_eq!(parts[1]["text"], "[file notes.md]\n# todo");
+        assert!(parts[2]["text"].as_str().unwrap().contains("unsupported"));
+        assert_eq!(parts[3]["image_url"]["url"], media[3]);
+        assert!(parts[4]["text"].as_str().unwrap().contains("unsupported"));
+
+        let limited = media_parts(&media[..1], 4, true, None);
+        assert!(limited[0]["text"].as_str().unwrap().contains("limit"));
+        let text_only = media_parts(&media[..1], 1024, false, None);
+        assert!(text_only[0]["text"].as_str().unwrap().contains("images"));
+        let confined = media_parts(&media[..1], 1024, true, Some(&dir.join("inbox")));
+        assert!(confined[0]["text"].as_str().unwrap().contains("outside"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use anyhow::Result;
 use tokio::sync::RwLock;
 
 use ferrumbot_config::Config;
@@ -11,7 +12,7 @@ use crate::{StubChannel, WhatsAppCloudChannel};
 use super::ChannelManager;
 
 impl ChannelManager {
-    pub fn new(config: &Config, bus: MessageBus) -> Self {
+    pub fn new(config: &Config, bus: MessageBus) -> Result<Self> {
         let mut channels = HashMap::new();
 
         if config.channels.telegram.enabled {
@@ -47,16 +48,18 @@ impl ChannelManager {
         if config.channels.whatsapp.enabled {
             channels.insert(
                 "whatsapp".to_string(),
-                Arc::new(WhatsAppCloudChannel::new(config.channels.clone()))
-                    as Arc<dyn crate::BaseChannel>,
+                Arc::new(WhatsAppCloudChannel::new(
+                    config.channels.clone(),
+                    &config.http.overlay(&config.channels.http),
+                )?) as Arc<dyn crate::BaseChannel>,
             );
         }
 
-        Self {
+        Ok(Self {
             bus,
             channels,
             dispatch_task: Arc::new(RwLock::new(None)),
-        }
+        })
     }
 
     pub fn enabled_channels(&self) -> Vec<String> {
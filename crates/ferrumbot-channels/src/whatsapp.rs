@@ -6,7 +6,7 @@ use reqwest::Client;
 use tokio::sync::RwLock;
 use tracing::{error, info, warn};
 
-use ferrumbot_config::{ChannelsConfig, WhatsAppCloudApiConfig};
+use ferrumbot_config::{ChannelsConfig, HttpConfig, WhatsAppCloudApiConfig};
 use ferrumbot_core::OutboundMessage;
 
 use crate::BaseChannel;
@@ -18,12 +18,12 @@ pub struct WhatsAppCloudChannel {
 }
 
 impl WhatsAppCloudChannel {
-    pub fn new(channels: ChannelsConfig) -> Self {
-        Self {
+    pub fn new(channels: ChannelsConfig, http: &HttpConfig) -> Result<Self> {
+        Ok(Self {
             cloud: channels.whatsapp.cloud_api,
             running: Arc::new(RwLock::new(false)),
-            client: Client::new(),
-        }
+            client: http.client()?,
+        })
     }
 
     fn api_url(&self) -> String {
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use ferrumbot_config::VERSION;
 
 use crate::commands;
@@ -19,6 +19,9 @@ pub enum Commands {
     Gateway(GatewayArgs),
     Channels(ChannelsCommand),
     Cron(CronCommand),
+    Usage(UsageArgs),
+    Models(ModelsCommand),
+    Providers(ProvidersCommand),
 }
 
 #[derive(Args, Debug)]
@@ -29,6 +32,58 @@ pub struct AgentArgs {
     pub session: String,
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum UsageGroup {
+    Day,
+    Session,
+    Model,
+}
+
+#[derive(Args, Debug)]
+pub struct UsageArgs {
+    #[arg(long, short = 'b', value_enum, default_value_t = UsageGroup::Day)]
+    pub by: UsageGroup,
+    #[arg(long, short = 'd')]
+    pub days: Option<u32>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ModelsAction {
+    Resolve {
+        name: String,
+    },
+    Probe {
+        name: String,
+        #[arg(long, default_value_t = false)]
+        save: bool,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct ModelsCommand {
+    #[command(subcommand)]
+    pub action: ModelsAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProvidersAction {
+    List,
+    Test {
+        name: Option<String>,
+        #[arg(long, short = 'm')]
+        model: Option<String>,
+    },
+    Models {
+        name: String,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct ProvidersCommand {
+    #[command(subcommand)]
+    pub action: ProvidersAction,
+}
+
 #[derive(Args, Debug)]
 pub struct GatewayArgs {
     #[arg(long, short = 'p')]
@@ -71,6 +126,8 @@ pub enum CronAction {
         to: Option<String>,
         #[arg(long)]
         channel: Option<String>,
+        #[arg(long)]
+        profile: Option<String>,
     },
     Remove {
         job_id: String,
@@ -103,6 +160,9 @@ pub async fn run() -> Result<()> {
         Commands::Gateway(args) => commands::gateway::run(args).await?,
         Commands::Channels(cmd) => commands::channels::run(cmd).await?,
         Commands::Cron(cmd) => commands::cron::run(cmd).await?,
+        Commands::Usage(args) => commands::usage::run(args).await?,
+        Commands::Models(cmd) => commands::models::run(cmd).await?,
+        Commands::Providers(cmd) => commands::providers::run(cmd).await?,
     }
 
     Ok(())
@@ -2,18 +2,23 @@ use std::fs;
 use std::future::Future;
 use std::io::{self, IsTerminal, Write};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use chrono::Local;
-use ferrumbot_agent::AgentLoop;
-use ferrumbot_config::{data_dir, load_config};
-use ferrumbot_core::MessageBus;
+use ferrumbot_agent::{AgentLoop, StreamSink};
+use ferrumbot_config::{data_dir, expand_tilde, load_config};
+use ferrumbot_core::{InboundMessage, MessageBus, Usage, UsageLedger, summarize_usage, today_date};
+use ferrumbot_providers::StreamEvent;
 use ferrumbot_runtime::init_tracing;
+use ferrumbot_tools::resolve_path;
 use rustyline::DefaultEditor;
 use rustyline::error::ReadlineError;
 
 use crate::app::AgentArgs;
+use crate::commands::usage::format_usage;
 
 pub async fn run(args: AgentArgs) -> Result<()> {
     let config = load_config(None)?;
@@ -22,7 +27,7 @@ pub async fn run(args: AgentArgs) -> Result<()> {
     let model = config.agents.defaults.model.clone();
     let workspace = config.workspace_path();
     let bus = MessageBus::new(64);
-    let agent = AgentLoop::from_config(bus, &config, None)?;
+    let agent = Arc::new(AgentLoop::from_config(bus.clone(), &config, None)?);
 
     if let Some(message) = args.message {
         let response = run_with_spinner(
@@ -34,7 +39,11 @@ pub async fn run(args: AgentArgs) -> Result<()> {
         return Ok(());
     }
 
+    agent.enable_subagents();
+    tokio::spawn(print_background_results(bus));
+
     let mut state = ReplState::new(args.session, model, workspace);
+    state.confine_attachments = config.tools.restrict_to_workspace;
     let mut repl_input = ReplInput::new()?;
 
     print_banner(&state);
@@ -64,10 +73,39 @@ pub async fn run(args: AgentArgs) -> Result<()> {
             match handle_command(input, &mut state, &mut repl_input)? {
                 ReplAction::Continue => continue,
                 ReplAction::Send(message) => {
-                    send_user_turn(&agent, &mut state, message).await?;
+                    if let Err(err) = send_user_turn(&agent, &mut state, message).await {
+                        print_turn_error(&err);
+                    }
                 }
                 ReplAction::Retry => {
-                    retry_last_turn(&agent, &mut state).await?;
+                    if let Err(err) = retry_last_turn(&agent, &mut state).await {
+                        print_turn_error(&err);
+                    }
+                }
+                ReplAction::Status => {
+                    if let Err(err) = print_status(&agent, &state).await {
+                        print_turn_error(&err);
+                    }
+                }
+                ReplAction::Profile(name) => {
+                    if let Err(err) = select_profile(&agent, &state, &name).await {
+                        print_turn_error(&err);
+                    }
+                }
+                ReplAction::Cost => {
+                    if let Err(err) = print_cost(&agent, &state).await {
+                        print_turn_error(&err);
+                    }
+                }
+                ReplAction::Subagents(arg) => manage_subagents(&agent, &state.session, &arg),
+                ReplAction::Compact => {
+                    let result =
+                        run_with_spinner("compacting", agent.compact(&state.session)).await;
+                    match result {
+                        Ok(Some(count)) => println!("Summarized {count} earlier messages."),
+                        Ok(None) => println!("Nothing to compact yet."),
+                        Err(err) => print_turn_error(&err),
+                    }
                 }
                 ReplAction::Exit => {
                     println!("Session closed.");
@@ -77,7 +115,9 @@ pub async fn run(args: AgentArgs) -> Result<()> {
             continue;
         }
 
-        send_user_turn(&agent, &mut state, input.to_string()).await?;
+        if let Err(err) = send_user_turn(&agent, &mut state, input.to_string()).await {
+            print_turn_error(&err);
+        }
     }
 
     repl_input.save_history()?;
@@ -90,7 +130,11 @@ struct ReplState {
     workspace: PathBuf,
     turns: u64,
     last_user: Option<String>,
+    last_media: Vec<String>,
     last_response: Option<String>,
+    pending_media: Vec<String>,
+    confine_attachments: bool,
+    show_reasoning: bool,
 }
 
 impl ReplState {
@@ -101,7 +145,11 @@ impl ReplState {
             workspace,
             turns: 0,
             last_user: None,
+            last_media: Vec::new(),
             last_response: None,
+            pending_media: Vec::new(),
+            confine_attachments: false,
+            show_reasoning: false,
         }
     }
 }
@@ -110,6 +158,11 @@ enum ReplAction {
     Continue,
     Send(String),
     Retry,
+    Status,
+    Profile(String),
+    Cost,
+    Subagents(String),
+    Compact,
     Exit,
 }
 
@@ -212,10 +265,11 @@ fn handle_command(
             print_help();
             Ok(ReplAction::Continue)
         }
-        "/status" => {
-            print_status(state);
-            Ok(ReplAction::Continue)
-        }
+        "/status" => Ok(ReplAction::Status),
+        "/profile" => Ok(ReplAction::Profile(arg.to_string())),
+        "/cost" => Ok(ReplAction::Cost),
+        "/subagents" => Ok(ReplAction::Subagents(arg.to_string())),
+        "/compact" => Ok(ReplAction::Compact),
         "/clear" => {
             clear_screen()?;
             print_banner(state);
@@ -258,6 +312,24 @@ fn handle_command(
             }
             Ok(ReplAction::Continue)
         }
+        "/attach" => {
+            stage_attachment(state, arg);
+            Ok(ReplAction::Continue)
+        }
+        "/reasoning" => {
+            state.show_reasoning = match arg {
+                "on" => true,
+                "off" => false,
+                _ => !state.show_reasoning,
+            };
+            let mode = if state.show_reasoning {
+                "shown"
+            } else {
+                "hidden"
+            };
+            println!("Model reasoning is now {mode}.");
+            Ok(ReplAction::Continue)
+        }
         "/retry" => Ok(ReplAction::Retry),
         "/quit" | "/exit" => Ok(ReplAction::Exit),
         _ => {
@@ -386,11 +458,53 @@ fn print_banner(state: &ReplState) {
     println!();
 }
 
-fn print_status(state: &ReplState) {
+async fn print_status(agent: &AgentLoop, state: &ReplState) -> Result<()> {
+    let metadata = agent.session_metadata(&state.session).await?;
+    let field = |key: &str| metadata.get(key).and_then(|v| v.as_str());
     println!("session: {}", state.session);
-    println!("model: {}", state.model);
+    match field("model") {
+        Some(active) if active != state.model => {
+            println!("model: {active} (fallback for {})", state.model)
+        }
+        _ => println!("model: {}", state.model),
+    }
+    println!("profile: {}", field("profile").unwrap_or("default"));
     println!("workspace: {}", state.workspace.display());
     println!("turns: {}", state.turns);
+    if let Some(summary) = field("summary") {
+        let compacted = metadata
+            .get("compacted")
+            .and_then(|v| v.as_u64())
+            .unwrap_or_default();
+        println!("summary ({compacted} earlier messages):");
+        println!("{}", paint(summary, "38;5;250"));
+    }
+    Ok(())
+}
+
+async fn select_profile(agent: &AgentLoop, state: &ReplState, name: &str) -> Result<()> {
+    match name {
+        "" => {
+            let metadata = agent.session_metadata(&state.session).await?;
+            let current = metadata
+                .get("profile")
+                .and_then(|v| v.as_str())
+                .unwrap_or("default");
+            println!("Current profile: {current}");
+            println!("Available: {}", agent.profile_names().join(", "));
+        }
+        "default" | "off" => {
+            agent.set_session_profile(&state.session, None).await?;
+            println!("Profile reset to default.");
+        }
+        name => {
+            agent
+                .set_session_profile(&state.session, Some(name))
+                .await?;
+            println!("Profile set to {name}.");
+        }
+    }
+    Ok(())
 }
 
 fn print_help() {
@@ -402,7 +516,7 @@ fn print_help() {
     println!(
         "{}",
         paint(
-            "/status             Show active session/model/workspace",
+            "/status             Show session, model, workspace and summary",
             "38;5;250"
         )
     );
@@ -441,6 +555,48 @@ fn print_help() {
             "38;5;250"
         )
     );
+    println!(
+        "{}",
+        paint(
+            "/attach <path>      Attach a file to the next message (clear to drop)",
+            "38;5;250"
+        )
+    );
+    println!(
+        "{}",
+        paint(
+            "/reasoning [on|off] Show or hide model reasoning above answers",
+            "38;5;250"
+        )
+    );
+    println!(
+        "{}",
+        paint(
+            "/profile [name]     Show or set the generation profile (default to reset)",
+            "38;5;250"
+        )
+    );
+    println!(
+        "{}",
+        paint(
+            "/cost               Show token usage and cost for this session and today",
+            "38;5;250"
+        )
+    );
+    println!(
+        "{}",
+        paint(
+            "/compact            Summarize older turns into the session summary",
+            "38;5;250"
+        )
+    );
+    println!(
+        "{}",
+        paint(
+            "/subagents [cancel <id>] List background subagents or cancel one",
+            "38;5;250"
+        )
+    );
     println!(
         "{}",
         paint("/clear              Clear terminal screen", "38;5;250")
@@ -452,13 +608,60 @@ fn print_help() {
 }
 
 fn print_response(response: &str, turn: u64) {
+    print_response_header(turn);
+    println!("{response}");
+    println!();
+}
+
+fn print_response_header(turn: u64) {
     println!();
     println!(
         "{} {}",
         paint("assistant>", "1;36"),
         paint(&format!("[turn {turn}]"), "38;5;244")
     );
-    println!("{response}");
+}
+
+fn stream_printer(show_reasoning: bool) -> StreamSink {
+    let in_reasoning = AtomicBool::new(false);
+    Arc::new(move |event: &StreamEvent| {
+        match event {
+            StreamEvent::ReasoningDelta(text) if show_reasoning => {
+                in_reasoning.store(true, Ordering::Relaxed);
+                print!("{}", paint(text, "2;38;5;244"));
+            }
+            StreamEvent::ReasoningDelta(_) => {}
+            StreamEvent::TextDelta(text) => {
+                if in_reasoning.swap(false, Ordering::Relaxed) {
+                    print!("\n\n");
+                }
+                print!("{text}");
+            }
+            StreamEvent::ToolCall(call) => {
+                println!("{}", paint(&format!("\n[tool] {}", call.name), "38;5;244"));
+            }
+            StreamEvent::Done(_) => {}
+        }
+        let _ = io::stdout().flush();
+    })
+}
+
+fn reasoning_collector() -> (StreamSink, Arc<Mutex<String>>) {
+    let reasoning = Arc::new(Mutex::new(String::new()));
+    let buffer = reasoning.clone();
+    let sink: StreamSink = Arc::new(move |event: &StreamEvent| {
+        if let StreamEvent::ReasoningDelta(text) = event
+            && let Ok(mut buffer) = buffer.lock()
+        {
+            buffer.push_str(text);
+        }
+    });
+    (sink, reasoning)
+}
+
+fn print_turn_error(err: &anyhow::Error) {
+    println!();
+    println!("{} {err:#}", paint("error>", "1;31"));
     println!();
 }
 
@@ -471,26 +674,136 @@ fn render_prompt(session: &str, next_turn: u64) -> String {
     )
 }
 
+async fn print_cost(agent: &AgentLoop, state: &ReplState) -> Result<()> {
+    let session = Usage::from_metadata(&agent.session_metadata(&state.session).await?);
+    let today = today_date();
+    let records = UsageLedger::new()?.load(Some(&today))?;
+    let today_usage = summarize_usage(&records, |r| r.date().to_string())
+        .remove(&today)
+        .unwrap_or_default();
+    println!("session: {}", format_usage(&session));
+    println!("today:   {}", format_usage(&today_usage));
+    Ok(())
+}
+
+fn manage_subagents(agent: &AgentLoop, session: &str, arg: &str) {
+    let subagents = agent.subagents();
+    if let Some(id) = arg.strip_prefix("cancel").map(str::trim) {
+        if subagents.cancel(id) {
+            println!("Cancelled subagent {id}.");
+        } else {
+            println!("No running subagent with id '{id}'.");
+        }
+        return;
+    }
+
+    let infos = subagents.list(Some(session));
+    if infos.is_empty() {
+        println!("No subagents started in this session.");
+        return;
+    }
+    for info in infos {
+        println!(
+            "{}  {:<10} {:>5}s  {}",
+            info.task.id,
+            info.status.to_string(),
+            info.elapsed().as_secs(),
+            info.task.label
+        );
+    }
+}
+
+async fn print_background_results(bus: MessageBus) {
+    while let Some(msg) = bus.consume_outbound().await {
+        println!();
+        println!("{}", paint("background>", "1;35"));
+        println!("{}", msg.content);
+        println!();
+    }
+}
+
 async fn retry_last_turn(agent: &AgentLoop, state: &mut ReplState) -> Result<()> {
     let Some(previous) = state.last_user.clone() else {
         println!("No previous user message to retry.");
         return Ok(());
     };
 
+    state.pending_media = state.last_media.clone();
     send_user_turn(agent, state, previous).await
 }
 
+fn stage_attachment(state: &mut ReplState, arg: &str) {
+    match arg {
+        "" if state.pending_media.is_empty() => println!("No attachments staged."),
+        "" => {
+            for path in &state.pending_media {
+                println!("- {path}");
+            }
+        }
+        "clear" => {
+            state.pending_media.clear();
+            println!("Attachments cleared.");
+        }
+        path => {
+            let path = expand_tilde(path);
+            if !path.is_file() {
+                println!("Not a file: {}", path.display());
+                return;
+            }
+            let path = path.canonicalize().unwrap_or(path);
+            if state.confine_attachments
+                && let Err(err) = resolve_path(&path.to_string_lossy(), Some(&state.workspace))
+            {
+                println!("Not attached: {err} (tools.restrict_to_workspace is on)");
+                return;
+            }
+            state.pending_media.push(path.display().to_string());
+            println!(
+                "Attached {} ({} staged, sent with your next message)",
+                path.display(),
+                state.pending_media.len()
+            );
+        }
+    }
+}
+
 async fn send_user_turn(agent: &AgentLoop, state: &mut ReplState, message: String) -> Result<()> {
-    let response = run_with_spinner(
-        "thinking",
-        agent.process_direct(&message, &state.session, "cli", "direct"),
-    )
-    .await?;
+    let turn = state.turns + 1;
+    state.last_user = Some(message.clone());
+    state.last_media = std::mem::take(&mut state.pending_media);
+    let mut msg = InboundMessage::new("cli", "user", "direct", &message);
+    msg.media = state.last_media.clone();
+    let response = if supports_ansi() {
+        print_response_header(turn);
+        let response = agent
+            .process_inbound(
+                msg,
+                &state.session,
+                Some(stream_printer(state.show_reasoning)),
+            )
+            .await?;
+        println!("\n");
+        response
+    } else {
+        let (sink, reasoning) = reasoning_collector();
+        let sink = state.show_reasoning.then_some(sink);
+        let response =
+            run_with_spinner("thinking", agent.process_inbound(msg, &state.session, sink)).await?;
+        let reasoning = reasoning
+            .lock()
+            .map(|r| r.trim().to_string())
+            .unwrap_or_default();
+        if !reasoning.is_empty() {
+            println!();
+            println!("[reasoning]");
+            println!("{reasoning}");
+        }
+        print_response(&response, turn);
+        response
+    };
 
-    state.turns += 1;
-    state.last_user = Some(message);
-    state.last_response = Some(response.clone());
-    print_response(&response, state.turns);
+    state.turns = turn;
+    state.last_response = Some(response);
     Ok(())
 }
 
@@ -556,3 +869,32 @@ fn supports_ansi() -> bool {
             .map(|term| term != "dumb")
             .unwrap_or(true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attach_refuses_files_outside_a_confined_workspace() {
+        let root = std::env::temp_dir().join(format!("ferrumbot-attach-{}", std::process::id()));
+        let workspace = root.join("workspace");
+        fs::create_dir_all(&workspace).unwrap();
+        let inside = workspace.join("notes.txt");
+        let outside = root.join("secret.txt");
+        fs::write(&inside, "inside").unwrap();
+        fs::write(&outside, "outside").unwrap();
+
+        let mut state = ReplState::new("cli:test".to_string(), "m".to_string(), workspace);
+        state.confine_attachments = true;
+        stage_attachment(&mut state, &outside.display().to_string());
+        assert!(state.pending_media.is_empty());
+        stage_attachment(&mut state, &inside.display().to_string());
+        assert_eq!(state.pending_media.len(), 1);
+
+        state.confine_attachments = false;
+        stage_attachment(&mut state, &outside.display().to_string());
+        assert_eq!(state.pending_media.len(), 2);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
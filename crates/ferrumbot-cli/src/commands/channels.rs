@@ -8,7 +8,7 @@ use crate::app::{ChannelsAction, ChannelsCommand};
 pub async fn run(cmd: ChannelsCommand) -> Result<()> {
     let config = load_config(None)?;
     let bus = MessageBus::new(64);
-    let manager = ChannelManager::new(&config, bus);
+    let manager = ChannelManager::new(&config, bus)?;
 
     match cmd.action {
         ChannelsAction::Status => {
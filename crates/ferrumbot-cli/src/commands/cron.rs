@@ -46,6 +46,7 @@ pub async fn run(cmd: CronCommand) -> Result<()> {
             deliver,
             to,
             channel,
+            profile,
         } => {
             let schedule = if let Some(every) = every {
                 CronScheduleDef {
@@ -76,6 +77,7 @@ pub async fn run(cmd: CronCommand) -> Result<()> {
                 deliver,
                 channel,
                 to,
+                profile,
             };
 
             let job = service.add_job(name, schedule, payload, false).await?;
@@ -2,5 +2,8 @@ pub mod agent;
 pub mod channels;
 pub mod cron;
 pub mod gateway;
+pub mod models;
 pub mod onboard;
+pub mod providers;
 pub mod status;
+pub mod usage;
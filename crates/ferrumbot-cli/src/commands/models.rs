@@ -0,0 +1,131 @@
+use anyhow::Result;
+use ferrumbot_config::{ModelCapabilities, load_config, save_config};
+use ferrumbot_providers::{
+    ChatOptions, LlmProvider, LlmResponse, ProviderError, ProviderResult, provider_for_model,
+    resolve_model,
+};
+use serde_json::{Value, json};
+
+use crate::app::{ModelsAction, ModelsCommand};
+
+const PROBE_IMAGE: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAABAAAAAQCAIAAACQkWg2AAAAFklEQVR42mP4z8BAEmIY1TCqYfhqAACQ+f8B8u7oVwAAAABJRU5ErkJggg==";
+
+pub async fn run(cmd: ModelsCommand) -> Result<()> {
+    let mut config = load_config(None)?;
+
+    match cmd.action {
+        ModelsAction::Resolve { name } => {
+            let route = resolve_model(&config, &name);
+            let provider = match (&route.provider, route.custom) {
+                (Some(provider), true) => format!("{provider} (custom)"),
+                (Some(provider), false) => provider.clone(),
+                (None, _) => "none (no matching provider has an API key)".to_string(),
+            };
+            println!("model:      {name}");
+            println!("provider:   {provider}");
+            println!("api base:   {}", route.api_base);
+            println!("wire model: {}", route.wire_model);
+        }
+        ModelsAction::Probe { name, save } => {
+            let known = config.capabilities.lookup(&name);
+            let provider = provider_for_model(&config, &name)?;
+            println!("Probing {name}...");
+            let probed = probe(provider.as_ref(), &name).await?;
+
+            let show = |flag: Option<bool>| match flag {
+                Some(true) => "yes",
+                Some(false) => "no",
+                None => "unknown",
+            };
+            println!("tools:     {}", show(probed.tools));
+            println!("vision:    {}", show(probed.vision));
+            println!("reasoning: {}", show(probed.reasoning));
+            if let Some(window) = known.context_window {
+                println!("context:   {window} (registry)");
+            }
+
+            if save {
+                let entry = config.capabilities.0.entry(name.clone()).or_default();
+                *entry = entry.overlay(&probed);
+                save_config(&config, None)?;
+                println!("Saved capabilities for {name} to config.");
+            } else {
+                let snippet = json!({ "capabilities": { name: probed } });
+                println!();
+                println!("{}", serde_json::to_string_pretty(&snippet)?);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn probe(provider: &dyn LlmProvider, model: &str) -> Result<ModelCapabilities> {
+    let options = ChatOptions {
+        max_tokens: Some(256),
+        temperature: Some(0.0),
+        ..Default::default()
+    };
+    let ask = |content: Value, tools: Option<Vec<Value>>| {
+        provider.chat(
+            vec![json!({"role": "user", "content": content})],
+            tools,
+            Some(model),
+            &options,
+        )
+    };
+
+    let text = ask(json!("Reply with the single word OK."), None).await?;
+
+    let tools = ask(
+        json!("Use the get_time tool to tell me the time."),
+        Some(vec![probe_tool()]),
+    )
+    .await;
+
+    let image = ask(
+        json!([
+            {"type": "text", "text": "What colour is this image? Answer in one word."},
+            {"type": "image_url", "image_url": {"url": PROBE_IMAGE}}
+        ]),
+        None,
+    )
+    .await;
+
+    Ok(ModelCapabilities {
+        tools: supported("tools", &tools, |resp| resp.has_tool_calls()),
+        vision: supported("vision", &image, |resp| {
+            resp.content
+                .as_deref()
+                .is_some_and(|answer| answer.to_lowercase().contains("red"))
+        }),
+        reasoning: text.reasoning.is_some().then_some(true),
+        ..Default::default()
+    })
+}
+
+pub(crate) fn probe_tool() -> Value {
+    json!({
+        "type": "function",
+        "function": {
+            "name": "get_time",
+            "description": "Return the current time",
+            "parameters": {"type": "object", "properties": {}}
+        }
+    })
+}
+
+fn supported(
+    label: &str,
+    result: &ProviderResult<LlmResponse>,
+    check: impl Fn(&LlmResponse) -> bool,
+) -> Option<bool> {
+    match result {
+        Ok(resp) => Some(check(resp)),
+        Err(ProviderError::InvalidRequest { .. }) => Some(false),
+        Err(err) => {
+            println!("{label} probe inconclusive: {err}");
+            None
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::time::Instant;
+
+use anyhow::{Result, bail};
+use ferrumbot_config::{Config, ResolvedProvider, load_config};
+use ferrumbot_providers::{ChatOptions, LlmProvider, ProviderError, provider_named, route_for};
+use serde_json::json;
+
+use super::models::probe_tool;
+use crate::app::{ProvidersAction, ProvidersCommand};
+
+pub async fn run(cmd: ProvidersCommand) -> Result<()> {
+    let config = load_config(None)?;
+
+    match cmd.action {
+        ProvidersAction::List => list(&config),
+        ProvidersAction::Test { name, model } => test(&config, name, model).await?,
+        ProvidersAction::Models { name } => {
+            let provider = provider_named(&config, &name, &config.agents.defaults.model)?;
+            match provider.list_models().await {
+                Ok(models) if models.is_empty() => println!("{name} reported no models."),
+                Ok(models) => {
+                    for model in models {
+                        println!("{model}");
+                    }
+                }
+                Err(ProviderError::Unsupported(_)) => {
+                    println!("{name} does not support listing models.")
+                }
+                Err(err) => bail!("failed to list models for {name}: {err}"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn list(config: &Config) {
+    let (configured, unconfigured): (Vec<_>, Vec<_>) = config
+        .all_providers()
+        .into_iter()
+        .partition(ResolvedProvider::is_configured);
+
+    if configured.is_empty() {
+        println!("No providers configured. Add an api_key under providers in the config.");
+    } else {
+        println!("{:<14} {:<8} {:<10} API BASE", "PROVIDER", "KEY", "TOOLS");
+        for provider in &configured {
+            let name = if provider.custom {
+                format!("{} *", provider.name)
+            } else {
+                provider.name.to_string()
+            };
+            let key = if provider.config.api_key.is_empty() {
+                "-"
+            } else {
+                "✓"
+            };
+            let tools = format!("{:?}", provider.config.tool_calls).to_lowercase();
+            let base = route_for(*provider, "").api_base;
+            println!("{name:<14} {key:<8} {tools:<10} {base}");
+        }
+    }
+
+    if !unconfigured.is_empty() {
+        let names: Vec<&str> = unconfigured.iter().map(|p| p.name).collect();
+        println!("\nNot configured: {}", names.join(", "));
+    }
+}
+
+async fn test(config: &Config, name: Option<String>, model: Option<String>) -> Result<()> {
+    let targets = match &name {
+        Some(name) => match config.provider_named(name) {
+            Some(provider) => vec![provider],
+            None => bail!("unknown provider '{name}'"),
+        },
+        None => config
+            .all_providers()
+            .into_iter()
+            .filter(ResolvedProvider::is_configured)
+            .collect(),
+    };
+    if targets.is_empty() {
+        println!("No providers configured.");
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for provider in targets {
+        let Some(model) = model
+            .clone()
+            .or_else(|| default_test_model(config, provider.name))
+        else {
+            println!(
+                "{}: skipped (no configured model routes here; pass --model)\n",
+                provider.name
+            );
+            continue;
+        };
+
+        let route = route_for(provider, &model);
+        println!("{}: {model} -> {}", provider.name, route.wire_model);
+        println!("  base:  {}", route.api_base);
+        let client = provider_named(config, provider.name, &model)?;
+        for (label, result) in [
+            ("chat", timed(chat_check(client.as_ref())).await),
+            ("tools", timed(tool_check(client.as_ref())).await),
+        ] {
+            match result {
+                (Ok(note), ms) => println!("  {label:<6} ok      {ms:>6} ms  {note}"),
+                (Err(err), ms) => {
+                    failed += 1;
+                    println!("  {label:<6} failed  {ms:>6} ms  {err}");
+                }
+            }
+        }
+        println!();
+    }
+
+    if failed > 0 {
+        bail!("{failed} provider check(s) failed");
+    }
+    Ok(())
+}
+
+fn default_test_model(config: &Config, name: &str) -> Option<String> {
+    let defaults = &config.agents.defaults;
+    std::iter::once(&defaults.model)
+        .chain(&defaults.fallback_models)
+        .find(|model| {
+            config
+                .resolve_provider(Some(model))
+                .is_some_and(|resolved| resolved.name == name)
+        })
+        .cloned()
+}
+
+async fn timed<T>(fut: impl Future<Output = T>) -> (T, u128) {
+    let started = Instant::now();
+    let out = fut.await;
+    (out, started.elapsed().as_millis())
+}
+
+fn test_options() -> ChatOptions {
+    ChatOptions {
+        max_tokens: Some(64),
+        temperature: Some(0.0),
+        ..Default::default()
+    }
+}
+
+async fn chat_check(provider: &dyn LlmProvider) -> Result<String> {
+    let resp = provider
+        .chat(
+            vec![json!({"role": "user", "content": "Reply with the single word OK."})],
+            None,
+            None,
+            &test_options(),
+        )
+        .await?;
+    let reply = resp.content.unwrap_or_default();
+    Ok(format!(
+        "\"{}\"",
+        reply.trim().chars().take(40).collect::<String>()
+    ))
+}
+
+async fn tool_check(provider: &dyn LlmProvider) -> Result<String> {
+    let mut messages =
+        vec![json!({"role": "user", "content": "Use the get_time tool to tell me the time."})];
+    let tools = Some(vec![probe_tool()]);
+    let first = provider
+        .chat(messages.clone(), tools.clone(), None, &test_options())
+        .await?;
+    let Some(call) = first.tool_calls.first() else {
+        bail!("model answered without calling the tool");
+    };
+
+    messages.push(json!({
+        "role": "assistant",
+        "content": first.content.clone().unwrap_or_default(),
+        "tool_calls": [{
+            "id": call.id,
+            "type": "function",
+            "function": {"name": call.name, "arguments": call.arguments.to_string()},
+        }],
+    }));
+    messages.push(json!({
+        "role": "tool",
+        "tool_call_id": call.id,
+        "name": call.name,
+        "content": "12:00",
+    }));
+    let second = provider
+        .chat(messages, tools, None, &test_options())
+        .await?;
+    if second
+        .content
+        .as_deref()
+        .is_none_or(|c| c.trim().is_empty())
+    {
+        bail!("no answer after the tool result");
+    }
+    Ok(format!("{} called, result accepted", call.name))
+}
@@ -20,63 +20,19 @@ pub async fn run() -> Result<()> {
 
     if path.exists() {
         println!("Model: {}", config.agents.defaults.model);
-        println!(
-            "OpenRouter API: {}",
-            if config.providers.openrouter.api_key.is_empty() {
-                "not set"
+        for provider in config.all_providers() {
+            let status = if !provider.config.api_key.is_empty() {
+                "✓".to_string()
+            } else if let Some(base) = &provider.config.api_base {
+                base.clone()
+            } else if provider.custom {
+                "no key".to_string()
             } else {
-                "✓"
-            }
-        );
-        println!(
-            "Anthropic API: {}",
-            if config.providers.anthropic.api_key.is_empty() {
-                "not set"
-            } else {
-                "✓"
-            }
-        );
-        println!(
-            "OpenAI API: {}",
-            if config.providers.openai.api_key.is_empty() {
-                "not set"
-            } else {
-                "✓"
-            }
-        );
-        println!(
-            "Gemini API: {}",
-            if config.providers.gemini.api_key.is_empty() {
-                "not set"
-            } else {
-                "✓"
-            }
-        );
-        println!(
-            "Zhipu AI API: {}",
-            if config.providers.zhipu.api_key.is_empty() {
-                "not set"
-            } else {
-                "✓"
-            }
-        );
-        println!(
-            "AiHubMix API: {}",
-            if config.providers.aihubmix.api_key.is_empty() {
-                "not set"
-            } else {
-                "✓"
-            }
-        );
-        println!(
-            "vLLM/Local: {}",
-            config
-                .providers
-                .vllm
-                .api_base
-                .clone()
-                .unwrap_or_else(|| "not set".to_string())
-        );
+                "not set".to_string()
+            };
+            println!("Provider {}: {status}", provider.name);
+        }
+        println!("Run `ferrum-bot providers test` to check the configured keys.");
     }
 
     Ok(())
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use chrono::{Duration, Local};
+use ferrumbot_core::{Usage, UsageLedger, summarize_usage};
+
+use crate::app::{UsageArgs, UsageGroup};
+
+pub async fn run(args: UsageArgs) -> Result<()> {
+    let ledger = UsageLedger::new()?;
+    let since = args.days.map(|days| {
+        (Local::now() - Duration::days(days.saturating_sub(1) as i64))
+            .format("%Y-%m-%d")
+            .to_string()
+    });
+    let records = ledger.load(since.as_deref())?;
+    if records.is_empty() {
+        println!("No usage recorded.");
+        return Ok(());
+    }
+
+    let summary = match args.by {
+        UsageGroup::Day => summarize_usage(&records, |r| r.date().to_string()),
+        UsageGroup::Session => summarize_usage(&records, |r| r.session.clone()),
+        UsageGroup::Model => summarize_usage(&records, |r| r.model.clone()),
+    };
+
+    let mut total = Usage::default();
+    for (key, usage) in &summary {
+        println!("{key:<40} {}", format_usage(usage));
+        total.add(usage);
+    }
+    println!("{:<40} {}", "total", format_usage(&total));
+    Ok(())
+}
+
+pub(crate) fn format_usage(usage: &Usage) -> String {
+    let mut line = format!(
+        "{:>5} req  {:>10} in  {:>9} out  ${:.4}",
+        usage.requests, usage.prompt_tokens, usage.completion_tokens, usage.cost
+    );
+    if usage.cached_tokens > 0 && usage.prompt_tokens > 0 {
+        line.push_str(&format!(
+            "  {} cached ({:.0}%)",
+            usage.cached_tokens,
+            usage.cached_tokens as f64 * 100.0 / usage.prompt_tokens as f64
+        ));
+    }
+    line
+}
@@ -1,13 +1,27 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use super::{GenerationProfile, ModelCapabilities};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct AgentDefaults {
     pub workspace: String,
     pub model: String,
+    pub fallback_models: Vec<String>,
     pub max_tokens: usize,
     pub temperature: f32,
+    pub profile: Option<String>,
+    pub channel_profiles: BTreeMap<String, String>,
     pub max_tool_iterations: usize,
+    pub max_history_messages: usize,
+    pub context_window: usize,
+    pub context_windows: BTreeMap<String, usize>,
+    pub max_media_bytes: u64,
+    pub persist_reasoning: bool,
+    pub replay_tool_calls: bool,
+    pub tool_result_replay_chars: usize,
 }
 
 impl Default for AgentDefaults {
@@ -15,9 +29,67 @@ impl Default for AgentDefaults {
         Self {
             workspace: "~/.ferrum-bot/workspace".to_string(),
             model: "anthropic/claude-opus-4-5".to_string(),
+            fallback_models: Vec::new(),
             max_tokens: 8192,
             temperature: 0.7,
+            profile: None,
+            channel_profiles: BTreeMap::new(),
             max_tool_iterations: 20,
+            max_history_messages: 50,
+            context_window: 128_000,
+            context_windows: BTreeMap::new(),
+            max_media_bytes: 5 * 1024 * 1024,
+            persist_reasoning: false,
+            replay_tool_calls: true,
+            tool_result_replay_chars: 2000,
+        }
+    }
+}
+
+impl AgentDefaults {
+    pub fn context_window_for(&self, model: &str, capabilities: &ModelCapabilities) -> usize {
+        self.context_windows
+            .get(model)
+            .copied()
+            .or(capabilities.context_window)
+            .unwrap_or(self.context_window)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubagentsConfig {
+    pub max_iterations: usize,
+    pub timeout_secs: u64,
+    pub max_concurrent: usize,
+    pub allow_spawn: bool,
+    pub tools: Vec<String>,
+}
+
+impl Default for SubagentsConfig {
+    fn default() -> Self {
+        Self {
+            max_iterations: 15,
+            timeout_secs: 600,
+            max_concurrent: 3,
+            allow_spawn: false,
+            tools: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompactionConfig {
+    pub enabled: bool,
+    pub keep_recent: usize,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            keep_recent: 10,
         }
     }
 }
@@ -26,4 +98,17 @@ impl Default for AgentDefaults {
 #[serde(default)]
 pub struct AgentsConfig {
     pub defaults: AgentDefaults,
+    pub profiles: BTreeMap<String, GenerationProfile>,
+    pub subagents: SubagentsConfig,
+    pub compaction: CompactionConfig,
+}
+
+impl AgentsConfig {
+    pub fn base_generation(&self) -> GenerationProfile {
+        GenerationProfile {
+            max_tokens: Some(self.defaults.max_tokens),
+            temperature: Some(self.defaults.temperature),
+            ..Default::default()
+        }
+    }
 }
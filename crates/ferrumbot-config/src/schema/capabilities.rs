@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tools: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vision: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json_mode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structured_outputs: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<bool>,
+}
+
+impl ModelCapabilities {
+    pub fn supports_tools(&self) -> bool {
+        self.tools.unwrap_or(true)
+    }
+
+    pub fn supports_parallel_tools(&self) -> bool {
+        self.parallel_tools.unwrap_or(true)
+    }
+
+    pub fn supports_vision(&self) -> bool {
+        self.vision.unwrap_or(true)
+    }
+
+    pub fn supports_json_mode(&self) -> bool {
+        self.json_mode.unwrap_or(false)
+    }
+
+    pub fn supports_structured_outputs(&self) -> bool {
+        self.structured_outputs.unwrap_or(false)
+    }
+
+    pub fn supports_reasoning(&self) -> bool {
+        self.reasoning.unwrap_or(true)
+    }
+
+    pub fn overlay(&self, other: &ModelCapabilities) -> ModelCapabilities {
+        ModelCapabilities {
+            context_window: other.context_window.or(self.context_window),
+            max_output_tokens: other.max_output_tokens.or(self.max_output_tokens),
+            tools: other.tools.or(self.tools),
+            parallel_tools: other.parallel_tools.or(self.parallel_tools),
+            vision: other.vision.or(self.vision),
+            json_mode: other.json_mode.or(self.json_mode),
+            structured_outputs: other.structured_outputs.or(self.structured_outputs),
+            reasoning: other.reasoning.or(self.reasoning),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CapabilityTable(pub BTreeMap<String, ModelCapabilities>);
+
+impl CapabilityTable {
+    pub fn lookup(&self, model: &str) -> ModelCapabilities {
+        let base = builtin_capabilities(model).unwrap_or_default();
+        let mut candidate = model;
+        loop {
+            if let Some(overrides) = self.0.get(candidate) {
+                return base.overlay(overrides);
+            }
+            match candidate.split_once('/') {
+                Some((_, rest)) => candidate = rest,
+                None => return base,
+            }
+        }
+    }
+}
+
+type BuiltinEntry = (
+    &'static str,
+    usize,
+    usize,
+    [bool; 6], // tools, parallel tools, vision, json mode, structured outputs, reasoning
+);
+
+#[rustfmt::skip]
+const BUILTIN: &[BuiltinEntry] = &[
+    ("claude-opus-4",     200_000,   32_000,  [true, true, true, false, false, true]),
+    ("claude-sonnet-4",   200_000,   64_000,  [true, true, true, false, false, true]),
+    ("claude-haiku-4",    200_000,   64_000,  [true, true, true, false, false, true]),
+    ("claude-3-7-sonnet", 200_000,   64_000,  [true, true, true, false, false, true]),
+    ("claude-3-5",        200_000,   8_192,   [true, true, true, false, false, false]),
+    ("claude",            200_000,   4_096,   [true, true, true, false, false, false]),
+    ("gpt-5",             400_000,   128_000, [true, true, true, true, true, true]),
+    ("gpt-4.1",           1_047_576, 32_768,  [true, true, true, true, true, false]),
+    ("gpt-4o",            128_000,   16_384,  [true, true, true, true, true, false]),
+    ("gpt-3.5-turbo",     16_385,    4_096,   [true, true, false, true, false, false]),
+    ("o1",                200_000,   100_000, [true, false, true, true, true, true]),
+    ("o3",                200_000,   100_000, [true, false, true, true, true, true]),
+    ("o4-mini",           200_000,   100_000, [true, false, true, true, true, true]),
+    ("deepseek-chat",     64_000,    8_192,   [true, true, false, true, false, false]),
+    ("deepseek-reasoner", 64_000,    32_768,  [false, false, false, false, false, true]),
+    ("deepseek-r1",       64_000,    32_768,  [false, false, false, false, false, true]),
+    ("gemini-2.5",        1_048_576, 65_536,  [true, true, true, true, true, true]),
+    ("gemini",            1_048_576, 8_192,   [true, true, true, true, true, false]),
+    ("qwen-vl",           131_072,   8_192,   [false, false, true, false, false, false]),
+    ("qwq",               131_072,   8_192,   [true, false, false, false, false, true]),
+    ("qwen",              131_072,   8_192,   [true, true, false, true, false, false]),
+    ("glm-4v",            8_192,     1_024,   [false, false, true, false, false, false]),
+    ("glm-4",             128_000,   4_096,   [true, true, false, true, false, false]),
+    ("kimi-k2",           131_072,   16_384,  [true, true, false, true, false, false]),
+    ("moonshot-v1",       128_000,   4_096,   [true, true, false, true, false, false]),
+];
+
+pub fn builtin_capabilities(model: &str) -> Option<ModelCapabilities> {
+    let name = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    let (_, context, output, [tools, parallel, vision, json, structured, reasoning]) = BUILTIN
+        .iter()
+        .filter(|(pattern, ..)| name.starts_with(pattern))
+        .max_by_key(|(pattern, ..)| pattern.len())?;
+    Some(ModelCapabilities {
+        context_window: Some(*context),
+        max_output_tokens: Some(*output),
+        tools: Some(*tools),
+        parallel_tools: Some(*parallel),
+        vision: Some(*vision),
+        json_mode: Some(*json),
+        structured_outputs: Some(*structured),
+        reasoning: Some(*reasoning),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_prefers_config_over_builtins() {
+        let deepseek = builtin_capabilities("deepseek/deepseek-reasoner").unwrap();
+        assert!(!deepseek.supports_tools());
+        assert_eq!(
+            builtin_capabilities("openrouter/anthropic/claude-opus-4-5")
+                .unwrap()
+                .max_output_tokens,
+            Some(32_000)
+        );
+        assert_eq!(builtin_capabilities("lab/llama-3.3-70b"), None);
+
+        let table = CapabilityTable(BTreeMap::from([(
+            "deepseek-reasoner".to_string(),
+            ModelCapabilities {
+                tools: Some(true),
+                ..Default::default()
+            },
+        )]));
+        let merged = table.lookup("deepseek/deepseek-reasoner");
+        assert!(merged.supports_tools());
+        assert_eq!(merged.context_window, Some(64_000));
+        assert!(table.lookup("lab/llama-3.3-70b").supports_tools());
+    }
+}
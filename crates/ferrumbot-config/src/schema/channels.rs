@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::HttpConfig;
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct WhatsAppCloudApiConfig {
@@ -80,4 +82,5 @@ pub struct ChannelsConfig {
     pub telegram: TelegramConfig,
     pub discord: DiscordConfig,
     pub feishu: FeishuConfig,
+    pub http: HttpConfig,
 }
@@ -5,7 +5,9 @@ use serde::{Deserialize, Serialize};
 use crate::expand_tilde;
 
 use super::{
-    AgentsConfig, ChannelsConfig, GatewayConfig, ProviderConfig, ProvidersConfig, ToolsConfig,
+    AgentsConfig, ApiStyle, CapabilityTable, ChannelsConfig, EmbeddingsConfig, GatewayConfig,
+    HttpConfig, ModelRewrite, PricingTable, ProviderConfig, ProvidersConfig, ToolsConfig,
+    default_api_base,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +19,52 @@ pub struct Config {
     pub providers: ProvidersConfig,
     pub gateway: GatewayConfig,
     pub tools: ToolsConfig,
+    pub pricing: PricingTable,
+    pub embeddings: EmbeddingsConfig,
+    pub capabilities: CapabilityTable,
+    pub http: HttpConfig,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedProvider<'a> {
+    pub name: &'a str,
+    pub config: &'a ProviderConfig,
+    pub custom: bool,
+}
+
+impl ResolvedProvider<'_> {
+    pub fn api_base(&self) -> Option<String> {
+        self.config
+            .api_base
+            .clone()
+            .or_else(|| default_api_base(self.name).map(ToString::to_string))
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.custom || !self.config.api_key.is_empty() || self.config.api_base.is_some()
+    }
+
+    pub fn prompt_cache(&self) -> bool {
+        self.config
+            .prompt_cache
+            .unwrap_or(!self.custom && matches!(self.name, "anthropic" | "openrouter"))
+    }
+
+    pub fn api_style(&self) -> ApiStyle {
+        match self.config.api_style {
+            ApiStyle::Auto if !self.custom && self.name == "anthropic" => ApiStyle::Anthropic,
+            ApiStyle::Auto => ApiStyle::Chat,
+            style => style,
+        }
+    }
+
+    pub fn model_rewrite(&self) -> ModelRewrite {
+        match &self.config.models {
+            Some(rewrite) => rewrite.clone(),
+            None if self.custom => ModelRewrite::strip(self.name),
+            None => ModelRewrite::preset(self.name),
+        }
+    }
 }
 
 impl Config {
@@ -24,64 +72,204 @@ impl Config {
         expand_tilde(&self.agents.defaults.workspace)
     }
 
-    pub fn get_provider_for_model(&self, model: Option<&str>) -> Option<&ProviderConfig> {
-        let model = model.unwrap_or(&self.agents.defaults.model).to_lowercase();
+    pub fn resolve_provider(&self, model: Option<&str>) -> Option<ResolvedProvider<'_>> {
+        let model = model.unwrap_or(&self.agents.defaults.model);
         let p = &self.providers;
+        let builtin = |name: &'static str, config| ResolvedProvider {
+            name,
+            config,
+            custom: false,
+        };
 
-        let entries: [(&str, &ProviderConfig); 16] = [
-            ("aihubmix", &p.aihubmix),
-            ("openrouter", &p.openrouter),
-            ("deepseek", &p.deepseek),
-            ("anthropic", &p.anthropic),
-            ("claude", &p.anthropic),
-            ("openai", &p.openai),
-            ("gpt", &p.openai),
-            ("gemini", &p.gemini),
-            ("zhipu", &p.zhipu),
-            ("glm", &p.zhipu),
-            ("dashscope", &p.dashscope),
-            ("qwen", &p.dashscope),
-            ("groq", &p.groq),
-            ("moonshot", &p.moonshot),
-            ("kimi", &p.moonshot),
-            ("vllm", &p.vllm),
+        if let Some((prefix, _)) = model.split_once('/') {
+            if let Some((name, config)) = p.custom.get_key_value(prefix) {
+                return Some(ResolvedProvider {
+                    name,
+                    config,
+                    custom: true,
+                });
+            }
+            let prefix = prefix.to_lowercase();
+            if let Some((name, config)) = p
+                .builtins()
+                .into_iter()
+                .find(|(name, config)| *name == prefix && !config.api_key.is_empty())
+            {
+                return Some(builtin(name, config));
+            }
+        }
+
+        let model = model.to_lowercase();
+        let keywords = [
+            ("aihubmix", "aihubmix"),
+            ("openrouter", "openrouter"),
+            ("deepseek", "deepseek"),
+            ("anthropic", "anthropic"),
+            ("claude", "anthropic"),
+            ("openai", "openai"),
+            ("gpt", "openai"),
+            ("gemini", "gemini"),
+            ("zhipu", "zhipu"),
+            ("glm", "zhipu"),
+            ("dashscope", "dashscope"),
+            ("qwen", "dashscope"),
+            ("groq", "groq"),
+            ("moonshot", "moonshot"),
+            ("kimi", "moonshot"),
+            ("vllm", "vllm"),
         ];
 
-        for (kw, provider) in entries {
-            if model.contains(kw) && !provider.api_key.is_empty() {
-                return Some(provider);
+        for (kw, name) in keywords {
+            if model.contains(kw)
+                && let Some(config) = p.builtin(name)
+                && !config.api_key.is_empty()
+            {
+                return Some(builtin(name, config));
             }
         }
 
         [
-            &p.openrouter,
-            &p.aihubmix,
-            &p.anthropic,
-            &p.openai,
-            &p.deepseek,
-            &p.gemini,
-            &p.zhipu,
-            &p.dashscope,
-            &p.moonshot,
-            &p.vllm,
-            &p.groq,
+            "openrouter",
+            "aihubmix",
+            "anthropic",
+            "openai",
+            "deepseek",
+            "gemini",
+            "zhipu",
+            "dashscope",
+            "moonshot",
+            "vllm",
+            "groq",
         ]
         .into_iter()
-        .find(|provider| !provider.api_key.is_empty())
+        .filter_map(|name| Some((name, p.builtin(name)?)))
+        .find(|(_, config)| !config.api_key.is_empty())
+        .map(|(name, config)| builtin(name, config))
+    }
+
+    pub fn all_providers(&self) -> Vec<ResolvedProvider<'_>> {
+        let builtins =
+            self.providers
+                .builtins()
+                .into_iter()
+                .map(|(name, config)| ResolvedProvider {
+                    name,
+                    config,
+                    custom: false,
+                });
+        let custom = self
+            .providers
+            .custom
+            .iter()
+            .map(|(name, config)| ResolvedProvider {
+                name,
+                config,
+                custom: true,
+            });
+        builtins.chain(custom).collect()
+    }
+
+    pub fn provider_named(&self, name: &str) -> Option<ResolvedProvider<'_>> {
+        let all = self.all_providers();
+        all.iter()
+            .find(|p| p.custom && p.name == name)
+            .or_else(|| all.iter().find(|p| p.name == name))
+            .copied()
+    }
+
+    pub fn get_provider_for_model(&self, model: Option<&str>) -> Option<&ProviderConfig> {
+        self.resolve_provider(model).map(|resolved| resolved.config)
+    }
+
+    pub fn get_provider_name(&self, model: Option<&str>) -> Option<&str> {
+        self.resolve_provider(model).map(|resolved| resolved.name)
     }
 
     pub fn get_api_base(&self, model: Option<&str>) -> Option<String> {
-        let provider = self.get_provider_for_model(model)?;
-        if let Some(base) = &provider.api_base {
-            return Some(base.clone());
-        }
+        self.resolve_provider(model)?.api_base()
+    }
 
-        if std::ptr::eq(provider, &self.providers.openrouter) {
-            return Some("https://openrouter.ai/api/v1".to_string());
-        }
-        if std::ptr::eq(provider, &self.providers.aihubmix) {
-            return Some("https://aihubmix.com/v1".to_string());
-        }
-        None
+    pub fn http_for(&self, provider: &ProviderConfig) -> HttpConfig {
+        self.http.overlay(&provider.http)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn resolves_explicit_custom_and_builtin_providers() {
+        let mut config = Config::default();
+        config.providers.openrouter.api_key = "sk-or".to_string();
+        config.providers.groq.api_key = "gsk".to_string();
+        config.providers.custom.insert(
+            "lab".to_string(),
+            ProviderConfig {
+                api_base: Some("http://10.0.0.5:8000/v1".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let lab = config.resolve_provider(Some("lab/gpt-oss-120b")).unwrap();
+        assert!(lab.custom);
+        assert_eq!(lab.name, "lab");
+        assert_eq!(lab.api_base().as_deref(), Some("http://10.0.0.5:8000/v1"));
+        assert_eq!(lab.api_style(), ApiStyle::Chat);
+
+        let groq = config.resolve_provider(Some("groq/qwen-qwq-32b")).unwrap();
+        assert_eq!(groq.name, "groq");
+        assert_eq!(
+            config.provider_named("anthropic").unwrap().api_style(),
+            ApiStyle::Anthropic
+        );
+
+        assert_eq!(
+            config.get_provider_name(Some("anthropic/claude-opus-4-5")),
+            Some("openrouter")
+        );
+        assert_eq!(
+            config.get_api_base(None).as_deref(),
+            Some("https://openrouter.ai/api/v1")
+        );
+    }
+
+    #[test]
+    fn model_rewrites_use_presets_unless_overridden() {
+        let mut config = Config::default();
+        config.providers.openrouter.api_key = "sk-or".to_string();
+        config.providers.vllm.api_key = "local".to_string();
+        config
+            .providers
+            .custom
+            .insert("lab".to_string(), ProviderConfig::default());
+
+        let wire = |config: &Config, model: &str| {
+            let resolved = config.resolve_provider(Some(model)).unwrap();
+            resolved.model_rewrite().apply(model)
+        };
+        assert_eq!(
+            wire(&config, "openrouter/anthropic/claude-sonnet-4"),
+            "openrouter/anthropic/claude-sonnet-4"
+        );
+        assert_eq!(wire(&config, "vllm/llama-3"), "hosted_vllm/vllm/llama-3");
+        assert_eq!(wire(&config, "lab/qwen3-32b"), "qwen3-32b");
+
+        config.providers.vllm.models = Some(ModelRewrite {
+            aliases: BTreeMap::from([("fast".to_string(), "qwen3-4b-q4".to_string())]),
+            strip_prefixes: vec!["vllm".to_string()],
+            add_prefix: None,
+        });
+        assert_eq!(wire(&config, "vllm/llama-3"), "llama-3");
+        assert_eq!(
+            ModelRewrite::preset("aihubmix").apply("anthropic/claude-opus-4-5"),
+            "openai/claude-opus-4-5"
+        );
+        assert_eq!(
+            config.providers.vllm.models.as_ref().unwrap().apply("fast"),
+            "qwen3-4b-q4"
+        );
     }
 }
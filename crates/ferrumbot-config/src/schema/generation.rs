@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerationProfile {
+    pub max_tokens: Option<usize>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub stop: Vec<String>,
+    pub seed: Option<u64>,
+    pub reasoning_effort: Option<String>,
+}
+
+impl GenerationProfile {
+    pub const PRESETS: [&'static str; 3] = ["precise", "balanced", "creative"];
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "precise" => Some(Self {
+                temperature: Some(0.1),
+                top_p: Some(0.9),
+                ..Default::default()
+            }),
+            "balanced" => Some(Self {
+                temperature: Some(0.7),
+                ..Default::default()
+            }),
+            "creative" => Some(Self {
+                temperature: Some(1.0),
+                top_p: Some(0.95),
+                ..Default::default()
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn overlay(&self, other: &GenerationProfile) -> GenerationProfile {
+        GenerationProfile {
+            max_tokens: other.max_tokens.or(self.max_tokens),
+            temperature: other.temperature.or(self.temperature),
+            top_p: other.top_p.or(self.top_p),
+            stop: if other.stop.is_empty() {
+                self.stop.clone()
+            } else {
+                other.stop.clone()
+            },
+            seed: other.seed.or(self.seed),
+            reasoning_effort: other
+                .reasoning_effort
+                .clone()
+                .or_else(|| self.reasoning_effort.clone()),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use reqwest::{Certificate, Client, ClientBuilder, Proxy};
+use serde::{Deserialize, Serialize};
+
+use crate::expand_tilde;
+
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_READ_TIMEOUT_SECS: u64 = 300;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_timeout_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ca_certs: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insecure: Option<bool>,
+}
+
+impl HttpConfig {
+    pub fn overlay(&self, other: &HttpConfig) -> HttpConfig {
+        let mut ca_certs = self.ca_certs.clone();
+        for cert in &other.ca_certs {
+            if !ca_certs.contains(cert) {
+                ca_certs.push(cert.clone());
+            }
+        }
+        HttpConfig {
+            connect_timeout_secs: other.connect_timeout_secs.or(self.connect_timeout_secs),
+            read_timeout_secs: other.read_timeout_secs.or(self.read_timeout_secs),
+            proxy: other.proxy.clone().or_else(|| self.proxy.clone()),
+            ca_certs,
+            insecure: other.insecure.or(self.insecure),
+        }
+    }
+
+    pub fn client_builder(&self) -> Result<ClientBuilder> {
+        let mut builder = Client::builder()
+            .connect_timeout(Duration::from_secs(
+                self.connect_timeout_secs
+                    .unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS),
+            ))
+            .read_timeout(Duration::from_secs(
+                self.read_timeout_secs.unwrap_or(DEFAULT_READ_TIMEOUT_SECS),
+            ));
+
+        if let Some(proxy) = self.proxy.as_deref().filter(|p| !p.is_empty()) {
+            builder = builder
+                .proxy(Proxy::all(proxy).with_context(|| format!("invalid proxy url {proxy}"))?);
+        }
+        for path in &self.ca_certs {
+            let path = expand_tilde(path);
+            let pem = std::fs::read(&path)
+                .with_context(|| format!("failed to read CA certificate {}", path.display()))?;
+            let certs = Certificate::from_pem_bundle(&pem)
+                .with_context(|| format!("invalid CA certificate {}", path.display()))?;
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        if self.insecure.unwrap_or(false) {
+            tracing::warn!("TLS certificate verification is disabled");
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        Ok(builder)
+    }
+
+    pub fn client(&self) -> Result<Client> {
+        Ok(self.client_builder()?.build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlay_prefers_specific_settings_and_merges_certs() {
+        let global = HttpConfig {
+            connect_timeout_secs: Some(5),
+            proxy: Some("http://proxy.corp:3128".to_string()),
+            ca_certs: vec!["~/corp-ca.pem".to_string()],
+            ..Default::default()
+        };
+        let provider = HttpConfig {
+            read_timeout_secs: Some(30),
+            ca_certs: vec!["/etc/gateway-ca.pem".to_string()],
+            insecure: Some(true),
+            ..Default::default()
+        };
+
+        let merged = global.overlay(&provider);
+        assert_eq!(merged.connect_timeout_secs, Some(5));
+        assert_eq!(merged.read_timeout_secs, Some(30));
+        assert_eq!(merged.proxy.as_deref(), Some("http://proxy.corp:3128"));
+        assert_eq!(merged.ca_certs.len(), 2);
+        assert_eq!(merged.insecure, Some(true));
+    }
+
+    #[test]
+    fn client_reports_bad_settings() {
+        assert!(HttpConfig::default().client().is_ok());
+
+        let missing_cert = HttpConfig {
+            ca_certs: vec!["/nonexistent/ca.pem".to_string()],
+            ..Default::default()
+        };
+        let err = missing_cert.client().unwrap_err();
+        assert!(err.to_string().contains("/nonexistent/ca.pem"));
+
+        let bad_proxy = HttpConfig {
+            proxy: Some("not a url".to_string()),
+            ..Default::default()
+        };
+        assert!(bad_proxy.client().is_err());
+    }
+}
@@ -1,13 +1,23 @@
 mod agents;
+mod capabilities;
 mod channels;
 mod config;
+mod embeddings;
 mod gateway;
+mod generation;
+mod http;
+mod pricing;
 mod providers;
 mod tools;
 
 pub use agents::*;
+pub use capabilities::*;
 pub use channels::*;
 pub use config::*;
+pub use embeddings::*;
 pub use gateway::*;
+pub use generation::*;
+pub use http::*;
+pub use pricing::*;
 pub use providers::*;
 pub use tools::*;
@@ -0,0 +1,51 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelPricing {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached_input_per_mtok: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_write_per_mtok: Option<f64>,
+}
+
+impl ModelPricing {
+    pub fn cost(&self, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+        self.cost_with_cache(prompt_tokens, 0, 0, completion_tokens)
+    }
+
+    pub fn cost_with_cache(
+        &self,
+        prompt_tokens: u64,
+        cached_tokens: u64,
+        cache_write_tokens: u64,
+        completion_tokens: u64,
+    ) -> f64 {
+        let uncached = prompt_tokens.saturating_sub(cached_tokens + cache_write_tokens);
+        (uncached as f64 * self.input_per_mtok
+            + cached_tokens as f64 * self.cached_input_per_mtok.unwrap_or(self.input_per_mtok)
+            + cache_write_tokens as f64 * self.cache_write_per_mtok.unwrap_or(self.input_per_mtok)
+            + completion_tokens as f64 * self.output_per_mtok)
+            / 1_000_000.0
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PricingTable(pub BTreeMap<String, ModelPricing>);
+
+impl PricingTable {
+    pub fn get(&self, model: &str) -> Option<&ModelPricing> {
+        let mut candidate = model;
+        loop {
+            if let Some(pricing) = self.0.get(candidate) {
+                return Some(pricing);
+            }
+            candidate = candidate.split_once('/')?.1;
+        }
+    }
+}
@@ -2,12 +2,156 @@ use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
+use super::HttpConfig;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub multiplier: f64,
+    pub jitter: f64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 20_000,
+            multiplier: 2.0,
+            jitter: 0.2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens_per_minute: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<usize>,
+}
+
+impl RateLimitConfig {
+    pub fn is_unlimited(&self) -> bool {
+        self.requests_per_minute.is_none()
+            && self.tokens_per_minute.is_none()
+            && self.max_concurrent.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelRewrite {
+    pub aliases: BTreeMap<String, String>,
+    pub strip_prefixes: Vec<String>,
+    pub add_prefix: Option<String>,
+}
+
+impl ModelRewrite {
+    pub fn preset(provider: &str) -> Self {
+        let rule = |strip: &[&str], add: Option<&str>| Self {
+            aliases: BTreeMap::new(),
+            strip_prefixes: strip.iter().map(ToString::to_string).collect(),
+            add_prefix: add.map(ToString::to_string),
+        };
+        match provider {
+            "anthropic" => rule(&["anthropic"], None),
+            "openrouter" => rule(&[], Some("openrouter")),
+            "aihubmix" => rule(&["*"], Some("openai")),
+            "vllm" => rule(&[], Some("hosted_vllm")),
+            "zhipu" => rule(&["zhipu", "zai"], Some("zai")),
+            "dashscope" => rule(&["dashscope"], Some("dashscope")),
+            "moonshot" => rule(&["moonshot"], Some("moonshot")),
+            "gemini" => rule(&["gemini"], Some("gemini")),
+            _ => Self::default(),
+        }
+    }
+
+    pub fn strip(prefix: &str) -> Self {
+        Self {
+            strip_prefixes: vec![prefix.to_string()],
+            ..Default::default()
+        }
+    }
+
+    pub fn apply(&self, model: &str) -> String {
+        if let Some(alias) = self.aliases.get(model) {
+            return alias.clone();
+        }
+
+        let mut wire = model;
+        for prefix in &self.strip_prefixes {
+            if prefix == "*" {
+                wire = wire.rsplit('/').next().unwrap_or(wire);
+                break;
+            }
+            if let Some(rest) = wire
+                .strip_prefix(prefix.as_str())
+                .and_then(|rest| rest.strip_prefix('/'))
+            {
+                wire = rest;
+                break;
+            }
+        }
+
+        match &self.add_prefix {
+            Some(prefix) if !wire.starts_with(&format!("{prefix}/")) => format!("{prefix}/{wire}"),
+            _ => wire.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolCallMode {
+    #[default]
+    Native,
+    Parse,
+    Prompt,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiStyle {
+    #[default]
+    Auto,
+    Chat,
+    Responses,
+    Anthropic,
+    Ollama,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OllamaConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>,
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ProviderConfig {
     pub api_key: String,
     pub api_base: Option<String>,
     pub extra_headers: Option<BTreeMap<String, String>>,
+    pub retry: RetryConfig,
+    pub rate_limit: RateLimitConfig,
+    pub models: Option<ModelRewrite>,
+    pub tool_calls: ToolCallMode,
+    pub api_style: ApiStyle,
+    pub http: HttpConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_cache: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ollama: Option<OllamaConfig>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -24,4 +168,38 @@ pub struct ProvidersConfig {
     pub gemini: ProviderConfig,
     pub moonshot: ProviderConfig,
     pub aihubmix: ProviderConfig,
+    pub custom: BTreeMap<String, ProviderConfig>,
+}
+
+impl ProvidersConfig {
+    pub fn builtins(&self) -> [(&'static str, &ProviderConfig); 11] {
+        [
+            ("anthropic", &self.anthropic),
+            ("openai", &self.openai),
+            ("openrouter", &self.openrouter),
+            ("deepseek", &self.deepseek),
+            ("groq", &self.groq),
+            ("zhipu", &self.zhipu),
+            ("dashscope", &self.dashscope),
+            ("vllm", &self.vllm),
+            ("gemini", &self.gemini),
+            ("moonshot", &self.moonshot),
+            ("aihubmix", &self.aihubmix),
+        ]
+    }
+
+    pub fn builtin(&self, name: &str) -> Option<&ProviderConfig> {
+        self.builtins()
+            .into_iter()
+            .find(|(candidate, _)| *candidate == name)
+            .map(|(_, provider)| provider)
+    }
+}
+
+pub fn default_api_base(provider: &str) -> Option<&'static str> {
+    match provider {
+        "openrouter" => Some("https://openrouter.ai/api/v1"),
+        "aihubmix" => Some("https://aihubmix.com/v1"),
+        _ => None,
+    }
 }
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::HttpConfig;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct WebSearchConfig {
@@ -20,6 +22,7 @@ impl Default for WebSearchConfig {
 #[serde(default)]
 pub struct WebToolsConfig {
     pub search: WebSearchConfig,
+    pub http: HttpConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,11 +37,24 @@ impl Default for ExecToolConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryToolConfig {
+    pub max_chars: usize,
+}
+
+impl Default for MemoryToolConfig {
+    fn default() -> Self {
+        Self { max_chars: 6000 }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ToolsConfig {
     pub web: WebToolsConfig,
     pub exec: ExecToolConfig,
+    pub memory: MemoryToolConfig,
     pub restrict_to_workspace: bool,
 }
 
@@ -47,6 +63,7 @@ impl Default for ToolsConfig {
         Self {
             web: WebToolsConfig::default(),
             exec: ExecToolConfig::default(),
+            memory: MemoryToolConfig::default(),
             restrict_to_workspace: true,
         }
     }
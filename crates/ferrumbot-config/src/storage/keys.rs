@@ -1,29 +1,45 @@
 use serde_json::Value;
 
+const VERBATIM_MAPS: &[&str] = &[
+    "custom",
+    "aliases",
+    "capabilities",
+    "extra_headers",
+    "context_windows",
+    "pricing",
+    "profiles",
+    "channel_profiles",
+];
+
 pub(crate) fn normalize_keys(value: Value) -> Value {
-    match value {
-        Value::Object(map) => {
-            let converted = map
-                .into_iter()
-                .map(|(k, v)| (camel_to_snake(&k), normalize_keys(v)))
-                .collect();
-            Value::Object(converted)
-        }
-        Value::Array(items) => Value::Array(items.into_iter().map(normalize_keys).collect()),
-        other => other,
-    }
+    convert_keys(value, camel_to_snake, false)
 }
 
 pub(crate) fn to_camel_case_keys(value: Value) -> Value {
+    convert_keys(value, snake_to_camel, false)
+}
+
+fn convert_keys(value: Value, convert: fn(&str) -> String, keep_keys: bool) -> Value {
     match value {
         Value::Object(map) => {
             let converted = map
                 .into_iter()
-                .map(|(k, v)| (snake_to_camel(&k), to_camel_case_keys(v)))
+                .map(|(k, v)| {
+                    let key = if keep_keys { k.clone() } else { convert(&k) };
+                    let verbatim = !keep_keys
+                        && (VERBATIM_MAPS.contains(&k.as_str())
+                            || VERBATIM_MAPS.contains(&key.as_str()));
+                    (key, convert_keys(v, convert, verbatim))
+                })
                 .collect();
             Value::Object(converted)
         }
-        Value::Array(items) => Value::Array(items.into_iter().map(to_camel_case_keys).collect()),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| convert_keys(item, convert, false))
+                .collect(),
+        ),
         other => other,
     }
 }
@@ -56,3 +72,30 @@ fn snake_to_camel(input: &str) -> String {
     }
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn keeps_user_defined_map_keys_verbatim() {
+        let raw = json!({
+            "providers": {
+                "custom": {
+                    "myLab": {
+                        "apiBase": "http://localhost:8000/v1",
+                        "extraHeaders": {"X-Title": "ferrum"}
+                    }
+                }
+            }
+        });
+
+        let normalized = normalize_keys(raw.clone());
+        let lab = &normalized["providers"]["custom"]["myLab"];
+        assert_eq!(lab["api_base"], "http://localhost:8000/v1");
+        assert_eq!(lab["extra_headers"]["X-Title"], "ferrum");
+        assert_eq!(to_camel_case_keys(normalized), raw);
+    }
+}
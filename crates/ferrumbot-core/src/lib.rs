@@ -1,9 +1,13 @@
 mod bus;
 mod messages;
 mod session;
+mod tokens;
+mod usage;
 mod utils;
 
 pub use bus::MessageBus;
 pub use messages::{InboundMessage, OutboundMessage};
-pub use session::{Session, SessionManager, SessionMessage};
+pub use session::{Session, SessionManager, SessionMessage, ToolReplay};
+pub use tokens::{HeuristicEstimator, TokenEstimator};
+pub use usage::{Usage, UsageLedger, UsageRecord, summarize_usage};
 pub use utils::{ensure_dir, safe_filename, today_date};
@@ -15,6 +15,18 @@ pub struct InboundMessage {
 }
 
 impl InboundMessage {
+    pub fn new(channel: &str, sender_id: &str, chat_id: &str, content: &str) -> Self {
+        Self {
+            channel: channel.to_string(),
+            sender_id: sender_id.to_string(),
+            chat_id: chat_id.to_string(),
+            content: content.to_string(),
+            timestamp: Utc::now(),
+            media: Vec::new(),
+            metadata: HashMap::new(),
+        }
+    }
+
     pub fn session_key(&self) -> String {
         format!("{}:{}", self.channel, self.chat_id)
     }
@@ -17,7 +17,10 @@ pub struct SessionManager {
 
 impl SessionManager {
     pub fn new() -> Result<Self> {
-        let sessions_dir = data_dir().join("sessions");
+        Self::open(data_dir().join("sessions"))
+    }
+
+    pub fn open(sessions_dir: PathBuf) -> Result<Self> {
         fs::create_dir_all(&sessions_dir)?;
         Ok(Self {
             sessions_dir,
@@ -3,4 +3,4 @@ mod manager;
 mod model;
 
 pub use manager::SessionManager;
-pub use model::{Session, SessionMessage};
+pub use model::{Session, SessionMessage, ToolReplay};
@@ -2,13 +2,35 @@ use std::collections::HashMap;
 
 use chrono::{DateTime, Local, Utc};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{Value, json};
+
+use crate::TokenEstimator;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionMessage {
     pub role: String,
     pub content: String,
     pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub media: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thinking_blocks: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ToolReplay {
+    #[default]
+    Off,
+    Full,
+    Truncated(usize),
 }
 
 #[derive(Debug, Clone)]
@@ -20,6 +42,67 @@ pub struct Session {
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
+impl SessionMessage {
+    fn history_content(&self) -> String {
+        if self.media.is_empty() {
+            return self.content.clone();
+        }
+        let names: Vec<&str> = self
+            .media
+            .iter()
+            .map(|reference| {
+                reference
+                    .rsplit(['/', '\\'])
+                    .next()
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or(reference)
+            })
+            .collect();
+        format!("{}\n[attachments: {}]", self.content, names.join(", "))
+    }
+
+    fn is_tool_step(&self) -> bool {
+        self.role == "tool" || !self.tool_calls.is_empty()
+    }
+
+    fn history_entry(&self, replay: ToolReplay) -> Option<Value> {
+        if !self.is_tool_step() {
+            return Some(json!({"role": self.role, "content": self.history_content()}));
+        }
+        let limit = match replay {
+            ToolReplay::Off => return None,
+            ToolReplay::Full => usize::MAX,
+            ToolReplay::Truncated(limit) => limit,
+        };
+        if self.role == "tool" {
+            return Some(json!({
+                "role": "tool",
+                "tool_call_id": self.tool_call_id.as_deref().unwrap_or_default(),
+                "name": self.name.as_deref().unwrap_or_default(),
+                "content": truncate_result(&self.content, limit),
+            }));
+        }
+        let mut entry = json!({
+            "role": self.role,
+            "content": self.content,
+            "tool_calls": self.tool_calls,
+        });
+        if !self.thinking_blocks.is_empty() {
+            entry["thinking_blocks"] = json!(self.thinking_blocks);
+        }
+        Some(entry)
+    }
+}
+
+fn truncate_result(content: &str, limit: usize) -> String {
+    let total = content.chars().count();
+    if total <= limit {
+        return content.to_string();
+    }
+    let kept: String = content.chars().take(limit).collect();
+    format!("{kept}\n[... truncated, {total} chars in full]")
+}
+
 impl Session {
     pub fn new(key: String) -> Self {
         let now = Utc::now();
@@ -33,19 +116,198 @@ impl Session {
     }
 
     pub fn add_message(&mut self, role: &str, content: &str) {
+        self.add_message_with_media(role, content, &[]);
+    }
+
+    pub fn add_message_with_media(&mut self, role: &str, content: &str, media: &[String]) {
         self.messages.push(SessionMessage {
             role: role.to_string(),
             content: content.to_string(),
             timestamp: Local::now().to_rfc3339(),
+            media: media.to_vec(),
+            reasoning: None,
+            tool_calls: Vec::new(),
+            thinking_blocks: Vec::new(),
+            tool_call_id: None,
+            name: None,
         });
         self.updated_at = Utc::now();
     }
 
-    pub fn get_history(&self, max_messages: usize) -> Vec<serde_json::Value> {
-        let start = self.messages.len().saturating_sub(max_messages);
+    pub fn add_assistant_message(&mut self, content: &str, reasoning: Option<&str>) {
+        self.add_message("assistant", content);
+        if let Some(last) = self.messages.last_mut() {
+            last.reasoning = reasoning.map(ToString::to_string);
+        }
+    }
+
+    pub fn add_tool_calls(
+        &mut self,
+        content: &str,
+        tool_calls: Vec<Value>,
+        thinking_blocks: Vec<Value>,
+    ) {
+        self.add_message("assistant", content);
+        if let Some(last) = self.messages.last_mut() {
+            last.tool_calls = tool_calls;
+            last.thinking_blocks = thinking_blocks;
+        }
+    }
+
+    pub fn add_tool_result(&mut self, tool_call_id: &str, name: &str, result: &str) {
+        self.add_message("tool", result);
+        if let Some(last) = self.messages.last_mut() {
+            last.tool_call_id = Some(tool_call_id.to_string());
+            last.name = Some(name.to_string());
+        }
+    }
+
+    pub fn summary(&self) -> Option<&str> {
+        self.metadata
+            .get("summary")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+    }
+
+    pub fn compacted(&self) -> usize {
+        self.metadata
+            .get("compacted")
+            .and_then(|v| v.as_u64())
+            .map_or(0, |n| (n as usize).min(self.messages.len()))
+    }
+
+    pub fn set_summary(&mut self, summary: &str, compacted: usize) {
+        self.metadata.insert("summary".to_string(), json!(summary));
+        self.metadata
+            .insert("compacted".to_string(), json!(compacted));
+        self.updated_at = Utc::now();
+    }
+
+    pub fn get_history(&self, max_messages: usize) -> Vec<Value> {
+        self.get_history_replaying(max_messages, ToolReplay::Off)
+    }
+
+    pub fn get_history_replaying(&self, max_messages: usize, replay: ToolReplay) -> Vec<Value> {
+        let start = self
+            .messages
+            .len()
+            .saturating_sub(max_messages)
+            .max(self.compacted());
         self.messages[start..]
             .iter()
-            .map(|m| json!({"role": m.role, "content": m.content}))
+            .skip_while(|m| m.role == "tool")
+            .filter_map(|m| m.history_entry(replay))
             .collect()
     }
+
+    pub fn get_history_within(
+        &self,
+        max_messages: usize,
+        budget: usize,
+        replay: ToolReplay,
+        estimator: &dyn TokenEstimator,
+    ) -> Vec<Value> {
+        let mut used = 0;
+        let mut history: Vec<Value> = self
+            .get_history_replaying(max_messages, replay)
+            .into_iter()
+            .rev()
+            .take_while(|message| {
+                used += estimator.estimate_message(message);
+                used <= budget
+            })
+            .collect();
+        history.reverse();
+        let leading = history
+            .iter()
+            .take_while(|message| message["role"] != "user")
+            .count();
+        history.split_off(leading)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::HeuristicEstimator;
+
+    use super::*;
+
+    #[test]
+    fn history_window_keeps_newest_turns_within_budget() {
+        let mut session = Session::new("cli:test".to_string());
+        session.add_message("user", &"a".repeat(400));
+        session.add_message("assistant", "first reply");
+        session.add_message("user", "short question");
+        session.add_message("assistant", "short answer");
+
+        let all = session.get_history_within(50, 10_000, ToolReplay::Off, &HeuristicEstimator);
+        assert_eq!(all.len(), 4);
+
+        let recent = session.get_history_within(50, 40, ToolReplay::Off, &HeuristicEstimator);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0]["content"], "short question");
+
+        assert!(
+            session
+                .get_history_within(50, 5, ToolReplay::Off, &HeuristicEstimator)
+                .is_empty()
+        );
+
+        session.set_summary("User sent a long message.", 2);
+        let history = session.get_history(50);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0]["content"], "short question");
+        assert_eq!(session.summary(), Some("User sent a long message."));
+    }
+
+    #[test]
+    fn tool_steps_replay_in_call_order_and_old_lines_still_load() {
+        let old: SessionMessage = serde_json::from_str(
+            r#"{"role":"user","content":"hi","timestamp":"2025-01-01T00:00:00+00:00"}"#,
+        )
+        .unwrap();
+        assert!(old.tool_calls.is_empty() && old.tool_call_id.is_none());
+
+        let mut session = Session::new("cli:test".to_string());
+        session.messages.push(old);
+        session.add_tool_calls(
+            "",
+            vec![json!({
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "read_file", "arguments": "{\"path\":\"a.txt\"}"},
+            })],
+            vec![json!({"type": "thinking", "thinking": "look", "signature": "sig"})],
+        );
+        session.add_tool_result("call_1", "read_file", &"x".repeat(50));
+        session.add_assistant_message("done", None);
+
+        let plain = session.get_history(50);
+        assert_eq!(plain.len(), 2);
+        assert_eq!(plain[1]["content"], "done");
+
+        let replayed = session.get_history_replaying(50, ToolReplay::Truncated(10));
+        let roles: Vec<&str> = replayed
+            .iter()
+            .map(|m| m["role"].as_str().unwrap())
+            .collect();
+        assert_eq!(roles, ["user", "assistant", "tool", "assistant"]);
+        assert_eq!(replayed[1]["tool_calls"][0]["id"], "call_1");
+        assert_eq!(replayed[1]["thinking_blocks"][0]["signature"], "sig");
+        assert_eq!(replayed[2]["tool_call_id"], "call_1");
+        assert!(
+            replayed[2]["content"]
+                .as_str()
+                .unwrap()
+                .starts_with("xxxxxxxxxx\n[...")
+        );
+
+        let window = session.get_history_replaying(2, ToolReplay::Full);
+        assert_eq!(window.len(), 1);
+        assert_eq!(window[0]["content"], "done");
+
+        let line = serde_json::to_string(&session.messages[2]).unwrap();
+        let restored: SessionMessage = serde_json::from_str(&line).unwrap();
+        assert_eq!(restored.name.as_deref(), Some("read_file"));
+    }
 }
@@ -0,0 +1,63 @@
+use serde_json::Value;
+
+const MESSAGE_OVERHEAD: usize = 4;
+const ATTACHMENT_TOKENS: usize = 1024;
+
+pub trait TokenEstimator: Send + Sync {
+    fn estimate(&self, text: &str) -> usize;
+
+    fn estimate_message(&self, message: &Value) -> usize {
+        let content = match message.get("content") {
+            Some(Value::String(text)) => self.estimate(text),
+            Some(Value::Null) | None => 0,
+            Some(Value::Array(parts)) => parts
+                .iter()
+                .map(|part| match part.get("text").and_then(Value::as_str) {
+                    Some(text) => self.estimate(text),
+                    None => ATTACHMENT_TOKENS,
+                })
+                .sum(),
+            Some(other) => self.estimate(&other.to_string()),
+        };
+        let tool_calls = message
+            .get("tool_calls")
+            .map(|calls| self.estimate(&calls.to_string()))
+            .unwrap_or(0);
+        content + tool_calls + MESSAGE_OVERHEAD
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicEstimator;
+
+impl TokenEstimator for HeuristicEstimator {
+    fn estimate(&self, text: &str) -> usize {
+        let (ascii, wide) = text.chars().fold((0usize, 0usize), |(ascii, wide), ch| {
+            if ch.is_ascii() {
+                (ascii + 1, wide)
+            } else {
+                (ascii, wide + 1)
+            }
+        });
+        ascii.div_ceil(4) + wide
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn heuristic_counts_ascii_and_wide_chars() {
+        let estimator = HeuristicEstimator;
+        assert_eq!(estimator.estimate(""), 0);
+        assert_eq!(estimator.estimate("hello world!"), 3);
+        assert_eq!(estimator.estimate("你好"), 2);
+        assert_eq!(
+            estimator.estimate_message(&json!({"role": "user", "content": "abcd"})),
+            5
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use ferrumbot_config::{ModelPricing, data_dir};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Usage {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub cached_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub cost: f64,
+}
+
+impl Usage {
+    pub fn from_counts(counts: &BTreeMap<String, i64>, pricing: Option<&ModelPricing>) -> Self {
+        let count = |key: &str| counts.get(key).copied().unwrap_or(0).max(0) as u64;
+        let prompt_tokens = count("prompt_tokens");
+        let completion_tokens = count("completion_tokens");
+        let cached_tokens = count("cached_tokens");
+        let cache_write_tokens = count("cache_write_tokens");
+        let total_tokens = match count("total_tokens") {
+            0 => prompt_tokens + completion_tokens,
+            total => total,
+        };
+        Self {
+            requests: 1,
+            prompt_tokens,
+            completion_tokens,
+            total_tokens,
+            cached_tokens,
+            cache_write_tokens,
+            cost: pricing
+                .map(|pricing| {
+                    pricing.cost_with_cache(
+                        prompt_tokens,
+                        cached_tokens,
+                        cache_write_tokens,
+                        completion_tokens,
+                    )
+                })
+                .unwrap_or(0.0),
+        }
+    }
+
+    pub fn add(&mut self, other: &Usage) {
+        self.requests += other.requests;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+        self.cached_tokens += other.cached_tokens;
+        self.cache_write_tokens += other.cache_write_tokens;
+        self.cost += other.cost;
+    }
+
+    pub fn from_metadata(metadata: &HashMap<String, serde_json::Value>) -> Self {
+        metadata
+            .get("usage")
+            .cloned()
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub timestamp: String,
+    pub session: String,
+    pub model: String,
+    #[serde(flatten)]
+    pub usage: Usage,
+}
+
+impl UsageRecord {
+    pub fn new(session: &str, model: &str, usage: Usage) -> Self {
+        Self {
+            timestamp: Local::now().to_rfc3339(),
+            session: session.to_string(),
+            model: model.to_string(),
+            usage,
+        }
+    }
+
+    pub fn date(&self) -> &str {
+        self.timestamp.get(..10).unwrap_or(&self.timestamp)
+    }
+}
+
+#[derive(Clone)]
+pub struct UsageLedger {
+    dir: PathBuf,
+}
+
+impl UsageLedger {
+    pub fn new() -> Result<Self> {
+        Self::open(data_dir().join("usage"))
+    }
+
+    pub fn open(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    pub fn record(&self, record: &UsageRecord) -> Result<()> {
+        let path = self.dir.join(format!("{}.jsonl", record.date()));
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("failed to open usage ledger: {}", path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+        Ok(())
+    }
+
+    pub fn load(&self, since: Option<&str>) -> Result<Vec<UsageRecord>> {
+        let mut days = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("jsonl")
+                && let Some(day) = path.file_stem().and_then(|s| s.to_str())
+                && since.is_none_or(|since| day >= since)
+            {
+                days.push(path);
+            }
+        }
+        days.sort();
+
+        let mut records = Vec::new();
+        for path in days {
+            let file = OpenOptions::new().read(true).open(&path)?;
+            for line in BufReader::new(file).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str(&line) {
+                    Ok(record) => records.push(record),
+                    Err(err) => {
+                        tracing::warn!("skipping usage record in {}: {err}", path.display())
+                    }
+                }
+            }
+        }
+        Ok(records)
+    }
+}
+
+pub fn summarize_usage<F>(records: &[UsageRecord], key: F) -> BTreeMap<String, Usage>
+where
+    F: Fn(&UsageRecord) -> String,
+{
+    let mut summary: BTreeMap<String, Usage> = BTreeMap::new();
+    for record in records {
+        summary.entry(key(record)).or_default().add(&record.usage);
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ledger_round_trips_and_summarizes() {
+        let dir = std::env::temp_dir().join(format!("ferrumbot-usage-{}", std::process::id()));
+        let ledger = UsageLedger::open(dir.clone()).unwrap();
+        let pricing = ModelPricing {
+            input_per_mtok: 3.0,
+            output_per_mtok: 15.0,
+            cached_input_per_mtok: Some(0.3),
+            ..Default::default()
+        };
+        let counts = BTreeMap::from([
+            ("prompt_tokens".to_string(), 1000),
+            ("completion_tokens".to_string(), 200),
+        ]);
+        let usage = Usage::from_counts(&counts, Some(&pricing));
+        assert_eq!(usage.total_tokens, 1200);
+        assert!((usage.cost - 0.006).abs() < 1e-9);
+
+        let cached = Usage::from_counts(
+            &BTreeMap::from([
+                ("prompt_tokens".to_string(), 1000),
+                ("cached_tokens".to_string(), 800),
+                ("completion_tokens".to_string(), 200),
+            ]),
+            Some(&pricing),
+        );
+        assert_eq!(cached.cached_tokens, 800);
+        assert!((cached.cost - 0.00384).abs() < 1e-9);
+
+        ledger
+            .record(&UsageRecord::new("cli:a", "m1", usage.clone()))
+            .unwrap();
+        ledger
+            .record(&UsageRecord::new("cli:b", "m1", usage))
+            .unwrap();
+
+        let records = ledger.load(None).unwrap();
+        let by_model = summarize_usage(&records, |r| r.model.clone());
+        assert_eq!(by_model["m1"].requests, 2);
+        assert_eq!(by_model["m1"].prompt_tokens, 2000);
+        assert!(ledger.load(Some("9999-01-01")).unwrap().is_empty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
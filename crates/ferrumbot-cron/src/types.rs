@@ -40,6 +40,7 @@ pub struct CronPayload {
     pub deliver: bool,
     pub channel: Option<String>,
     pub to: Option<String>,
+    pub profile: Option<String>,
 }
 
 impl Default for CronPayload {
@@ -50,6 +51,7 @@ impl Default for CronPayload {
             deliver: false,
             channel: None,
             to: None,
+            profile: None,
         }
     }
 }
@@ -0,0 +1,53 @@
+use anyhow::Result;
+use reqwest::Client;
+
+use ferrumbot_config::{Config, ModelRewrite, ProviderConfig};
+
+use super::{AnthropicProvider, DEFAULT_API_BASE};
+
+impl AnthropicProvider {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let model = config.agents.defaults.model.clone();
+        let provider = config.providers.anthropic.clone();
+        let client = config.http_for(&provider).client()?;
+        Ok(Self::from_provider(model, &provider, provider.api_base.clone())?.with_client(client))
+    }
+
+    pub fn from_provider(
+        model: String,
+        provider: &ProviderConfig,
+        api_base: Option<String>,
+    ) -> Result<Self> {
+        Ok(Self {
+            client: provider.http.client()?,
+            api_key: provider.api_key.clone(),
+            api_base: api_base.unwrap_or_else(|| DEFAULT_API_BASE.to_string()),
+            default_model: model,
+            extra_headers: provider.extra_headers.clone().unwrap_or_default(),
+            rewrite: provider
+                .models
+                .clone()
+                .unwrap_or_else(|| ModelRewrite::preset("anthropic")),
+            prompt_cache: provider.prompt_cache.unwrap_or(true),
+        })
+    }
+
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn with_prompt_cache(mut self, enabled: bool) -> Self {
+        self.prompt_cache = enabled;
+        self
+    }
+
+    pub fn with_model_rewrite(mut self, rewrite: ModelRewrite) -> Self {
+        self.rewrite = rewrite;
+        self
+    }
+
+    pub(super) fn wire_model(&self, model: &str) -> String {
+        self.rewrite.apply(model)
+    }
+}
@@ -0,0 +1,167 @@
+use async_trait::async_trait;
+use reqwest::Response;
+use serde_json::{Value, json};
+
+use crate::provider::parse_model_ids;
+use crate::{
+    ChatOptions, ChatStream, LlmProvider, LlmResponse, ProviderError, ProviderResult, tag_model,
+};
+
+use super::convert::{
+    mark_cache_breakpoints, strip_cache_markers, system_value, to_anthropic_messages,
+    to_anthropic_tools,
+};
+use super::parse::parse_messages_response;
+use super::stream::sse_stream;
+use super::{API_VERSION, AnthropicProvider};
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<LlmResponse> {
+        let model = model.unwrap_or(&self.default_model).to_string();
+        let body = self.build_body(messages, tools, &model, options);
+        let resp = self.send(&body).await?;
+        let payload: Value = resp.json().await?;
+        let mut resp = parse_messages_response(payload)?;
+        resp.model = Some(model);
+        Ok(resp)
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<ChatStream> {
+        let model = model.unwrap_or(&self.default_model).to_string();
+        let mut body = self.build_body(messages, tools, &model, options);
+        body["stream"] = Value::Bool(true);
+
+        let resp = self.send(&body).await?;
+        Ok(tag_model(sse_stream(resp), model))
+    }
+
+    async fn list_models(&self) -> ProviderResult<Vec<String>> {
+        if self.api_key.is_empty() {
+            return Err(ProviderError::Auth("API key not configured".to_string()));
+        }
+
+        let url = format!("{}/models?limit=1000", self.api_base.trim_end_matches('/'));
+        let mut req = self
+            .client
+            .get(url)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", API_VERSION);
+        for (k, v) in &self.extra_headers {
+            req = req.header(k, v);
+        }
+
+        let resp = req.send().await?;
+        if !resp.status().is_success() {
+            return Err(ProviderError::from_response(resp).await);
+        }
+        let payload: Value = resp.json().await?;
+        parse_model_ids(&payload)
+    }
+
+    fn get_default_model(&self) -> &str {
+        &self.default_model
+    }
+}
+
+impl AnthropicProvider {
+    fn build_body(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: &str,
+        options: &ChatOptions,
+    ) -> Value {
+        let (mut system, mut messages) = to_anthropic_messages(messages);
+        if !self.prompt_cache {
+            strip_cache_markers(&mut messages);
+        }
+        let max_tokens = options.max_tokens.unwrap_or(4096);
+        let mut body = json!({
+            "model": self.wire_model(model),
+            "messages": messages,
+            "max_tokens": max_tokens,
+        });
+        match options.reasoning_effort.as_deref().map(thinking_budget) {
+            Some(budget) => {
+                body["max_tokens"] = json!(max_tokens + budget);
+                body["thinking"] = json!({"type": "enabled", "budget_tokens": budget});
+            }
+            None => {
+                body["temperature"] = json!(options.temperature.unwrap_or(0.7));
+                if let Some(top_p) = options.top_p {
+                    body["top_p"] = json!(top_p);
+                }
+            }
+        }
+        if !options.stop.is_empty() {
+            body["stop_sequences"] = json!(options.stop);
+        }
+
+        if let Some(format) = &options.response_format {
+            system.push(json!({"type": "text", "text": format.instructions()}));
+        }
+        if let Some(system) = system_value(system, self.prompt_cache) {
+            body["system"] = system;
+        }
+        if let Some(tools) = tools.filter(|t| !t.is_empty()) {
+            body["tools"] = Value::Array(to_anthropic_tools(tools));
+            body["tool_choice"] = json!({"type": "auto"});
+            if options.parallel_tool_calls == Some(false) {
+                body["tool_choice"]["disable_parallel_tool_use"] = Value::Bool(true);
+            }
+        }
+        if self.prompt_cache {
+            mark_cache_breakpoints(&mut body);
+        }
+        body
+    }
+
+    async fn send(&self, body: &Value) -> ProviderResult<Response> {
+        if self.api_key.is_empty() {
+            return Err(ProviderError::Auth("API key not configured".to_string()));
+        }
+
+        let url = format!("{}/messages", self.api_base.trim_end_matches('/'));
+        tracing::debug!("Request URL: {}", url);
+        tracing::debug!("Request body: {:?}", body);
+
+        let mut req = self
+            .client
+            .post(url)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", API_VERSION)
+            .json(body);
+
+        for (k, v) in &self.extra_headers {
+            req = req.header(k, v);
+        }
+
+        let resp = req.send().await?;
+        tracing::debug!("Response status: {:?}", resp.status());
+        if !resp.status().is_success() {
+            return Err(ProviderError::from_response(resp).await);
+        }
+        Ok(resp)
+    }
+}
+
+fn thinking_budget(effort: &str) -> usize {
+    match effort {
+        "low" | "minimal" => 1024,
+        "high" => 16_384,
+        _ => 4096,
+    }
+}
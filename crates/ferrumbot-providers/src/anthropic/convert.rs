@@ -0,0 +1,205 @@
+use serde_json::{Value, json};
+
+pub(super) fn to_anthropic_messages(messages: Vec<Value>) -> (Vec<Value>, Vec<Value>) {
+    let mut system = Vec::new();
+    let mut out: Vec<Value> = Vec::new();
+
+    for message in messages {
+        let role = message
+            .get("role")
+            .and_then(|v| v.as_str())
+            .unwrap_or("user");
+
+        let (role, blocks) = match role {
+            "system" => {
+                system.extend(user_blocks(&message));
+                continue;
+            }
+            "assistant" => ("assistant", assistant_blocks(&message)),
+            "tool" => ("user", vec![tool_result_block(&message)]),
+            _ => ("user", user_blocks(&message)),
+        };
+
+        if blocks.is_empty() {
+            continue;
+        }
+        push_merged(&mut out, role, blocks);
+    }
+
+    (system, out)
+}
+
+pub(super) fn system_value(blocks: Vec<Value>, cache: bool) -> Option<Value> {
+    if blocks.is_empty() {
+        return None;
+    }
+    if cache
+        && blocks
+            .iter()
+            .any(|block| block.get("cache_control").is_some())
+    {
+        return Some(Value::Array(blocks));
+    }
+    let text: Vec<&str> = blocks
+        .iter()
+        .filter_map(|block| block.get("text").and_then(Value::as_str))
+        .collect();
+    Some(Value::String(text.join("\n\n")))
+}
+
+pub(super) fn mark_cache_breakpoints(body: &mut Value) {
+    let marker = json!({"type": "ephemeral"});
+    if let Some(tool) = body
+        .get_mut("tools")
+        .and_then(Value::as_array_mut)
+        .and_then(|tools| tools.last_mut())
+    {
+        tool["cache_control"] = marker.clone();
+    }
+    if let Some(block) = body
+        .get_mut("messages")
+        .and_then(Value::as_array_mut)
+        .and_then(|messages| messages.last_mut())
+        .and_then(|message| message.get_mut("content"))
+        .and_then(Value::as_array_mut)
+        .and_then(|blocks| blocks.last_mut())
+    {
+        block["cache_control"] = marker;
+    }
+}
+
+pub(super) fn strip_cache_markers(messages: &mut [Value]) {
+    for message in messages {
+        if let Some(blocks) = message.get_mut("content").and_then(Value::as_array_mut) {
+            for block in blocks.iter_mut().filter_map(Value::as_object_mut) {
+                block.remove("cache_control");
+            }
+        }
+    }
+}
+
+pub(super) fn to_anthropic_tools(tools: Vec<Value>) -> Vec<Value> {
+    tools
+        .into_iter()
+        .filter_map(|tool| {
+            let function = tool.get("function")?;
+            Some(json!({
+                "name": function.get("name").cloned().unwrap_or(Value::Null),
+                "description": function.get("description").cloned().unwrap_or(Value::Null),
+                "input_schema": function
+                    .get("parameters")
+                    .cloned()
+                    .unwrap_or_else(|| json!({"type": "object"})),
+            }))
+        })
+        .collect()
+}
+
+fn push_merged(out: &mut Vec<Value>, role: &str, blocks: Vec<Value>) {
+    if let Some(last) = out.last_mut()
+        && last.get("role").and_then(|v| v.as_str()) == Some(role)
+        && let Some(content) = last.get_mut("content").and_then(Value::as_array_mut)
+    {
+        content.extend(blocks);
+        return;
+    }
+    out.push(json!({"role": role, "content": blocks}));
+}
+
+fn text_content(message: &Value) -> Option<String> {
+    match message.get("content") {
+        Some(Value::String(text)) if !text.is_empty() => Some(text.clone()),
+        Some(Value::Array(parts)) => {
+            let text = parts
+                .iter()
+                .filter_map(|part| part.get("text").and_then(|v| v.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            (!text.is_empty()).then_some(text)
+        }
+        _ => None,
+    }
+}
+
+fn user_blocks(message: &Value) -> Vec<Value> {
+    if let Some(parts) = message.get("content").and_then(Value::as_array) {
+        return parts.iter().filter_map(content_block).collect();
+    }
+    text_content(message)
+        .map(|text| vec![json!({"type": "text", "text": text})])
+        .unwrap_or_default()
+}
+
+fn content_block(part: &Value) -> Option<Value> {
+    match part.get("type").and_then(Value::as_str)? {
+        "text" => {
+            let text = part.get("text").and_then(Value::as_str)?;
+            let mut block = json!({"type": "text", "text": text});
+            if let Some(marker) = part.get("cache_control") {
+                block["cache_control"] = marker.clone();
+            }
+            (!text.is_empty()).then_some(block)
+        }
+        "image_url" => {
+            let url = part.pointer("/image_url/url").and_then(Value::as_str)?;
+            Some(json!({"type": "image", "source": media_source(url)}))
+        }
+        "file" => {
+            let data = part.pointer("/file/file_data").and_then(Value::as_str)?;
+            Some(json!({"type": "document", "source": media_source(data)}))
+        }
+        _ => None,
+    }
+}
+
+fn media_source(url: &str) -> Value {
+    match url
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"))
+    {
+        Some((media_type, data)) => json!({
+            "type": "base64",
+            "media_type": media_type,
+            "data": data,
+        }),
+        None => json!({"type": "url", "url": url}),
+    }
+}
+
+fn assistant_blocks(message: &Value) -> Vec<Value> {
+    let mut blocks: Vec<Value> = message
+        .get("thinking_blocks")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    blocks.extend(user_blocks(message));
+
+    if let Some(calls) = message.get("tool_calls").and_then(|v| v.as_array()) {
+        for call in calls {
+            let function = call.get("function").cloned().unwrap_or_else(|| json!({}));
+            let input = match function.get("arguments") {
+                Some(Value::String(raw)) => {
+                    serde_json::from_str(raw).unwrap_or_else(|_| json!({ "raw": raw }))
+                }
+                Some(other) => other.clone(),
+                None => json!({}),
+            };
+            blocks.push(json!({
+                "type": "tool_use",
+                "id": call.get("id").cloned().unwrap_or(Value::Null),
+                "name": function.get("name").cloned().unwrap_or(Value::Null),
+                "input": input,
+            }));
+        }
+    }
+
+    blocks
+}
+
+fn tool_result_block(message: &Value) -> Value {
+    json!({
+        "type": "tool_result",
+        "tool_use_id": message.get("tool_call_id").cloned().unwrap_or(Value::Null),
+        "content": text_content(message).unwrap_or_default(),
+    })
+}
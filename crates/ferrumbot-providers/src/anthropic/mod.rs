@@ -0,0 +1,26 @@
+use std::collections::BTreeMap;
+
+use ferrumbot_config::ModelRewrite;
+use reqwest::Client;
+
+pub struct AnthropicProvider {
+    pub(super) client: Client,
+    pub(super) api_key: String,
+    pub(super) api_base: String,
+    pub(super) default_model: String,
+    pub(super) extra_headers: BTreeMap<String, String>,
+    pub(super) rewrite: ModelRewrite,
+    pub(super) prompt_cache: bool,
+}
+
+pub(crate) const DEFAULT_API_BASE: &str = "https://api.anthropic.com/v1";
+pub(crate) const API_VERSION: &str = "2023-06-01";
+
+mod build;
+mod call;
+mod convert;
+mod parse;
+mod stream;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::{LlmResponse, ProviderError, ProviderResult, ToolCallRequest};
+
+pub(super) fn parse_messages_response(payload: Value) -> ProviderResult<LlmResponse> {
+    tracing::debug!("parse_messages_response payload: {:?}", payload);
+
+    let Some(blocks) = payload.get("content").and_then(|v| v.as_array()) else {
+        return Err(ProviderError::MalformedResponse(format!(
+            "missing content in response: {payload}"
+        )));
+    };
+
+    let mut text = Vec::new();
+    let mut thinking = Vec::new();
+    let mut thinking_blocks = Vec::new();
+    let mut tool_calls = Vec::new();
+    for block in blocks {
+        match block.get("type").and_then(|v| v.as_str()) {
+            Some("text") => {
+                if let Some(t) = block.get("text").and_then(|v| v.as_str()) {
+                    text.push(t.to_string());
+                }
+            }
+            Some("thinking") => {
+                if let Some(t) = block.get("thinking").and_then(|v| v.as_str()) {
+                    thinking.push(t.to_string());
+                }
+                thinking_blocks.push(block.clone());
+            }
+            Some("redacted_thinking") => thinking_blocks.push(block.clone()),
+            Some("tool_use") => tool_calls.push(ToolCallRequest {
+                id: block
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                name: block
+                    .get("name")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                arguments: block
+                    .get("input")
+                    .cloned()
+                    .unwrap_or_else(|| Value::Object(Default::default())),
+            }),
+            _ => {}
+        }
+    }
+
+    let mut usage = BTreeMap::new();
+    if let Some(obj) = payload.get("usage").and_then(|v| v.as_object()) {
+        let count = |key: &str| obj.get(key).and_then(|v| v.as_i64());
+        let cache_read = count("cache_read_input_tokens");
+        let cache_write = count("cache_creation_input_tokens");
+        let input = count("input_tokens")
+            .map(|input| input + cache_read.unwrap_or(0) + cache_write.unwrap_or(0));
+        let output = count("output_tokens");
+        if let Some(v) = cache_read.filter(|v| *v > 0) {
+            usage.insert("cached_tokens".to_string(), v);
+        }
+        if let Some(v) = cache_write.filter(|v| *v > 0) {
+            usage.insert("cache_write_tokens".to_string(), v);
+        }
+        if let Some(v) = input {
+            usage.insert("prompt_tokens".to_string(), v);
+        }
+        if let Some(v) = output {
+            usage.insert("completion_tokens".to_string(), v);
+        }
+        if input.is_some() || output.is_some() {
+            usage.insert(
+                "total_tokens".to_string(),
+                input.unwrap_or_default() + output.unwrap_or_default(),
+            );
+        }
+    }
+
+    let finish_reason = match payload.get("stop_reason").and_then(|v| v.as_str()) {
+        Some("tool_use") => "tool_calls",
+        Some("max_tokens") => "length",
+        Some("end_turn") | Some("stop_sequence") | None => "stop",
+        Some(other) => other,
+    };
+
+    Ok(LlmResponse {
+        content: (!text.is_empty()).then(|| text.join("")),
+        tool_calls,
+        finish_reason: finish_reason.to_string(),
+        usage,
+        model: None,
+        reasoning: (!thinking.is_empty()).then(|| thinking.join("\n\n")),
+        thinking_blocks,
+    })
+}
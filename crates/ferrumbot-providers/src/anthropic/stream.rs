@@ -0,0 +1,202 @@
+use std::collections::VecDeque;
+
+use futures::StreamExt;
+use reqwest::Response;
+use serde_json::{Value, json};
+
+use crate::stream::SseLines;
+use crate::{ChatStream, ProviderError, ProviderResult, StreamEvent};
+
+use super::parse::parse_messages_response;
+
+pub(super) fn sse_stream(resp: Response) -> ChatStream {
+    let state = SseState {
+        resp,
+        lines: SseLines::default(),
+        assembler: EventAssembler::default(),
+        pending: VecDeque::new(),
+    };
+
+    futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                return Some((event, state));
+            }
+            if state.assembler.done {
+                return None;
+            }
+
+            match state.resp.chunk().await {
+                Ok(Some(bytes)) => {
+                    for data in state.lines.push(&bytes) {
+                        state.pending.extend(state.assembler.handle(&data));
+                    }
+                }
+                Ok(None) => state.pending.extend(state.assembler.finish()),
+                Err(err) => {
+                    state.assembler.done = true;
+                    return Some((Err(ProviderError::from(err)), state));
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
+struct SseState {
+    resp: Response,
+    lines: SseLines,
+    assembler: EventAssembler,
+    pending: VecDeque<ProviderResult<StreamEvent>>,
+}
+
+#[derive(Default)]
+pub(super) struct EventAssembler {
+    message: Value,
+    blocks: Vec<Value>,
+    partial_json: Vec<String>,
+    pub(super) done: bool,
+}
+
+impl EventAssembler {
+    pub(super) fn handle(&mut self, data: &str) -> Vec<ProviderResult<StreamEvent>> {
+        if self.done {
+            return Vec::new();
+        }
+        let Ok(event) = serde_json::from_str::<Value>(data) else {
+            tracing::debug!("skipping malformed stream event: {data}");
+            return Vec::new();
+        };
+        let index = event
+            .get("index")
+            .and_then(Value::as_u64)
+            .unwrap_or_default() as usize;
+
+        match event.get("type").and_then(Value::as_str) {
+            Some("message_start") => {
+                self.message = event.get("message").cloned().unwrap_or_else(|| json!({}));
+                Vec::new()
+            }
+            Some("content_block_start") => {
+                let block = event.get("content_block").cloned().unwrap_or_default();
+                if self.blocks.len() <= index {
+                    self.blocks.resize(index + 1, Value::Null);
+                    self.partial_json.resize(index + 1, String::new());
+                }
+                self.blocks[index] = block;
+                Vec::new()
+            }
+            Some("content_block_delta") => self.delta(index, &event["delta"]),
+            Some("content_block_stop") => {
+                self.close_block(index);
+                Vec::new()
+            }
+            Some("message_delta") => {
+                if let Some(reason) = event["delta"].get("stop_reason") {
+                    self.message["stop_reason"] = reason.clone();
+                }
+                if let Some(usage) = event.get("usage").and_then(Value::as_object) {
+                    for (key, value) in usage {
+                        self.message["usage"][key] = value.clone();
+                    }
+                }
+                Vec::new()
+            }
+            Some("message_stop") => self.complete(),
+            Some("error") => {
+                self.done = true;
+                let message = event["error"]
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("stream failed");
+                vec![Err(ProviderError::MalformedResponse(message.to_string()))]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub(super) fn finish(&mut self) -> Vec<ProviderResult<StreamEvent>> {
+        if self.done {
+            return Vec::new();
+        }
+        self.done = true;
+        vec![Err(ProviderError::MalformedResponse(
+            "stream ended before the message completed".to_string(),
+        ))]
+    }
+
+    fn delta(&mut self, index: usize, delta: &Value) -> Vec<ProviderResult<StreamEvent>> {
+        let Some(block) = self.blocks.get_mut(index) else {
+            return Vec::new();
+        };
+        let text = |key: &str| delta.get(key).and_then(Value::as_str).unwrap_or_default();
+
+        match delta.get("type").and_then(Value::as_str) {
+            Some("text_delta") => {
+                append(block, "text", text("text"));
+                vec![Ok(StreamEvent::TextDelta(text("text").to_string()))]
+            }
+            Some("thinking_delta") => {
+                append(block, "thinking", text("thinking"));
+                vec![Ok(StreamEvent::ReasoningDelta(
+                    text("thinking").to_string(),
+                ))]
+            }
+            Some("signature_delta") => {
+                append(block, "signature", text("signature"));
+                Vec::new()
+            }
+            Some("input_json_delta") => {
+                self.partial_json[index].push_str(text("partial_json"));
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn close_block(&mut self, index: usize) {
+        let Some(block) = self.blocks.get_mut(index) else {
+            return;
+        };
+        if block["type"] != "tool_use" {
+            return;
+        }
+        let raw = std::mem::take(&mut self.partial_json[index]);
+        if raw.trim().is_empty() {
+            return;
+        }
+        match serde_json::from_str(&raw) {
+            Ok(input) => block["input"] = input,
+            Err(err) => tracing::debug!("tool input is not valid JSON ({err}): {raw}"),
+        }
+    }
+
+    fn complete(&mut self) -> Vec<ProviderResult<StreamEvent>> {
+        self.done = true;
+        let mut payload = std::mem::take(&mut self.message);
+        payload["content"] = Value::Array(
+            std::mem::take(&mut self.blocks)
+                .into_iter()
+                .filter(|block| !block.is_null())
+                .collect(),
+        );
+        match parse_messages_response(payload) {
+            Ok(resp) => {
+                let mut events: Vec<_> = resp
+                    .tool_calls
+                    .iter()
+                    .cloned()
+                    .map(|call| Ok(StreamEvent::ToolCall(call)))
+                    .collect();
+                events.push(Ok(StreamEvent::Done(resp)));
+                events
+            }
+            Err(err) => vec![Err(err)],
+        }
+    }
+}
+
+fn append(block: &mut Value, key: &str, text: &str) {
+    let current = block.get(key).and_then(Value::as_str).unwrap_or_default();
+    block[key] = Value::String(format!("{current}{text}"));
+}
@@ -0,0 +1,331 @@
+use futures::StreamExt;
+use serde_json::json;
+
+use ferrumbot_config::ProviderConfig;
+
+use crate::mock::{MockResponse, MockServer};
+use crate::{ChatOptions, LlmProvider, ProviderError, StreamEvent};
+
+use super::AnthropicProvider;
+
+fn provider(base_url: &str) -> AnthropicProvider {
+    let config = ProviderConfig {
+        api_key: "sk-ant-test".to_string(),
+        ..Default::default()
+    };
+    AnthropicProvider::from_provider(
+        "anthropic/claude-opus-4-5".to_string(),
+        &config,
+        Some(format!("{base_url}/v1")),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn chat_hoists_system_and_parses_tool_use() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        json!({
+            "content": [
+                {"type": "text", "text": "Let me check."},
+                {"type": "tool_use", "id": "toolu_2", "name": "list_dir", "input": {"path": "."}}
+            ],
+            "stop_reason": "tool_use",
+            "usage": {"input_tokens": 12, "output_tokens": 5}
+        }),
+    )])
+    .await;
+
+    let messages = vec![
+        json!({"role": "system", "content": "You are ferrum-bot."}),
+        json!({"role": "user", "content": "read the file"}),
+        json!({
+            "role": "assistant",
+            "content": "",
+            "tool_calls": [{
+                "id": "toolu_1",
+                "type": "function",
+                "function": {"name": "read_file", "arguments": "{\"path\":\"a.txt\"}"}
+            }]
+        }),
+        json!({"role": "tool", "tool_call_id": "toolu_1", "name": "read_file", "content": "hello"}),
+    ];
+    let tools = vec![json!({
+        "type": "function",
+        "function": {"name": "list_dir", "description": "List", "parameters": {"type": "object"}}
+    })];
+
+    let resp = provider(&server.base_url)
+        .chat(
+            messages,
+            Some(tools),
+            None,
+            &ChatOptions {
+                max_tokens: Some(256),
+                stop: vec!["END".to_string()],
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("chat should succeed");
+
+    assert_eq!(resp.content.as_deref(), Some("Let me check."));
+    assert_eq!(resp.finish_reason, "tool_calls");
+    assert_eq!(resp.tool_calls.len(), 1);
+    assert_eq!(resp.tool_calls[0].name, "list_dir");
+    assert_eq!(resp.tool_calls[0].arguments, json!({"path": "."}));
+    assert_eq!(resp.usage.get("total_tokens"), Some(&17));
+
+    let requests = server.requests();
+    let request = &requests[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/v1/messages");
+    assert_eq!(request.header("x-api-key"), Some("sk-ant-test"));
+    assert_eq!(request.header("anthropic-version"), Some("2023-06-01"));
+
+    let body = request.json();
+    assert_eq!(body["model"], "claude-opus-4-5");
+    assert_eq!(body["system"], "You are ferrum-bot.");
+    assert_eq!(body["max_tokens"], 256);
+    assert_eq!(body["stop_sequences"], json!(["END"]));
+    assert_eq!(body["tools"][0]["input_schema"], json!({"type": "object"}));
+    assert_eq!(body["messages"][1]["content"][0]["type"], "tool_use");
+    assert_eq!(
+        body["messages"][1]["content"][0]["input"],
+        json!({"path": "a.txt"})
+    );
+    assert_eq!(body["messages"][2]["role"], "user");
+    assert_eq!(body["messages"][2]["content"][0]["tool_use_id"], "toolu_1");
+}
+
+#[tokio::test]
+async fn thinking_blocks_replay_ahead_of_tool_use() {
+    let server = MockServer::start(vec![
+        MockResponse::json(
+            200,
+            json!({
+                "content": [
+                    {"type": "thinking", "thinking": "Need the file.", "signature": "sig-1"},
+                    {"type": "redacted_thinking", "data": "opaque"},
+                    {"type": "tool_use", "id": "toolu_1", "name": "read_file", "input": {"path": "a.txt"}}
+                ],
+                "stop_reason": "tool_use",
+                "usage": {"input_tokens": 10, "output_tokens": 4}
+            }),
+        ),
+        MockResponse::json(
+            200,
+            json!({
+                "content": [{"type": "text", "text": "It says hello."}],
+                "stop_reason": "end_turn",
+                "usage": {"input_tokens": 20, "output_tokens": 4}
+            }),
+        ),
+    ])
+    .await;
+    let provider = provider(&server.base_url);
+    let options = ChatOptions {
+        reasoning_effort: Some("low".to_string()),
+        ..Default::default()
+    };
+
+    let mut messages = vec![json!({"role": "user", "content": "read a.txt"})];
+    let first = provider
+        .chat(messages.clone(), None, None, &options)
+        .await
+        .expect("first turn should succeed");
+    assert_eq!(first.reasoning.as_deref(), Some("Need the file."));
+    assert_eq!(first.thinking_blocks.len(), 2);
+
+    messages.push(json!({
+        "role": "assistant",
+        "content": "",
+        "thinking_blocks": first.thinking_blocks,
+        "tool_calls": [{
+            "id": "toolu_1",
+            "type": "function",
+            "function": {"name": "read_file", "arguments": "{\"path\":\"a.txt\"}"}
+        }]
+    }));
+    messages.push(
+        json!({"role": "tool", "tool_call_id": "toolu_1", "name": "read_file", "content": "hello"}),
+    );
+    let second = provider
+        .chat(messages, None, None, &options)
+        .await
+        .expect("second turn should succeed");
+    assert_eq!(second.content.as_deref(), Some("It says hello."));
+
+    let body = server.requests()[1].json();
+    let assistant = &body["messages"][1]["content"];
+    assert_eq!(assistant[0]["type"], "thinking");
+    assert_eq!(assistant[0]["signature"], "sig-1");
+    assert_eq!(assistant[1]["type"], "redacted_thinking");
+    assert_eq!(assistant[2]["type"], "tool_use");
+    assert!(body["messages"][1].get("thinking_blocks").is_none());
+}
+
+#[tokio::test]
+async fn chat_reports_http_errors() {
+    let server = MockServer::start(vec![MockResponse::json(
+        401,
+        json!({"type": "error", "error": {"type": "authentication_error", "message": "bad key"}}),
+    )])
+    .await;
+
+    let err = provider(&server.base_url)
+        .chat(
+            vec![json!({"role": "user", "content": "hi"})],
+            None,
+            None,
+            &ChatOptions::default(),
+        )
+        .await
+        .expect_err("chat should fail");
+
+    assert!(matches!(err, ProviderError::Auth(ref message) if message == "bad key"));
+}
+
+#[test]
+fn converts_image_and_document_parts() {
+    let (_, messages) = super::convert::to_anthropic_messages(vec![json!({
+        "role": "user",
+        "content": [
+            {"type": "text", "text": "what is this?"},
+            {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0K"}},
+            {"type": "image_url", "image_url": {"url": "https://example.com/cat.jpg"}},
+            {"type": "file", "file": {"filename": "a.pdf", "file_data": "data:application/pdf;base64,JVBERi0"}}
+        ]
+    })]);
+
+    let blocks = &messages[0]["content"];
+    assert_eq!(blocks[0], json!({"type": "text", "text": "what is this?"}));
+    assert_eq!(
+        blocks[1]["source"],
+        json!({"type": "base64", "media_type": "image/png", "data": "iVBORw0K"})
+    );
+    assert_eq!(
+        blocks[2]["source"],
+        json!({"type": "url", "url": "https://example.com/cat.jpg"})
+    );
+    assert_eq!(blocks[3]["type"], "document");
+    assert_eq!(blocks[3]["source"]["media_type"], "application/pdf");
+}
+
+#[tokio::test]
+async fn chat_marks_cache_breakpoints_and_reads_cache_usage() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        json!({
+            "content": [{"type": "text", "text": "Hi"}],
+            "stop_reason": "end_turn",
+            "usage": {
+                "input_tokens": 20,
+                "cache_read_input_tokens": 1800,
+                "cache_creation_input_tokens": 100,
+                "output_tokens": 3
+            }
+        }),
+    )])
+    .await;
+
+    let messages = vec![
+        json!({"role": "system", "content": [
+            {"type": "text", "text": "Static rules.", "cache_control": {"type": "ephemeral"}},
+            {"type": "text", "text": "## Current Session\nCurrent Time: now"}
+        ]}),
+        json!({"role": "user", "content": "hello"}),
+    ];
+    let resp = provider(&server.base_url)
+        .chat(messages, None, None, &ChatOptions::default())
+        .await
+        .expect("chat should succeed");
+
+    assert_eq!(resp.usage.get("prompt_tokens"), Some(&1920));
+    assert_eq!(resp.usage.get("cached_tokens"), Some(&1800));
+    assert_eq!(resp.usage.get("cache_write_tokens"), Some(&100));
+
+    let body = server.requests()[0].json();
+    assert_eq!(body["system"][0]["cache_control"]["type"], "ephemeral");
+    assert!(body["system"][1].get("cache_control").is_none());
+    assert_eq!(
+        body["messages"][0]["content"][0]["cache_control"]["type"],
+        "ephemeral"
+    );
+}
+
+#[tokio::test]
+async fn chat_stream_assembles_thinking_text_and_tool_use() {
+    let body = [
+        json!({"type": "message_start", "message": {
+            "id": "msg_1", "role": "assistant", "content": [],
+            "usage": {"input_tokens": 12, "output_tokens": 1}
+        }}),
+        json!({"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "Need "}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "the file."}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "sig-1"}}),
+        json!({"type": "content_block_stop", "index": 0}),
+        json!({"type": "content_block_start", "index": 1, "content_block": {"type": "text", "text": ""}}),
+        json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "Reading"}}),
+        json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": " it."}}),
+        json!({"type": "content_block_stop", "index": 1}),
+        json!({"type": "ping"}),
+        json!({"type": "content_block_start", "index": 2, "content_block": {"type": "tool_use", "id": "toolu_1", "name": "read_file", "input": {}}}),
+        json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "{\"path\":"}}),
+        json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": " \"a.txt\"}"}}),
+        json!({"type": "content_block_stop", "index": 2}),
+        json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 9}}),
+        json!({"type": "message_stop"}),
+    ]
+    .iter()
+    .map(|event| format!("event: {}\ndata: {event}\n\n", event["type"].as_str().unwrap()))
+    .collect::<String>();
+    let server = MockServer::start(vec![MockResponse {
+        status: 200,
+        content_type: "text/event-stream",
+        headers: Vec::new(),
+        body,
+    }])
+    .await;
+
+    let mut stream = provider(&server.base_url)
+        .chat_stream(
+            vec![json!({"role": "user", "content": "read a.txt"})],
+            None,
+            None,
+            &ChatOptions {
+                reasoning_effort: Some("low".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("stream should open");
+
+    let mut deltas = Vec::new();
+    let mut reasoning = String::new();
+    let mut calls = Vec::new();
+    let mut done = None;
+    while let Some(event) = stream.next().await {
+        match event.expect("stream event") {
+            StreamEvent::TextDelta(text) => deltas.push(text),
+            StreamEvent::ReasoningDelta(text) => reasoning.push_str(&text),
+            StreamEvent::ToolCall(call) => calls.push(call),
+            StreamEvent::Done(resp) => done = Some(resp),
+        }
+    }
+
+    assert_eq!(deltas, ["Reading", " it."]);
+    assert_eq!(reasoning, "Need the file.");
+    assert_eq!(calls[0].arguments, json!({"path": "a.txt"}));
+    let done = done.expect("final response");
+    assert_eq!(done.content.as_deref(), Some("Reading it."));
+    assert_eq!(done.finish_reason, "tool_calls");
+    assert_eq!(done.usage.get("total_tokens"), Some(&21));
+    assert_eq!(done.model.as_deref(), Some("anthropic/claude-opus-4-5"));
+    assert_eq!(
+        done.thinking_blocks,
+        [json!({"type": "thinking", "thinking": "Need the file.", "signature": "sig-1"})]
+    );
+    assert_eq!(server.requests()[0].json()["stream"], true);
+}
@@ -0,0 +1,49 @@
+use ferrumbot_config::ModelCapabilities;
+use serde_json::{Value, json};
+
+use crate::ChatOptions;
+
+pub fn fit_options(capabilities: &ModelCapabilities, mut options: ChatOptions) -> ChatOptions {
+    if let Some(cap) = capabilities.max_output_tokens {
+        options.max_tokens = Some(options.max_tokens.map_or(cap, |max| max.min(cap)));
+    }
+    if !capabilities.supports_reasoning() {
+        options.reasoning_effort = None;
+    }
+    if !capabilities.supports_parallel_tools() {
+        options.parallel_tool_calls = Some(false);
+    }
+    options
+}
+
+pub(crate) fn fit_request(
+    capabilities: &ModelCapabilities,
+    messages: Vec<Value>,
+    tools: Option<Vec<Value>>,
+    options: &ChatOptions,
+) -> (Vec<Value>, Option<Vec<Value>>, ChatOptions) {
+    let messages = if capabilities.supports_vision() {
+        messages
+    } else {
+        without_images(messages)
+    };
+    let tools = tools.filter(|_| capabilities.supports_tools());
+    (messages, tools, fit_options(capabilities, options.clone()))
+}
+
+fn without_images(messages: Vec<Value>) -> Vec<Value> {
+    messages
+        .into_iter()
+        .map(|mut message| {
+            if let Some(parts) = message.get_mut("content").and_then(Value::as_array_mut) {
+                for part in parts.iter_mut().filter(|part| part["type"] == "image_url") {
+                    *part = json!({
+                        "type": "text",
+                        "text": "[image omitted: the model does not accept images]",
+                    });
+                }
+            }
+            message
+        })
+        .collect()
+}
@@ -0,0 +1,51 @@
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+pub fn request_key(messages: &[Value], tools: Option<&[Value]>) -> String {
+    let messages: Vec<Value> = messages
+        .iter()
+        .filter(|message| message["role"] != "system")
+        .map(normalize_message)
+        .collect();
+    let mut tools: Vec<&str> = tools
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|tool| tool["function"]["name"].as_str())
+        .collect();
+    tools.sort_unstable();
+
+    let normalized = json!({"messages": messages, "tools": tools});
+    let digest = Sha256::digest(normalized.to_string().as_bytes());
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn normalize_message(message: &Value) -> Value {
+    let content = match &message["content"] {
+        Value::String(text) => Value::String(text.trim().to_string()),
+        other => other.clone(),
+    };
+    let tool_calls: Vec<Value> = message["tool_calls"]
+        .as_array()
+        .map(|calls| {
+            calls
+                .iter()
+                .map(|call| {
+                    let arguments = match &call["function"]["arguments"] {
+                        Value::String(raw) => {
+                            serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.clone()))
+                        }
+                        other => other.clone(),
+                    };
+                    json!({"name": call["function"]["name"], "arguments": arguments})
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    json!({
+        "role": message["role"],
+        "name": message["name"],
+        "content": content,
+        "tool_calls": tool_calls,
+    })
+}
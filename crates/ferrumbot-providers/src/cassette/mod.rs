@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::LlmResponse;
+
+mod key;
+mod record;
+mod replay;
+
+pub use key::request_key;
+pub use record::RecordingProvider;
+pub use replay::ReplayProvider;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CassetteEntry {
+    pub key: String,
+    pub model: Option<String>,
+    pub messages: Vec<Value>,
+    pub response: LlmResponse,
+}
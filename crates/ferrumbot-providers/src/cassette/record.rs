@@ -0,0 +1,132 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde_json::Value;
+
+use crate::{
+    ChatOptions, ChatStream, Embeddings, LlmProvider, ProviderError, ProviderResult, StreamEvent,
+};
+
+use super::{CassetteEntry, request_key};
+
+pub struct RecordingProvider {
+    inner: Arc<dyn LlmProvider>,
+    writer: Arc<CassetteWriter>,
+}
+
+impl RecordingProvider {
+    pub fn new(inner: Arc<dyn LlmProvider>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            writer: Arc::new(CassetteWriter {
+                path: path.into(),
+                lock: Mutex::new(()),
+            }),
+        }
+    }
+}
+
+struct CassetteWriter {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl CassetteWriter {
+    fn append(&self, entry: &CassetteEntry) -> ProviderResult<()> {
+        self.write(entry).map_err(|err| {
+            ProviderError::Cassette(format!(
+                "failed to write cassette {}: {err}",
+                self.path.display()
+            ))
+        })
+    }
+
+    fn write(&self, entry: &CassetteEntry) -> std::io::Result<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for RecordingProvider {
+    async fn chat_stream(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<ChatStream> {
+        let key = request_key(&messages, tools.as_deref());
+        let stream = self
+            .inner
+            .chat_stream(messages.clone(), tools, model, options)
+            .await?;
+
+        let writer = self.writer.clone();
+        let mut pending = Some((key, model.map(ToString::to_string), messages));
+        Ok(stream
+            .map(move |event| {
+                if let Ok(StreamEvent::Done(response)) = &event
+                    && let Some((key, model, messages)) = pending.take()
+                {
+                    writer.append(&CassetteEntry {
+                        key,
+                        model,
+                        messages,
+                        response: response.clone(),
+                    })?;
+                }
+                event
+            })
+            .boxed())
+    }
+
+    async fn embed(&self, texts: Vec<String>, model: Option<&str>) -> ProviderResult<Embeddings> {
+        self.inner.embed(texts, model).await
+    }
+
+    async fn list_models(&self) -> ProviderResult<Vec<String>> {
+        self.inner.list_models().await
+    }
+
+    fn get_default_model(&self) -> &str {
+        self.inner.get_default_model()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LlmResponse, ScriptedProvider};
+
+    #[tokio::test]
+    async fn cassette_write_failures_are_not_retried() {
+        let blocker =
+            std::env::temp_dir().join(format!("ferrumbot-cassette-{}", std::process::id()));
+        std::fs::write(&blocker, "not a directory").unwrap();
+        let provider = RecordingProvider::new(
+            Arc::new(ScriptedProvider::new(vec![LlmResponse::text("hi")])),
+            blocker.join("run.jsonl"),
+        );
+
+        let err = provider
+            .chat(Vec::new(), None, None, &ChatOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProviderError::Cassette(_)), "{err:?}");
+        assert!(!err.is_retryable());
+
+        std::fs::remove_file(blocker).unwrap();
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::{
+    ChatOptions, ChatStream, LlmProvider, LlmResponse, ProviderError, ProviderResult,
+    response_stream,
+};
+
+use super::{CassetteEntry, request_key};
+
+pub struct ReplayProvider {
+    default_model: String,
+    entries: Mutex<HashMap<String, VecDeque<LlmResponse>>>,
+}
+
+impl ReplayProvider {
+    pub fn load(path: &Path, default_model: impl Into<String>) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read cassette: {}", path.display()))?;
+        let mut entries: HashMap<String, VecDeque<LlmResponse>> = HashMap::new();
+        for line in raw.lines().filter(|line| !line.trim().is_empty()) {
+            let entry: CassetteEntry = serde_json::from_str(line)
+                .with_context(|| format!("invalid cassette entry in {}", path.display()))?;
+            entries
+                .entry(entry.key)
+                .or_default()
+                .push_back(entry.response);
+        }
+        Ok(Self {
+            default_model: default_model.into(),
+            entries: Mutex::new(entries),
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ReplayProvider {
+    async fn chat_stream(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        _model: Option<&str>,
+        _options: &ChatOptions,
+    ) -> ProviderResult<ChatStream> {
+        let key = request_key(&messages, tools.as_deref());
+        let mut entries = self.entries.lock().unwrap_or_else(|err| err.into_inner());
+        let queue = entries.get_mut(&key).filter(|queue| !queue.is_empty());
+        match queue {
+            Some(queue) if queue.len() > 1 => {
+                Ok(response_stream(queue.pop_front().expect("queue not empty")))
+            }
+            Some(queue) => Ok(response_stream(queue[0].clone())),
+            None => Err(ProviderError::InvalidRequest {
+                status: 404,
+                message: format!("no recorded response for request {key}"),
+            }),
+        }
+    }
+
+    fn get_default_model(&self) -> &str {
+        &self.default_model
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+
+use ferrumbot_config::data_dir;
+
+use crate::{Embeddings, LlmProvider, ProviderResult};
+
+pub struct EmbeddingCache {
+    dir: PathBuf,
+}
+
+impl EmbeddingCache {
+    pub fn new() -> Result<Self> {
+        Self::open(data_dir().join("embeddings"))
+    }
+
+    pub fn open(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    pub fn get(&self, model: &str, text: &str) -> Option<Vec<f32>> {
+        let raw = fs::read(self.path(model, text)).ok()?;
+        serde_json::from_slice(&raw).ok()
+    }
+
+    pub fn put(&self, model: &str, text: &str, vector: &[f32]) -> Result<()> {
+        let path = self.path(model, text);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_vec(vector)?)?;
+        Ok(())
+    }
+
+    pub async fn embed(
+        &self,
+        provider: &dyn LlmProvider,
+        texts: &[String],
+        model: &str,
+    ) -> ProviderResult<Embeddings> {
+        let mut vectors: Vec<Option<Vec<f32>>> =
+            texts.iter().map(|text| self.get(model, text)).collect();
+        let missing: Vec<usize> = (0..texts.len()).filter(|&i| vectors[i].is_none()).collect();
+
+        let mut embeddings = Embeddings::new(model);
+        if !missing.is_empty() {
+            let batch = missing.iter().map(|&i| texts[i].clone()).collect();
+            let fresh = provider.embed(batch, Some(model)).await?;
+            for (&i, vector) in missing.iter().zip(fresh.vectors) {
+                if let Err(err) = self.put(model, &texts[i], &vector) {
+                    tracing::warn!("failed to cache embedding: {err:#}");
+                }
+                vectors[i] = Some(vector);
+            }
+            embeddings.usage = fresh.usage;
+        }
+
+        embeddings.vectors = vectors.into_iter().map(Option::unwrap_or_default).collect();
+        Ok(embeddings)
+    }
+
+    fn path(&self, model: &str, text: &str) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(model.as_bytes());
+        hasher.update([0]);
+        hasher.update(text.as_bytes());
+        let key: String = hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        self.dir.join(&key[..2]).join(format!("{key}.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::EmbeddingCache;
+    use crate::LocalEmbeddingProvider;
+    use crate::mock::{MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn cache_only_embeds_unseen_texts() {
+        let dir = std::env::temp_dir().join(format!("ferrumbot-embed-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let cache = EmbeddingCache::open(dir.clone()).unwrap();
+        let server = MockServer::start(vec![
+            MockResponse::json(
+                200,
+                json!({"embeddings": [[0.1, 0.2], [0.3, 0.4]], "prompt_eval_count": 6}),
+            ),
+            MockResponse::json(200, json!({"embeddings": [[0.5, 0.6]]})),
+        ])
+        .await;
+        let provider = LocalEmbeddingProvider::new(&server.base_url, "local/nomic-embed-text");
+        let model = "local/nomic-embed-text";
+
+        let texts = vec!["alpha".to_string(), "beta".to_string()];
+        let first = cache.embed(&provider, &texts, model).await.unwrap();
+        assert_eq!(first.dimensions(), 2);
+        assert_eq!(first.usage.get("prompt_tokens"), Some(&6));
+
+        let texts = vec!["beta".to_string(), "gamma".to_string()];
+        let second = cache.embed(&provider, &texts, model).await.unwrap();
+        assert_eq!(second.vectors, vec![vec![0.3, 0.4], vec![0.5, 0.6]]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].path, "/api/embed");
+        assert_eq!(requests[0].json()["model"], "nomic-embed-text");
+        assert_eq!(requests[1].json()["input"], json!(["gamma"]));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
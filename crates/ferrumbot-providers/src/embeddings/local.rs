@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{Value, json};
+
+use crate::{ChatOptions, ChatStream, Embeddings, LlmProvider, ProviderError, ProviderResult};
+
+use super::{EMBED_BATCH_SIZE, parse_embeddings};
+
+pub struct LocalEmbeddingProvider {
+    client: Client,
+    api_base: String,
+    default_model: String,
+}
+
+impl LocalEmbeddingProvider {
+    pub fn new(api_base: impl Into<String>, default_model: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            api_base: api_base.into().trim_end_matches('/').to_string(),
+            default_model: default_model.into(),
+        }
+    }
+
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    fn url(&self) -> String {
+        if self.api_base.ends_with("/v1") {
+            format!("{}/embeddings", self.api_base)
+        } else {
+            format!("{}/api/embed", self.api_base)
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for LocalEmbeddingProvider {
+    async fn chat_stream(
+        &self,
+        _messages: Vec<Value>,
+        _tools: Option<Vec<Value>>,
+        _model: Option<&str>,
+        _options: &ChatOptions,
+    ) -> ProviderResult<ChatStream> {
+        Err(ProviderError::Unsupported("chat".to_string()))
+    }
+
+    async fn embed(&self, texts: Vec<String>, model: Option<&str>) -> ProviderResult<Embeddings> {
+        let model = model.unwrap_or(&self.default_model);
+        let wire_model = model.strip_prefix("local/").unwrap_or(model);
+        let mut embeddings = Embeddings::new(model);
+        for batch in texts.chunks(EMBED_BATCH_SIZE) {
+            let body = json!({"model": wire_model, "input": batch});
+            let resp = self.client.post(self.url()).json(&body).send().await?;
+            if !resp.status().is_success() {
+                return Err(ProviderError::from_response(resp).await);
+            }
+            let payload: Value = resp.json().await?;
+            embeddings.extend(parse_embeddings(payload, model, batch.len())?);
+        }
+        Ok(embeddings)
+    }
+
+    fn get_default_model(&self) -> &str {
+        &self.default_model
+    }
+}
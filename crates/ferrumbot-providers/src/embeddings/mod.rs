@@ -0,0 +1,67 @@
+use serde_json::Value;
+
+use crate::{Embeddings, ProviderError, ProviderResult};
+
+mod cache;
+mod local;
+
+pub use cache::EmbeddingCache;
+pub use local::LocalEmbeddingProvider;
+
+pub(crate) const EMBED_BATCH_SIZE: usize = 64;
+
+pub(crate) fn parse_embeddings(
+    payload: Value,
+    model: &str,
+    expected: usize,
+) -> ProviderResult<Embeddings> {
+    let mut items: Vec<(u64, Vec<f32>)> = Vec::new();
+    if let Some(data) = payload.get("data").and_then(|v| v.as_array()) {
+        for (pos, item) in data.iter().enumerate() {
+            let index = item
+                .get("index")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(pos as u64);
+            items.push((index, vector(item.get("embedding"))));
+        }
+    } else if let Some(data) = payload.get("embeddings").and_then(|v| v.as_array()) {
+        for (pos, item) in data.iter().enumerate() {
+            items.push((pos as u64, vector(Some(item))));
+        }
+    }
+    items.sort_by_key(|(index, _)| *index);
+
+    if items.len() != expected || items.iter().any(|(_, v)| v.is_empty()) {
+        return Err(ProviderError::MalformedResponse(format!(
+            "expected {expected} embeddings, got {}",
+            items.len()
+        )));
+    }
+
+    let mut embeddings = Embeddings::new(model);
+    embeddings.vectors = items.into_iter().map(|(_, v)| v).collect();
+    if let Some(obj) = payload.get("usage").and_then(|v| v.as_object()) {
+        for key in ["prompt_tokens", "total_tokens"] {
+            if let Some(v) = obj.get(key).and_then(|x| x.as_i64()) {
+                embeddings.usage.insert(key.to_string(), v);
+            }
+        }
+    } else if let Some(count) = payload.get("prompt_eval_count").and_then(|v| v.as_i64()) {
+        embeddings.usage.insert("prompt_tokens".to_string(), count);
+        embeddings.usage.insert("total_tokens".to_string(), count);
+    }
+    Ok(embeddings)
+}
+
+fn vector(value: Option<&Value>) -> Vec<f32> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|x| x.as_f64())
+                .map(|x| x as f32)
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use reqwest::Response;
+use serde_json::Value;
+use thiserror::Error;
+
+pub type ProviderResult<T> = Result<T, ProviderError>;
+
+#[derive(Debug, Clone, Error)]
+pub enum ProviderError {
+    #[error("authentication failed: {0}")]
+    Auth(String),
+    #[error("rate limited: {message}")]
+    RateLimited {
+        retry_after: Option<Duration>,
+        message: String,
+    },
+    #[error("context length exceeded: {0}")]
+    ContextLengthExceeded(String),
+    #[error("provider server error ({status}): {message}")]
+    Server { status: u16, message: String },
+    #[error("invalid request ({status}): {message}")]
+    InvalidRequest { status: u16, message: String },
+    #[error("network error: {0}")]
+    Network(String),
+    #[error("malformed provider response: {0}")]
+    MalformedResponse(String),
+    #[error("not supported by this provider: {0}")]
+    Unsupported(String),
+    #[error("invalid response schema {0}")]
+    InvalidSchema(String),
+    #[error("cassette error: {0}")]
+    Cassette(String),
+}
+
+impl ProviderError {
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::RateLimited { .. } | Self::Server { .. } | Self::Network(_)
+        )
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    pub fn from_status(status: u16, retry_after: Option<Duration>, body: &str) -> Self {
+        let message = error_message(body);
+        let lower = body.to_lowercase();
+
+        match status {
+            401 | 403 => Self::Auth(message),
+            429 => Self::RateLimited {
+                retry_after,
+                message,
+            },
+            400 | 413 | 422 if is_context_overflow(&lower) => Self::ContextLengthExceeded(message),
+            500..=599 => Self::Server { status, message },
+            _ => Self::InvalidRequest { status, message },
+        }
+    }
+
+    pub(crate) async fn from_response(resp: Response) -> Self {
+        let status = resp.status().as_u16();
+        let retry_after = resp
+            .headers()
+            .get("retry-after")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|secs| *secs >= 0.0)
+            .map(Duration::from_secs_f64);
+        let body = resp.text().await.unwrap_or_default();
+        tracing::debug!("provider error response ({status}): {body}");
+        Self::from_status(status, retry_after, &body)
+    }
+}
+
+impl From<reqwest::Error> for ProviderError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_decode() {
+            Self::MalformedResponse(err.to_string())
+        } else {
+            Self::Network(err.to_string())
+        }
+    }
+}
+
+fn error_message(body: &str) -> String {
+    let parsed: Option<Value> = serde_json::from_str(body).ok();
+    let message = parsed.as_ref().and_then(|v| {
+        v.get("error")
+            .and_then(|e| e.get("message").or(Some(e)))
+            .or_else(|| v.get("message"))
+            .and_then(|m| m.as_str())
+            .map(ToString::to_string)
+    });
+
+    let mut message = message.unwrap_or_else(|| body.trim().to_string());
+    if message.len() > 500 {
+        let mut cut = 500;
+        while !message.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        message.truncate(cut);
+        message.push_str("...");
+    }
+    if message.is_empty() {
+        "empty response body".to_string()
+    } else {
+        message
+    }
+}
+
+fn is_context_overflow(lower: &str) -> bool {
+    [
+        "context_length_exceeded",
+        "maximum context length",
+        "context window",
+        "prompt is too long",
+        "too many tokens",
+    ]
+    .iter()
+    .any(|needle| lower.contains(needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::ProviderError;
+
+    #[test]
+    fn classifies_status_codes() {
+        let err = ProviderError::from_status(
+            429,
+            Some(Duration::from_secs(3)),
+            r#"{"error":{"message":"slow down"}}"#,
+        );
+        assert!(err.is_retryable());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(3)));
+        assert_eq!(err.to_string(), "rate limited: slow down");
+
+        let err = ProviderError::from_status(
+            400,
+            None,
+            r#"{"error":{"code":"context_length_exceeded","message":"too long"}}"#,
+        );
+        assert!(matches!(err, ProviderError::ContextLengthExceeded(_)));
+        assert!(!err.is_retryable());
+
+        assert!(matches!(
+            ProviderError::from_status(401, None, "nope"),
+            ProviderError::Auth(_)
+        ));
+        assert!(ProviderError::from_status(503, None, "").is_retryable());
+        assert!(!ProviderError::from_status(404, None, "").is_retryable());
+    }
+}
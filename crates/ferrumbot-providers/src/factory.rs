@@ -0,0 +1,237 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use ferrumbot_config::{
+    ApiStyle, Config, ModelCapabilities, ProviderConfig, ResolvedProvider, ToolCallMode,
+};
+
+use crate::{
+    AnthropicProvider, FallbackEntry, FallbackProvider, LlmProvider, LocalEmbeddingProvider,
+    OllamaProvider, OpenAiCompatibleProvider, OpenAiResponsesProvider, RateLimitedProvider,
+    RateLimiter, RetryPolicy, RetryingProvider, TextToolProvider,
+};
+
+const LOCAL_EMBEDDINGS_BASE: &str = "http://localhost:11434";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelRoute {
+    pub provider: Option<String>,
+    pub custom: bool,
+    pub api_base: String,
+    pub wire_model: String,
+}
+
+pub fn resolve_model(config: &Config, model: &str) -> ModelRoute {
+    let Some(resolved) = config.resolve_provider(Some(model)) else {
+        return ModelRoute {
+            provider: None,
+            custom: false,
+            api_base: crate::openai::DEFAULT_API_BASE.to_string(),
+            wire_model: model.to_string(),
+        };
+    };
+    route_for(resolved, model)
+}
+
+pub fn route_for(resolved: ResolvedProvider<'_>, model: &str) -> ModelRoute {
+    let default_base = match resolved.api_style() {
+        ApiStyle::Anthropic => crate::anthropic::DEFAULT_API_BASE,
+        ApiStyle::Ollama => crate::ollama::DEFAULT_API_BASE,
+        _ => crate::openai::DEFAULT_API_BASE,
+    };
+    ModelRoute {
+        provider: Some(resolved.name.to_string()),
+        custom: resolved.custom,
+        api_base: resolved
+            .api_base()
+            .unwrap_or_else(|| default_base.to_string()),
+        wire_model: resolved.model_rewrite().apply(model),
+    }
+}
+
+pub fn provider_from_config(config: &Config) -> Result<Arc<dyn LlmProvider>> {
+    let defaults = &config.agents.defaults;
+    let primary = provider_for_model(config, &defaults.model)?;
+    if defaults.fallback_models.is_empty() {
+        return Ok(primary);
+    }
+
+    let mut entries = vec![
+        FallbackEntry::new(defaults.model.clone(), primary)
+            .with_capabilities(capabilities_for(config, &defaults.model)),
+    ];
+    for model in &defaults.fallback_models {
+        entries.push(
+            FallbackEntry::new(model.clone(), provider_for_model(config, model)?)
+                .with_capabilities(capabilities_for(config, model)),
+        );
+    }
+    Ok(Arc::new(FallbackProvider::new(entries)))
+}
+
+pub fn capabilities_for(config: &Config, model: &str) -> ModelCapabilities {
+    let mut capabilities = config.capabilities.lookup(model);
+    if config
+        .resolve_provider(Some(model))
+        .is_some_and(|resolved| resolved.config.tool_calls == ToolCallMode::Prompt)
+    {
+        capabilities.tools = Some(true);
+    }
+    capabilities
+}
+
+pub fn embedding_provider_from_config(config: &Config) -> Result<Arc<dyn LlmProvider>> {
+    let embeddings = &config.embeddings;
+    if !embeddings.model.starts_with("local/") {
+        let resolved = config.resolve_provider(Some(&embeddings.model));
+        let Some(api_base) = &embeddings.api_base else {
+            return build_provider(config, resolved, &embeddings.model);
+        };
+        let provider = ProviderConfig {
+            api_base: Some(api_base.clone()),
+            ..resolved
+                .map(|resolved| resolved.config.clone())
+                .unwrap_or_default()
+        };
+        let resolved = ResolvedProvider {
+            name: resolved.map_or("custom", |resolved| resolved.name),
+            config: &provider,
+            custom: resolved.is_none_or(|resolved| resolved.custom),
+        };
+        return build_provider(config, Some(resolved), &embeddings.model);
+    }
+
+    let api_base = embeddings
+        .api_base
+        .clone()
+        .unwrap_or_else(|| LOCAL_EMBEDDINGS_BASE.to_string());
+    let inner = Arc::new(
+        LocalEmbeddingProvider::new(api_base, embeddings.model.clone())
+            .with_client(config.http.client()?),
+    );
+    Ok(Arc::new(RetryingProvider::new(
+        inner,
+        RetryPolicy::default(),
+    )))
+}
+
+pub fn provider_for_model(config: &Config, model: &str) -> Result<Arc<dyn LlmProvider>> {
+    build_provider(config, config.resolve_provider(Some(model)), model)
+}
+
+pub fn provider_named(config: &Config, name: &str, model: &str) -> Result<Arc<dyn LlmProvider>> {
+    let resolved = config
+        .provider_named(name)
+        .ok_or_else(|| anyhow::anyhow!("unknown provider '{name}'"))?;
+    build_provider(config, Some(resolved), model)
+}
+
+fn build_provider(
+    config: &Config,
+    resolved: Option<ResolvedProvider<'_>>,
+    model: &str,
+) -> Result<Arc<dyn LlmProvider>> {
+    let provider = resolved.map(|resolved| resolved.config);
+    let api_base = resolved.and_then(|resolved| resolved.api_base());
+    let client = match provider {
+        Some(provider) => config.http_for(provider).client()?,
+        None => config.http.client()?,
+    };
+    let inner: Arc<dyn LlmProvider> = match resolved {
+        Some(resolved) => {
+            let model = model.to_string();
+            let rewrite = resolved.model_rewrite();
+            match resolved.api_style() {
+                ApiStyle::Anthropic => Arc::new(
+                    AnthropicProvider::from_provider(model, resolved.config, api_base)?
+                        .with_client(client)
+                        .with_model_rewrite(rewrite)
+                        .with_prompt_cache(resolved.prompt_cache()),
+                ),
+                ApiStyle::Ollama => Arc::new(
+                    OllamaProvider::from_provider(model, resolved.config, api_base)?
+                        .with_client(client)
+                        .with_model_rewrite(rewrite),
+                ),
+                ApiStyle::Responses => {
+                    let provider =
+                        OpenAiResponsesProvider::from_provider(model, resolved.config, api_base)?
+                            .with_client(client)
+                            .with_model_rewrite(rewrite);
+                    if resolved.custom {
+                        Arc::new(provider.allow_missing_key())
+                    } else {
+                        Arc::new(provider)
+                    }
+                }
+                ApiStyle::Chat | ApiStyle::Auto => {
+                    let provider =
+                        OpenAiCompatibleProvider::from_provider(model, resolved.config, api_base)?
+                            .with_client(client)
+                            .with_model_rewrite(rewrite)
+                            .with_prompt_cache(resolved.prompt_cache())
+                            .with_capabilities(config.capabilities.clone());
+                    if resolved.custom {
+                        Arc::new(provider.allow_missing_key())
+                    } else {
+                        Arc::new(provider)
+                    }
+                }
+            }
+        }
+        None => Arc::new(
+            OpenAiCompatibleProvider::from_provider(
+                model.to_string(),
+                &ProviderConfig::default(),
+                None,
+            )?
+            .with_client(client)
+            .with_capabilities(config.capabilities.clone()),
+        ),
+    };
+
+    let inner: Arc<dyn LlmProvider> = match provider.map(|provider| provider.tool_calls) {
+        Some(mode) if mode != ToolCallMode::Native => Arc::new(TextToolProvider::new(inner, mode)),
+        _ => inner,
+    };
+
+    let limiter = resolved.and_then(|resolved| RateLimiter::shared(&resolved));
+    let inner: Arc<dyn LlmProvider> = match limiter {
+        Some(limiter) => Arc::new(RateLimitedProvider::new(inner, limiter)),
+        None => inner,
+    };
+
+    let policy = provider
+        .map(|provider| RetryPolicy::from_config(&provider.retry))
+        .unwrap_or_default();
+    Ok(Arc::new(RetryingProvider::new(inner, policy)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::mock::{MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn embeddings_api_base_applies_to_remote_models() {
+        let server = MockServer::start(vec![MockResponse::json(
+            200,
+            json!({"data": [{"index": 0, "embedding": [0.5, 0.25]}]}),
+        )])
+        .await;
+        let mut config = Config::default();
+        config.providers.openai.api_key = "sk-test".to_string();
+        config.embeddings.model = "text-embedding-3-small".to_string();
+        config.embeddings.api_base = Some(server.base_url.clone());
+
+        let provider = embedding_provider_from_config(&config).unwrap();
+        let embeddings = provider.embed(vec!["hi".to_string()], None).await.unwrap();
+        assert_eq!(embeddings.dimensions(), 2);
+        let request = &server.requests()[0];
+        assert!(request.path.ends_with("/embeddings"));
+        assert_eq!(request.header("authorization"), Some("Bearer sk-test"));
+    }
+}
@@ -0,0 +1,262 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ferrumbot_config::ModelCapabilities;
+use serde_json::Value;
+
+use crate::capabilities::fit_request;
+use crate::{
+    ChatOptions, ChatStream, Embeddings, LlmProvider, LlmResponse, ProviderError, ProviderResult,
+    tag_model,
+};
+
+pub struct FallbackEntry {
+    pub model: String,
+    pub provider: Arc<dyn LlmProvider>,
+    pub capabilities: Option<ModelCapabilities>,
+}
+
+impl FallbackEntry {
+    pub fn new(model: String, provider: Arc<dyn LlmProvider>) -> Self {
+        Self {
+            model,
+            provider,
+            capabilities: None,
+        }
+    }
+
+    pub fn with_capabilities(mut self, capabilities: ModelCapabilities) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    fn fit(
+        &self,
+        messages: &[Value],
+        tools: &Option<Vec<Value>>,
+        options: &ChatOptions,
+    ) -> (Vec<Value>, Option<Vec<Value>>, ChatOptions) {
+        match &self.capabilities {
+            Some(capabilities) => {
+                fit_request(capabilities, messages.to_vec(), tools.clone(), options)
+            }
+            None => (messages.to_vec(), tools.clone(), options.clone()),
+        }
+    }
+}
+
+pub struct FallbackProvider {
+    entries: Vec<FallbackEntry>,
+}
+
+impl FallbackProvider {
+    pub fn new(entries: Vec<FallbackEntry>) -> Self {
+        assert!(
+            !entries.is_empty(),
+            "fallback chain needs at least one model"
+        );
+        Self { entries }
+    }
+
+    fn candidates<'a>(&'a self, model: Option<&'a str>) -> Vec<(&'a str, &'a FallbackEntry)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| {
+                let model = match model {
+                    Some(requested) if idx == 0 => requested,
+                    _ => entry.model.as_str(),
+                };
+                (model, entry)
+            })
+            .collect()
+    }
+
+    fn should_fall_back(&self, idx: usize, model: &str, err: &ProviderError) -> bool {
+        if !err.is_retryable() || idx + 1 >= self.entries.len() {
+            return false;
+        }
+        tracing::warn!(
+            "model {model} failed ({err}), falling back to {}",
+            self.entries[idx + 1].model
+        );
+        true
+    }
+}
+
+#[async_trait]
+impl LlmProvider for FallbackProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<LlmResponse> {
+        let candidates = self.candidates(model);
+        let mut idx = 0;
+        loop {
+            let (model, entry) = candidates[idx];
+            let (messages, tools, options) = entry.fit(&messages, &tools, options);
+            match entry
+                .provider
+                .chat(messages, tools, Some(model), &options)
+                .await
+            {
+                Ok(mut resp) => {
+                    resp.model = Some(model.to_string());
+                    return Ok(resp);
+                }
+                Err(err) if self.should_fall_back(idx, model, &err) => idx += 1,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<ChatStream> {
+        let candidates = self.candidates(model);
+        let mut idx = 0;
+        loop {
+            let (model, entry) = candidates[idx];
+            let (messages, tools, options) = entry.fit(&messages, &tools, options);
+            match entry
+                .provider
+                .chat_stream(messages, tools, Some(model), &options)
+                .await
+            {
+                Ok(stream) => return Ok(tag_model(stream, model.to_string())),
+                Err(err) if self.should_fall_back(idx, model, &err) => idx += 1,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    async fn embed(&self, texts: Vec<String>, model: Option<&str>) -> ProviderResult<Embeddings> {
+        self.entries[0].provider.embed(texts, model).await
+    }
+
+    async fn list_models(&self) -> ProviderResult<Vec<String>> {
+        self.entries[0].provider.list_models().await
+    }
+
+    fn get_default_model(&self) -> &str {
+        &self.entries[0].model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::ScriptedProvider;
+
+    struct Stub {
+        result: ProviderResult<LlmResponse>,
+        seen: Mutex<Vec<String>>,
+    }
+
+    impl Stub {
+        fn new(result: ProviderResult<LlmResponse>) -> Arc<Self> {
+            Arc::new(Self {
+                result,
+                seen: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for Stub {
+        async fn chat_stream(
+            &self,
+            _messages: Vec<Value>,
+            _tools: Option<Vec<Value>>,
+            model: Option<&str>,
+            _options: &ChatOptions,
+        ) -> ProviderResult<ChatStream> {
+            self.seen
+                .lock()
+                .unwrap()
+                .push(model.unwrap_or_default().to_string());
+            self.result.clone().map(crate::response_stream)
+        }
+
+        fn get_default_model(&self) -> &str {
+            "stub"
+        }
+    }
+
+    #[tokio::test]
+    async fn falls_back_on_retryable_errors_only() {
+        let primary = Stub::new(Err(ProviderError::Server {
+            status: 503,
+            message: "overloaded".to_string(),
+        }));
+        let secondary = Stub::new(Ok(LlmResponse::text("hi")));
+        let chain = FallbackProvider::new(vec![
+            FallbackEntry::new("a/one".to_string(), primary.clone()),
+            FallbackEntry::new("b/two".to_string(), secondary.clone()),
+        ]);
+
+        let resp = chain
+            .chat(Vec::new(), None, None, &ChatOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(resp.content.as_deref(), Some("hi"));
+        assert_eq!(resp.model.as_deref(), Some("b/two"));
+        assert_eq!(*primary.seen.lock().unwrap(), vec!["a/one"]);
+        assert_eq!(*secondary.seen.lock().unwrap(), vec!["b/two"]);
+
+        let denied = Stub::new(Err(ProviderError::Auth("bad key".to_string())));
+        let chain = FallbackProvider::new(vec![
+            FallbackEntry::new("a/one".to_string(), denied),
+            FallbackEntry::new("b/two".to_string(), secondary.clone()),
+        ]);
+        let err = chain
+            .chat(Vec::new(), None, None, &ChatOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProviderError::Auth(_)));
+        assert_eq!(secondary.seen.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn fallback_requests_fit_the_serving_model() {
+        let primary = Stub::new(Err(ProviderError::Network("reset".to_string())));
+        let secondary = Arc::new(ScriptedProvider::new(vec![LlmResponse::text("ok")]));
+        let chain = FallbackProvider::new(vec![
+            FallbackEntry::new("a/one".to_string(), primary),
+            FallbackEntry::new("b/two".to_string(), secondary.clone()).with_capabilities(
+                ModelCapabilities {
+                    max_output_tokens: Some(1024),
+                    tools: Some(false),
+                    vision: Some(false),
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        let messages = vec![serde_json::json!({"role": "user", "content": [
+            {"type": "text", "text": "look"},
+            {"type": "image_url", "image_url": {"url": "https://example.com/cat.png"}}
+        ]})];
+        let options = ChatOptions {
+            max_tokens: Some(8192),
+            ..Default::default()
+        };
+        chain
+            .chat(messages, Some(vec![serde_json::json!({})]), None, &options)
+            .await
+            .unwrap();
+
+        assert_eq!(secondary.options()[0].max_tokens, Some(1024));
+        assert!(secondary.tools()[0].is_none());
+        assert_eq!(secondary.requests()[0][0]["content"][1]["type"], "text");
+    }
+}
@@ -1,7 +1,47 @@
+mod anthropic;
+mod capabilities;
+mod cassette;
+mod embeddings;
+mod error;
+mod factory;
+mod fallback;
+mod ollama;
 mod openai;
+mod prompt_cache;
 mod provider;
+mod rate_limit;
+mod reasoning;
+mod responses;
+mod retry;
+mod scripted;
+mod stream;
+mod structured;
+mod text_tools;
 mod types;
 
+#[cfg(test)]
+mod mock;
+
+pub use anthropic::AnthropicProvider;
+pub use capabilities::fit_options;
+pub use cassette::{CassetteEntry, RecordingProvider, ReplayProvider, request_key};
+pub use embeddings::{EmbeddingCache, LocalEmbeddingProvider};
+pub use error::{ProviderError, ProviderResult};
+pub use factory::{
+    ModelRoute, capabilities_for, embedding_provider_from_config, provider_for_model,
+    provider_from_config, provider_named, resolve_model, route_for,
+};
+pub use fallback::{FallbackEntry, FallbackProvider};
+pub use ollama::OllamaProvider;
 pub use openai::OpenAiCompatibleProvider;
 pub use provider::LlmProvider;
-pub use types::{LlmResponse, ToolCallRequest};
+pub use rate_limit::{RateLimitedProvider, RateLimiter};
+pub use responses::OpenAiResponsesProvider;
+pub use retry::{RetryPolicy, RetryingProvider};
+pub use scripted::ScriptedProvider;
+pub use stream::{StreamAccumulator, collect_stream, response_stream, tag_model};
+pub use structured::{chat_structured, chat_typed};
+pub use text_tools::TextToolProvider;
+pub use types::{
+    ChatOptions, ChatStream, Embeddings, LlmResponse, ResponseFormat, StreamEvent, ToolCallRequest,
+};
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use ferrumbot_config::{ModelRewrite, ProviderConfig};
+use reqwest::Client;
+
+use super::{DEFAULT_API_BASE, OllamaProvider};
+
+impl OllamaProvider {
+    pub fn from_provider(
+        model: String,
+        provider: &ProviderConfig,
+        api_base: Option<String>,
+    ) -> Result<Self> {
+        let api_base = api_base.unwrap_or_else(|| DEFAULT_API_BASE.to_string());
+        let api_base = api_base.trim_end_matches('/');
+        let api_base = api_base
+            .strip_suffix("/v1")
+            .or_else(|| api_base.strip_suffix("/api"))
+            .unwrap_or(api_base);
+        let ollama = provider.ollama.clone().unwrap_or_default();
+        Ok(Self {
+            client: provider.http.client()?,
+            api_key: provider.api_key.clone(),
+            api_base: api_base.to_string(),
+            default_model: model,
+            extra_headers: provider.extra_headers.clone().unwrap_or_default(),
+            rewrite: ModelRewrite::default(),
+            num_ctx: ollama.num_ctx,
+            keep_alive: ollama.keep_alive,
+        })
+    }
+
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn with_model_rewrite(mut self, rewrite: ModelRewrite) -> Self {
+        self.rewrite = rewrite;
+        self
+    }
+
+    pub(super) fn wire_model(&self, model: &str) -> String {
+        self.rewrite.apply(model)
+    }
+}
@@ -0,0 +1,134 @@
+use async_trait::async_trait;
+use reqwest::{Method, RequestBuilder, Response};
+use serde_json::{Value, json};
+
+use crate::provider::parse_model_ids;
+use crate::{
+    ChatOptions, ChatStream, LlmProvider, LlmResponse, ProviderError, ProviderResult, tag_model,
+};
+
+use super::OllamaProvider;
+use super::convert::to_ollama_messages;
+use super::parse::parse_chat_payload;
+use super::stream::ndjson_stream;
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<LlmResponse> {
+        let model = model.unwrap_or(&self.default_model).to_string();
+        let body = self.build_body(messages, tools, &model, options);
+        tracing::debug!("Request body: {:?}", body);
+
+        let resp = Self::execute(self.request(Method::POST, "api/chat").json(&body)).await?;
+        let payload: Value = resp.json().await?;
+        let mut resp = parse_chat_payload(payload)?;
+        resp.model = Some(model);
+        Ok(resp)
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<ChatStream> {
+        let model = model.unwrap_or(&self.default_model).to_string();
+        let mut body = self.build_body(messages, tools, &model, options);
+        body["stream"] = Value::Bool(true);
+
+        let resp = Self::execute(self.request(Method::POST, "api/chat").json(&body)).await?;
+        Ok(tag_model(ndjson_stream(resp), model))
+    }
+
+    async fn list_models(&self) -> ProviderResult<Vec<String>> {
+        let resp = Self::execute(self.request(Method::GET, "api/tags")).await?;
+        let payload: Value = resp.json().await?;
+        parse_model_ids(&payload)
+    }
+
+    fn get_default_model(&self) -> &str {
+        &self.default_model
+    }
+}
+
+impl OllamaProvider {
+    fn build_body(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: &str,
+        options: &ChatOptions,
+    ) -> Value {
+        let mut model_options = json!({});
+        if let Some(num_ctx) = self.num_ctx {
+            model_options["num_ctx"] = json!(num_ctx);
+        }
+        if let Some(max_tokens) = options.max_tokens {
+            model_options["num_predict"] = json!(max_tokens);
+        }
+        if let Some(temperature) = options.temperature {
+            model_options["temperature"] = json!(temperature);
+        }
+        if let Some(top_p) = options.top_p {
+            model_options["top_p"] = json!(top_p);
+        }
+        if !options.stop.is_empty() {
+            model_options["stop"] = json!(options.stop);
+        }
+        if let Some(seed) = options.seed {
+            model_options["seed"] = json!(seed);
+        }
+
+        let mut body = json!({
+            "model": self.wire_model(model),
+            "messages": to_ollama_messages(messages),
+            "stream": false,
+            "options": model_options,
+        });
+        if let Some(keep_alive) = &self.keep_alive {
+            body["keep_alive"] = match keep_alive.parse::<i64>() {
+                Ok(seconds) => json!(seconds),
+                Err(_) => json!(keep_alive),
+            };
+        }
+        if options.reasoning_effort.is_some() {
+            body["think"] = Value::Bool(true);
+        }
+        if let Some(format) = &options.response_format {
+            body["format"] = format.schema.clone();
+        }
+        if let Some(tools) = tools.filter(|t| !t.is_empty()) {
+            body["tools"] = Value::Array(tools);
+        }
+        body
+    }
+
+    fn request(&self, method: Method, endpoint: &str) -> RequestBuilder {
+        let url = format!("{}/{endpoint}", self.api_base);
+        tracing::debug!("Request URL: {}", url);
+        let mut req = self.client.request(method, url);
+        if !self.api_key.is_empty() {
+            req = req.bearer_auth(&self.api_key);
+        }
+        for (k, v) in &self.extra_headers {
+            req = req.header(k, v);
+        }
+        req
+    }
+
+    async fn execute(req: RequestBuilder) -> ProviderResult<Response> {
+        let resp = req.send().await?;
+        tracing::debug!("Response status: {:?}", resp.status());
+        if !resp.status().is_success() {
+            return Err(ProviderError::from_response(resp).await);
+        }
+        Ok(resp)
+    }
+}
@@ -0,0 +1,91 @@
+use serde_json::{Map, Value, json};
+
+use crate::prompt_cache::without_cache_markers;
+
+pub(super) fn to_ollama_messages(messages: Vec<Value>) -> Vec<Value> {
+    without_cache_markers(messages)
+        .into_iter()
+        .map(|message| {
+            let role = message
+                .get("role")
+                .and_then(Value::as_str)
+                .unwrap_or("user")
+                .to_string();
+            let (content, images) = split_content(message.get("content"));
+
+            let mut out = Map::new();
+            out.insert("role".to_string(), json!(role));
+            out.insert("content".to_string(), json!(content));
+            if !images.is_empty() {
+                out.insert("images".to_string(), json!(images));
+            }
+            match role.as_str() {
+                "assistant" => {
+                    let calls: Vec<Value> = message
+                        .get("tool_calls")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                        .map(tool_call)
+                        .collect();
+                    if !calls.is_empty() {
+                        out.insert("tool_calls".to_string(), Value::Array(calls));
+                    }
+                }
+                "tool" => {
+                    if let Some(name) = message.get("name") {
+                        out.insert("tool_name".to_string(), name.clone());
+                    }
+                }
+                _ => {}
+            }
+            Value::Object(out)
+        })
+        .collect()
+}
+
+fn split_content(content: Option<&Value>) -> (String, Vec<String>) {
+    match content {
+        Some(Value::String(text)) => (text.clone(), Vec::new()),
+        Some(Value::Array(parts)) => {
+            let mut text = Vec::new();
+            let mut images = Vec::new();
+            for part in parts {
+                match part.get("type").and_then(Value::as_str) {
+                    Some("text") => text.extend(part.get("text").and_then(Value::as_str)),
+                    Some("image_url") => {
+                        let url = part
+                            .pointer("/image_url/url")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default();
+                        match url.split_once(";base64,") {
+                            Some((_, data)) if url.starts_with("data:") => {
+                                images.push(data.to_string())
+                            }
+                            _ => tracing::debug!("ollama only accepts inline images, skipping"),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            (text.join("\n"), images)
+        }
+        _ => (String::new(), Vec::new()),
+    }
+}
+
+fn tool_call(call: &Value) -> Value {
+    let arguments = match call.pointer("/function/arguments") {
+        Some(Value::String(raw)) => {
+            serde_json::from_str(raw).unwrap_or_else(|_| json!({ "raw": raw }))
+        }
+        Some(other) => other.clone(),
+        None => json!({}),
+    };
+    json!({
+        "function": {
+            "name": call.pointer("/function/name").cloned().unwrap_or(Value::Null),
+            "arguments": arguments,
+        }
+    })
+}
@@ -0,0 +1,26 @@
+use std::collections::BTreeMap;
+
+use ferrumbot_config::ModelRewrite;
+use reqwest::Client;
+
+pub struct OllamaProvider {
+    pub(super) client: Client,
+    pub(super) api_key: String,
+    pub(super) api_base: String,
+    pub(super) default_model: String,
+    pub(super) extra_headers: BTreeMap<String, String>,
+    pub(super) rewrite: ModelRewrite,
+    pub(super) num_ctx: Option<u32>,
+    pub(super) keep_alive: Option<String>,
+}
+
+pub(crate) const DEFAULT_API_BASE: &str = "http://localhost:11434";
+
+mod build;
+mod call;
+mod convert;
+mod parse;
+mod stream;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::reasoning::split_think;
+use crate::{LlmResponse, ProviderError, ProviderResult, ToolCallRequest};
+
+pub(super) fn parse_chat_payload(payload: Value) -> ProviderResult<LlmResponse> {
+    tracing::debug!("parse_chat_payload payload: {:?}", payload);
+
+    let Some(message) = payload.get("message") else {
+        return Err(ProviderError::MalformedResponse(format!(
+            "missing message in response: {payload}"
+        )));
+    };
+
+    let content = message
+        .get("content")
+        .and_then(Value::as_str)
+        .filter(|c| !c.is_empty())
+        .map(ToString::to_string);
+    let reasoning = message
+        .get("thinking")
+        .and_then(Value::as_str)
+        .filter(|r| !r.is_empty())
+        .map(ToString::to_string);
+
+    let tool_calls: Vec<ToolCallRequest> = message
+        .get("tool_calls")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|call| ToolCallRequest {
+            id: call
+                .get("id")
+                .and_then(Value::as_str)
+                .map(ToString::to_string)
+                .unwrap_or_else(|| format!("call_{:08x}", fastrand::u32(..))),
+            name: call
+                .pointer("/function/name")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            arguments: call
+                .pointer("/function/arguments")
+                .cloned()
+                .unwrap_or_else(|| Value::Object(Default::default())),
+        })
+        .collect();
+
+    let mut usage = BTreeMap::new();
+    let prompt = payload.get("prompt_eval_count").and_then(Value::as_i64);
+    let completion = payload.get("eval_count").and_then(Value::as_i64);
+    if let Some(v) = prompt {
+        usage.insert("prompt_tokens".to_string(), v);
+    }
+    if let Some(v) = completion {
+        usage.insert("completion_tokens".to_string(), v);
+    }
+    if prompt.is_some() || completion.is_some() {
+        usage.insert(
+            "total_tokens".to_string(),
+            prompt.unwrap_or_default() + completion.unwrap_or_default(),
+        );
+    }
+
+    let finish_reason = if !tool_calls.is_empty() {
+        "tool_calls"
+    } else if payload.get("done_reason").and_then(Value::as_str) == Some("length") {
+        "length"
+    } else {
+        "stop"
+    };
+
+    let mut resp = LlmResponse {
+        content,
+        tool_calls,
+        finish_reason: finish_reason.to_string(),
+        usage,
+        model: None,
+        reasoning,
+        thinking_blocks: Vec::new(),
+    };
+    split_think(&mut resp);
+    Ok(resp)
+}
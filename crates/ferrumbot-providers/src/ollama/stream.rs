@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+
+use futures::StreamExt;
+use reqwest::Response;
+use serde_json::{Value, json};
+
+use crate::reasoning::{Segment, ThinkSplitter};
+use crate::{ChatStream, ProviderError, ProviderResult, StreamEvent};
+
+use super::parse::parse_chat_payload;
+
+pub(super) fn ndjson_stream(resp: Response) -> ChatStream {
+    let state = NdjsonState {
+        resp,
+        buf: Vec::new(),
+        assembler: ChunkAssembler::default(),
+        pending: VecDeque::new(),
+    };
+
+    futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                return Some((event, state));
+            }
+            if state.assembler.done {
+                return None;
+            }
+
+            match state.resp.chunk().await {
+                Ok(Some(bytes)) => {
+                    state.buf.extend_from_slice(&bytes);
+                    while let Some(pos) = state.buf.iter().position(|b| *b == b'\n') {
+                        let line: Vec<u8> = state.buf.drain(..=pos).collect();
+                        state.pending.extend(state.assembler.handle(&line));
+                    }
+                }
+                Ok(None) => {
+                    let rest = std::mem::take(&mut state.buf);
+                    state.pending.extend(state.assembler.handle(&rest));
+                    state.pending.extend(state.assembler.finish());
+                }
+                Err(err) => {
+                    state.assembler.done = true;
+                    return Some((Err(ProviderError::from(err)), state));
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
+struct NdjsonState {
+    resp: Response,
+    buf: Vec<u8>,
+    assembler: ChunkAssembler,
+    pending: VecDeque<ProviderResult<StreamEvent>>,
+}
+
+#[derive(Default)]
+pub(super) struct ChunkAssembler {
+    content: String,
+    thinking: String,
+    splitter: ThinkSplitter,
+    tool_calls: Vec<Value>,
+    last: Option<Value>,
+    pub(super) done: bool,
+}
+
+impl ChunkAssembler {
+    pub(super) fn handle(&mut self, line: &[u8]) -> Vec<ProviderResult<StreamEvent>> {
+        let line = String::from_utf8_lossy(line);
+        if line.trim().is_empty() || self.done {
+            return Vec::new();
+        }
+        let Ok(chunk) = serde_json::from_str::<Value>(&line) else {
+            tracing::debug!("skipping malformed stream chunk: {line}");
+            return Vec::new();
+        };
+        if let Some(err) = chunk.get("error").and_then(Value::as_str) {
+            self.done = true;
+            return vec![Err(ProviderError::MalformedResponse(err.to_string()))];
+        }
+
+        let mut events = Vec::new();
+        let message = chunk.get("message");
+        if let Some(text) = message
+            .and_then(|m| m.get("thinking"))
+            .and_then(Value::as_str)
+            .filter(|t| !t.is_empty())
+        {
+            self.thinking.push_str(text);
+            events.push(Ok(StreamEvent::ReasoningDelta(text.to_string())));
+        }
+        if let Some(text) = message
+            .and_then(|m| m.get("content"))
+            .and_then(Value::as_str)
+        {
+            self.content.push_str(text);
+            events.extend(self.splitter.push(text).into_iter().map(delta));
+        }
+        if let Some(calls) = message
+            .and_then(|m| m.get("tool_calls"))
+            .and_then(Value::as_array)
+        {
+            self.tool_calls.extend(calls.iter().cloned());
+        }
+
+        if chunk.get("done").and_then(Value::as_bool) == Some(true) {
+            self.last = Some(chunk);
+            events.extend(self.finish());
+        }
+        events
+    }
+
+    pub(super) fn finish(&mut self) -> Vec<ProviderResult<StreamEvent>> {
+        if self.done {
+            return Vec::new();
+        }
+        self.done = true;
+
+        let mut events: Vec<_> = self.splitter.finish().into_iter().map(delta).collect();
+        let mut payload = self.last.take().unwrap_or_else(|| json!({}));
+        payload["message"] = json!({
+            "role": "assistant",
+            "content": std::mem::take(&mut self.content),
+            "thinking": std::mem::take(&mut self.thinking),
+            "tool_calls": std::mem::take(&mut self.tool_calls),
+        });
+        match parse_chat_payload(payload) {
+            Ok(resp) => {
+                events.extend(
+                    resp.tool_calls
+                        .iter()
+                        .cloned()
+                        .map(|call| Ok(StreamEvent::ToolCall(call))),
+                );
+                events.push(Ok(StreamEvent::Done(resp)));
+            }
+            Err(err) => events.push(Err(err)),
+        }
+        events
+    }
+}
+
+fn delta(segment: Segment) -> ProviderResult<StreamEvent> {
+    Ok(match segment {
+        Segment::Text(text) => StreamEvent::TextDelta(text),
+        Segment::Reasoning(text) => StreamEvent::ReasoningDelta(text),
+    })
+}
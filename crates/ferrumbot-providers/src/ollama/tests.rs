@@ -0,0 +1,193 @@
+use futures::StreamExt;
+use serde_json::json;
+
+use ferrumbot_config::{OllamaConfig, ProviderConfig};
+
+use crate::mock::{MockResponse, MockServer};
+use crate::{ChatOptions, LlmProvider, StreamEvent};
+
+use super::OllamaProvider;
+
+fn provider(base_url: &str) -> OllamaProvider {
+    let config = ProviderConfig {
+        ollama: Some(OllamaConfig {
+            num_ctx: Some(32_768),
+            keep_alive: Some("30m".to_string()),
+        }),
+        ..Default::default()
+    };
+    OllamaProvider::from_provider(
+        "qwen3:14b".to_string(),
+        &config,
+        Some(format!("{base_url}/v1")),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn chat_sends_native_options_and_parses_usage() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        json!({
+            "model": "qwen3:14b",
+            "message": {"role": "assistant", "content": "Hi!", "thinking": "Say hi."},
+            "done": true,
+            "done_reason": "stop",
+            "prompt_eval_count": 26,
+            "eval_count": 4
+        }),
+    )])
+    .await;
+
+    let messages = vec![
+        json!({"role": "system", "content": "You are ferrum-bot."}),
+        json!({"role": "user", "content": [
+            {"type": "text", "text": "what is this?"},
+            {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0KGgo="}}
+        ]}),
+    ];
+    let resp = provider(&server.base_url)
+        .chat(
+            messages,
+            None,
+            None,
+            &ChatOptions {
+                max_tokens: Some(128),
+                temperature: Some(0.2),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("chat should succeed");
+
+    assert_eq!(resp.content.as_deref(), Some("Hi!"));
+    assert_eq!(resp.reasoning.as_deref(), Some("Say hi."));
+    assert_eq!(resp.finish_reason, "stop");
+    assert_eq!(resp.usage.get("prompt_tokens"), Some(&26));
+    assert_eq!(resp.usage.get("completion_tokens"), Some(&4));
+    assert_eq!(resp.usage.get("total_tokens"), Some(&30));
+
+    let request = &server.requests()[0];
+    assert_eq!(request.path, "/api/chat");
+    assert_eq!(request.header("authorization"), None);
+    let body = request.json();
+    assert_eq!(body["stream"], false);
+    assert_eq!(body["keep_alive"], "30m");
+    assert_eq!(body["options"]["num_ctx"], 32_768);
+    assert_eq!(body["options"]["num_predict"], 128);
+    assert_eq!(body["messages"][1]["content"], "what is this?");
+    assert_eq!(body["messages"][1]["images"], json!(["iVBORw0KGgo="]));
+}
+
+#[tokio::test]
+async fn chat_round_trips_tool_calls() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        json!({
+            "message": {
+                "role": "assistant",
+                "content": "",
+                "tool_calls": [{"function": {"name": "list_dir", "arguments": {"path": "."}}}]
+            },
+            "done": true,
+            "done_reason": "stop",
+            "prompt_eval_count": 80,
+            "eval_count": 15
+        }),
+    )])
+    .await;
+
+    let messages = vec![
+        json!({"role": "user", "content": "read the file"}),
+        json!({
+            "role": "assistant",
+            "content": "",
+            "tool_calls": [{
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "read_file", "arguments": "{\"path\":\"a.txt\"}"}
+            }]
+        }),
+        json!({"role": "tool", "tool_call_id": "call_1", "name": "read_file", "content": "hello"}),
+    ];
+    let tools = vec![json!({
+        "type": "function",
+        "function": {"name": "list_dir", "description": "List", "parameters": {"type": "object"}}
+    })];
+
+    let resp = provider(&server.base_url)
+        .chat(messages, Some(tools), None, &ChatOptions::default())
+        .await
+        .expect("chat should succeed");
+
+    assert_eq!(resp.content, None);
+    assert_eq!(resp.finish_reason, "tool_calls");
+    assert_eq!(resp.tool_calls.len(), 1);
+    assert!(resp.tool_calls[0].id.starts_with("call_"));
+    assert_eq!(resp.tool_calls[0].name, "list_dir");
+    assert_eq!(resp.tool_calls[0].arguments, json!({"path": "."}));
+    assert_eq!(resp.usage.get("total_tokens"), Some(&95));
+
+    let body = server.requests()[0].json();
+    assert_eq!(
+        body["messages"][1]["tool_calls"][0]["function"]["arguments"],
+        json!({"path": "a.txt"})
+    );
+    assert_eq!(body["messages"][2]["role"], "tool");
+    assert_eq!(body["messages"][2]["tool_name"], "read_file");
+    assert_eq!(body["tools"][0]["function"]["name"], "list_dir");
+}
+
+#[tokio::test]
+async fn chat_stream_reads_ndjson_chunks() {
+    let body = [
+        json!({"message": {"role": "assistant", "content": "", "thinking": "Greet."}, "done": false}),
+        json!({"message": {"role": "assistant", "content": "Hel"}, "done": false}),
+        json!({"message": {"role": "assistant", "content": "lo"}, "done": false}),
+        json!({
+            "message": {"role": "assistant", "content": ""},
+            "done": true,
+            "done_reason": "stop",
+            "prompt_eval_count": 10,
+            "eval_count": 3
+        }),
+    ]
+    .iter()
+    .map(|chunk| format!("{chunk}\n"))
+    .collect::<String>();
+    let server = MockServer::start(vec![MockResponse {
+        status: 200,
+        content_type: "application/x-ndjson",
+        headers: Vec::new(),
+        body,
+    }])
+    .await;
+
+    let mut stream = provider(&server.base_url)
+        .chat_stream(
+            vec![json!({"role": "user", "content": "hi"})],
+            None,
+            None,
+            &ChatOptions::default(),
+        )
+        .await
+        .expect("stream should open");
+
+    let mut deltas = Vec::new();
+    let mut done = None;
+    while let Some(event) = stream.next().await {
+        match event.expect("stream event") {
+            StreamEvent::TextDelta(text) => deltas.push(text),
+            StreamEvent::Done(resp) => done = Some(resp),
+            StreamEvent::ToolCall(_) | StreamEvent::ReasoningDelta(_) => {}
+        }
+    }
+
+    assert_eq!(deltas, ["Hel", "lo"]);
+    let done = done.expect("final response");
+    assert_eq!(done.content.as_deref(), Some("Hello"));
+    assert_eq!(done.reasoning.as_deref(), Some("Greet."));
+    assert_eq!(done.usage.get("total_tokens"), Some(&13));
+    assert_eq!(done.model.as_deref(), Some("qwen3:14b"));
+    assert_eq!(server.requests()[0].json()["stream"], true);
+}
@@ -3,37 +3,47 @@ use std::collections::BTreeMap;
 use anyhow::Result;
 use reqwest::Client;
 
-use ferrumbot_config::{Config, ProviderConfig};
+use ferrumbot_config::{CapabilityTable, Config, ModelRewrite, ProviderConfig};
 
-use super::OpenAiCompatibleProvider;
+use super::{DEFAULT_API_BASE, OpenAiCompatibleProvider};
 
 impl OpenAiCompatibleProvider {
     pub fn from_config(config: &Config) -> Result<Self> {
         let model = config.agents.defaults.model.clone();
         let provider = config.get_provider_for_model(Some(&model));
+        let resolved = config.resolve_provider(Some(&model));
+        let rewrite = resolved
+            .map(|resolved| resolved.model_rewrite())
+            .unwrap_or_default();
 
-        let (api_key, api_base, extra_headers) = if let Some(provider) = provider {
+        let (api_key, api_base, extra_headers, http) = if let Some(provider) = provider {
             (
                 provider.api_key.clone(),
                 config
                     .get_api_base(Some(&model))
-                    .unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
+                    .unwrap_or_else(|| DEFAULT_API_BASE.to_string()),
                 provider.extra_headers.clone().unwrap_or_default(),
+                config.http_for(provider),
             )
         } else {
             (
                 String::new(),
-                "https://api.openai.com/v1".to_string(),
+                DEFAULT_API_BASE.to_string(),
                 BTreeMap::new(),
+                config.http.clone(),
             )
         };
 
         Ok(Self {
-            client: Client::new(),
+            client: http.client()?,
             api_key,
             api_base,
             default_model: model,
             extra_headers,
+            rewrite,
+            key_optional: false,
+            prompt_cache: resolved.is_some_and(|resolved| resolved.prompt_cache()),
+            capabilities: config.capabilities.clone(),
         })
     }
 
@@ -41,13 +51,180 @@ impl OpenAiCompatibleProvider {
         model: String,
         provider: &ProviderConfig,
         api_base: Option<String>,
-    ) -> Self {
-        Self {
-            client: Client::new(),
+    ) -> Result<Self> {
+        Ok(Self {
+            client: provider.http.client()?,
             api_key: provider.api_key.clone(),
-            api_base: api_base.unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
+            api_base: api_base.unwrap_or_else(|| DEFAULT_API_BASE.to_string()),
             default_model: model,
             extra_headers: provider.extra_headers.clone().unwrap_or_default(),
+            rewrite: ModelRewrite::default(),
+            key_optional: false,
+            prompt_cache: false,
+            capabilities: CapabilityTable::default(),
+        })
+    }
+
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn with_model_rewrite(mut self, rewrite: ModelRewrite) -> Self {
+        self.rewrite = rewrite;
+        self
+    }
+
+    pub fn with_prompt_cache(mut self, enabled: bool) -> Self {
+        self.prompt_cache = enabled;
+        self
+    }
+
+    pub fn with_capabilities(mut self, capabilities: CapabilityTable) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    pub fn allow_missing_key(mut self) -> Self {
+        self.key_optional = true;
+        self
+    }
+
+    pub(super) fn wire_model(&self, model: &str) -> String {
+        self.rewrite.apply(model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    use ferrumbot_config::{HttpConfig, ProviderConfig};
+
+    use crate::mock::{MockResponse, MockServer};
+    use crate::{
+        ChatOptions, LlmProvider, OpenAiCompatibleProvider, ProviderError, ResponseFormat,
+    };
+
+    #[tokio::test]
+    async fn list_models_reads_sorted_ids() {
+        let server = MockServer::start(vec![MockResponse::json(
+            200,
+            json!({"object": "list", "data": [{"id": "gpt-4o"}, {"id": "gpt-4.1-mini"}]}),
+        )])
+        .await;
+        let provider = OpenAiCompatibleProvider::from_provider(
+            "gpt-4o".to_string(),
+            &ProviderConfig {
+                api_key: "sk-test".to_string(),
+                ..Default::default()
+            },
+            Some(server.base_url.clone()),
+        )
+        .unwrap();
+
+        let models = provider.list_models().await.unwrap();
+        assert_eq!(models, vec!["gpt-4.1-mini", "gpt-4o"]);
+        let request = &server.requests()[0];
+        assert_eq!(request.method, "GET");
+        assert!(request.path.ends_with("/models"));
+        assert_eq!(request.header("authorization"), Some("Bearer sk-test"));
+    }
+
+    #[tokio::test]
+    async fn hung_upstream_hits_read_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hold = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+            drop(socket);
+        });
+
+        let provider = OpenAiCompatibleProvider::from_provider(
+            "gpt-4o".to_string(),
+            &ProviderConfig {
+                api_key: "sk-test".to_string(),
+                http: HttpConfig {
+                    read_timeout_secs: Some(1),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(format!("http://{addr}/v1")),
+        )
+        .unwrap();
+        let err = provider
+            .chat(
+                vec![json!({"role": "user", "content": "hi"})],
+                None,
+                None,
+                &ChatOptions::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProviderError::Network(_)), "{err:?}");
+        hold.abort();
+    }
+
+    #[tokio::test]
+    async fn response_format_follows_structured_output_support() {
+        let reply = || {
+            MockResponse::json(
+                200,
+                json!({"choices": [{"message": {"content": "{}"}, "finish_reason": "stop"}]}),
+            )
+        };
+        let server = MockServer::start(vec![reply(), reply(), reply()]).await;
+        let provider = OpenAiCompatibleProvider::from_provider(
+            "gpt-4o".to_string(),
+            &ProviderConfig {
+                api_key: "sk-test".to_string(),
+                ..Default::default()
+            },
+            Some(server.base_url.clone()),
+        )
+        .unwrap();
+        let options = ChatOptions {
+            response_format: Some(ResponseFormat::new("answer", json!({"type": "object"}))),
+            ..Default::default()
+        };
+        let messages = vec![
+            json!({"role": "system", "content": "You are ferrum-bot."}),
+            json!({"role": "user", "content": "hi"}),
+        ];
+
+        for model in ["gpt-4o", "deepseek-chat", "llama-3.1-8b"] {
+            provider
+                .chat(messages.clone(), None, Some(model), &options)
+                .await
+                .unwrap();
         }
+        let requests = server.requests();
+        let native = requests[0].json();
+        assert_eq!(native["response_format"]["type"], "json_schema");
+        assert_eq!(native["messages"][0]["content"], "You are ferrum-bot.");
+
+        let json_object = requests[1].json();
+        assert_eq!(
+            json_object["response_format"],
+            json!({"type": "json_object"})
+        );
+        assert!(
+            json_object["messages"][0]["content"]
+                .as_str()
+                .unwrap()
+                .contains("matches this JSON schema")
+        );
+
+        let prompted = requests[2].json();
+        assert!(prompted.get("response_format").is_none());
+        assert!(
+            prompted["messages"][0]["content"]
+                .as_str()
+                .unwrap()
+                .contains("matches this JSON schema")
+        );
     }
 }
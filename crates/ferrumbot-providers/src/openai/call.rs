@@ -1,13 +1,19 @@
-use std::collections::BTreeMap;
-
-use anyhow::{Context, Result};
 use async_trait::async_trait;
+use reqwest::{Method, RequestBuilder, Response};
 use serde_json::{Value, json};
 
-use crate::{LlmProvider, LlmResponse};
+use crate::embeddings::{EMBED_BATCH_SIZE, parse_embeddings};
+use crate::prompt_cache::without_cache_markers;
+use crate::provider::parse_model_ids;
+use crate::structured::with_system_text;
+use crate::{
+    ChatOptions, ChatStream, Embeddings, LlmProvider, LlmResponse, ProviderError, ProviderResult,
+    tag_model,
+};
 
 use super::OpenAiCompatibleProvider;
 use super::parse::parse_chat_response;
+use super::stream::sse_stream;
 
 #[async_trait]
 impl LlmProvider for OpenAiCompatibleProvider {
@@ -16,62 +22,164 @@ impl LlmProvider for OpenAiCompatibleProvider {
         messages: Vec<Value>,
         tools: Option<Vec<Value>>,
         model: Option<&str>,
-        max_tokens: Option<usize>,
-        temperature: Option<f32>,
-    ) -> Result<LlmResponse> {
-        if self.api_key.is_empty() {
-            return Ok(LlmResponse {
-                content: Some("Error calling LLM: API key not configured".to_string()),
-                tool_calls: Vec::new(),
-                finish_reason: "error".to_string(),
-                usage: BTreeMap::new(),
-            });
+        options: &ChatOptions,
+    ) -> ProviderResult<LlmResponse> {
+        let model = model.unwrap_or(&self.default_model).to_string();
+        let body = self.build_body(messages, tools, &model, options);
+        let resp = self.send("chat/completions", &body).await?;
+        let payload: Value = resp.json().await?;
+        let mut resp = parse_chat_response(payload)?;
+        resp.model = Some(model);
+        Ok(resp)
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<ChatStream> {
+        let model = model.unwrap_or(&self.default_model).to_string();
+        let mut body = self.build_body(messages, tools, &model, options);
+        body["stream"] = Value::Bool(true);
+        body["stream_options"] = json!({"include_usage": true});
+
+        let resp = self.send("chat/completions", &body).await?;
+        Ok(tag_model(sse_stream(resp), model))
+    }
+
+    async fn embed(&self, texts: Vec<String>, model: Option<&str>) -> ProviderResult<Embeddings> {
+        let model = model.unwrap_or(&self.default_model);
+        let wire_model = self.wire_model(model);
+        let mut embeddings = Embeddings::new(model);
+        for batch in texts.chunks(EMBED_BATCH_SIZE) {
+            let body = json!({"model": wire_model, "input": batch});
+            let resp = self.send("embeddings", &body).await?;
+            let payload: Value = resp.json().await?;
+            embeddings.extend(parse_embeddings(payload, model, batch.len())?);
         }
+        Ok(embeddings)
+    }
 
-        let model = self.normalize_model(model.unwrap_or(&self.default_model));
+    async fn list_models(&self) -> ProviderResult<Vec<String>> {
+        let req = self.request(Method::GET, "models")?;
+        let resp = Self::execute(req).await?;
+        let payload: Value = resp.json().await?;
+        parse_model_ids(&payload)
+    }
+
+    fn get_default_model(&self) -> &str {
+        &self.default_model
+    }
+}
+
+impl OpenAiCompatibleProvider {
+    fn build_body(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: &str,
+        options: &ChatOptions,
+    ) -> Value {
+        let capabilities = self.capabilities.lookup(model);
+        let structured = capabilities.supports_structured_outputs();
+        let model = self.wire_model(model);
+        let messages: Vec<Value> = messages
+            .into_iter()
+            .map(|mut message| {
+                if let Some(message) = message.as_object_mut() {
+                    message.remove("thinking_blocks");
+                }
+                message
+            })
+            .collect();
+        let messages = if self.prompt_cache {
+            messages
+        } else {
+            without_cache_markers(messages)
+        };
+        let messages = match &options.response_format {
+            Some(format) if !structured => with_system_text(messages, &format.instructions()),
+            _ => messages,
+        };
         let mut body = json!({
             "model": model,
             "messages": messages,
-            "max_tokens": max_tokens.unwrap_or(4096),
-            "temperature": temperature.unwrap_or(0.7),
+            "max_tokens": options.max_tokens.unwrap_or(4096),
+            "temperature": options.temperature.unwrap_or(0.7),
         });
+        if let Some(top_p) = options.top_p {
+            body["top_p"] = json!(top_p);
+        }
+        if !options.stop.is_empty() {
+            body["stop"] = json!(options.stop);
+        }
+        if let Some(seed) = options.seed {
+            body["seed"] = json!(seed);
+        }
+        if let Some(effort) = &options.reasoning_effort {
+            body["reasoning_effort"] = json!(effort);
+        }
+        if let Some(format) = &options.response_format {
+            if structured {
+                body["response_format"] = json!({
+                    "type": "json_schema",
+                    "json_schema": {"name": format.name, "schema": format.schema},
+                });
+            } else if capabilities.supports_json_mode() {
+                body["response_format"] = json!({"type": "json_object"});
+            }
+        }
 
         if let Some(tools) = tools {
             body["tools"] = Value::Array(tools);
             body["tool_choice"] = Value::String("auto".to_string());
+            if let Some(parallel) = options.parallel_tool_calls {
+                body["parallel_tool_calls"] = Value::Bool(parallel);
+            }
         }
+        body
+    }
 
-        let url = format!("{}/chat/completions", self.api_base.trim_end_matches('/'));
-        tracing::debug!("Request URL: {}", url);
+    async fn send(&self, endpoint: &str, body: &Value) -> ProviderResult<Response> {
         tracing::debug!("Request body: {:?}", body);
+        let req = self
+            .request(Method::POST, endpoint)?
+            .json(body)
+            .header("Content-Type", "application/json");
+        Self::execute(req).await
+    }
+
+    fn request(&self, method: Method, endpoint: &str) -> ProviderResult<RequestBuilder> {
+        if self.api_key.is_empty() && !self.key_optional {
+            return Err(ProviderError::Auth("API key not configured".to_string()));
+        }
+
+        let url = format!("{}/{endpoint}", self.api_base.trim_end_matches('/'));
+        tracing::debug!("Request URL: {}", url);
         tracing::debug!(
             "API key first 10 chars: {}",
             &self.api_key[..std::cmp::min(10, self.api_key.len())]
         );
 
-        let mut req = self
-            .client
-            .post(url)
-            .bearer_auth(&self.api_key)
-            .json(&body)
-            .header("Content-Type", "application/json");
+        let mut req = self.client.request(method, url);
+        if !self.api_key.is_empty() {
+            req = req.bearer_auth(&self.api_key);
+        }
 
         for (k, v) in &self.extra_headers {
             req = req.header(k, v);
         }
-
-        let resp = req.send().await.context("failed to call provider")?;
-        tracing::debug!("Response status: {:?}", resp.status());
-        let success = resp.status().is_success();
-        let payload: Value = resp
-            .json()
-            .await
-            .context("provider response is not valid JSON")?;
-
-        Ok(parse_chat_response(payload, success))
+        Ok(req)
     }
 
-    fn get_default_model(&self) -> &str {
-        &self.default_model
+    async fn execute(req: RequestBuilder) -> ProviderResult<Response> {
+        let resp = req.send().await?;
+        tracing::debug!("Response status: {:?}", resp.status());
+        if !resp.status().is_success() {
+            return Err(ProviderError::from_response(resp).await);
+        }
+        Ok(resp)
     }
 }
@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 
+use ferrumbot_config::{CapabilityTable, ModelRewrite};
 use reqwest::Client;
 
 pub struct OpenAiCompatibleProvider {
@@ -8,9 +9,15 @@ pub struct OpenAiCompatibleProvider {
     pub(super) api_base: String,
     pub(super) default_model: String,
     pub(super) extra_headers: BTreeMap<String, String>,
+    pub(super) rewrite: ModelRewrite,
+    pub(super) key_optional: bool,
+    pub(super) prompt_cache: bool,
+    pub(super) capabilities: CapabilityTable,
 }
 
+pub(crate) const DEFAULT_API_BASE: &str = "https://api.openai.com/v1";
+
 mod build;
 mod call;
-mod model;
 mod parse;
+mod stream;
@@ -2,26 +2,18 @@ use std::collections::BTreeMap;
 
 use serde_json::{Value, json};
 
-use crate::{LlmResponse, ToolCallRequest};
+use crate::reasoning::split_think;
+use crate::{LlmResponse, ProviderError, ProviderResult, ToolCallRequest};
 
-pub(super) fn parse_chat_response(payload: Value, success: bool) -> LlmResponse {
+pub(super) fn parse_chat_response(payload: Value) -> ProviderResult<LlmResponse> {
     tracing::debug!("parse_chat_response payload: {:?}", payload);
 
-    if !success {
-        return LlmResponse {
-            content: Some(format!("Error calling LLM: {}", payload)),
-            tool_calls: Vec::new(),
-            finish_reason: "error".to_string(),
-            usage: BTreeMap::new(),
-        };
-    }
-
-    let choice = payload
-        .get("choices")
-        .and_then(|v| v.as_array())
-        .and_then(|arr| arr.first())
-        .cloned()
-        .unwrap_or_else(|| json!({}));
+    let Some(choices) = payload.get("choices").and_then(|v| v.as_array()) else {
+        return Err(ProviderError::MalformedResponse(format!(
+            "missing choices in response: {payload}"
+        )));
+    };
+    let choice = choices.first().cloned().unwrap_or_else(|| json!({}));
 
     tracing::debug!("choice: {:?}", choice);
 
@@ -35,6 +27,13 @@ pub(super) fn parse_chat_response(payload: Value, success: bool) -> LlmResponse
 
     tracing::debug!("parsed content: {:?}", content);
 
+    let reasoning = message
+        .get("reasoning_content")
+        .or_else(|| message.get("reasoning"))
+        .and_then(|v| v.as_str())
+        .filter(|r| !r.is_empty())
+        .map(ToString::to_string);
+
     let mut tool_calls = Vec::new();
     if let Some(items) = message.get("tool_calls").and_then(|v| v.as_array()) {
         for item in items {
@@ -68,15 +67,11 @@ pub(super) fn parse_chat_response(payload: Value, success: bool) -> LlmResponse
     }
 
     let mut usage = BTreeMap::new();
-    if let Some(obj) = payload.get("usage").and_then(|v| v.as_object()) {
-        for key in ["prompt_tokens", "completion_tokens", "total_tokens"] {
-            if let Some(v) = obj.get(key).and_then(|x| x.as_i64()) {
-                usage.insert(key.to_string(), v);
-            }
-        }
+    if let Some(obj) = payload.get("usage") {
+        read_usage(obj, &mut usage);
     }
 
-    LlmResponse {
+    let mut resp = LlmResponse {
         content,
         tool_calls,
         finish_reason: choice
@@ -85,5 +80,25 @@ pub(super) fn parse_chat_response(payload: Value, success: bool) -> LlmResponse
             .unwrap_or("stop")
             .to_string(),
         usage,
+        model: None,
+        reasoning,
+        thinking_blocks: Vec::new(),
+    };
+    split_think(&mut resp);
+    Ok(resp)
+}
+
+pub(super) fn read_usage(obj: &Value, usage: &mut BTreeMap<String, i64>) {
+    for key in ["prompt_tokens", "completion_tokens", "total_tokens"] {
+        if let Some(v) = obj.get(key).and_then(|x| x.as_i64()) {
+            usage.insert(key.to_string(), v);
+        }
+    }
+    if let Some(cached) = obj
+        .pointer("/prompt_tokens_details/cached_tokens")
+        .or_else(|| obj.get("prompt_cache_hit_tokens"))
+        .and_then(|x| x.as_i64())
+    {
+        usage.insert("cached_tokens".to_string(), cached);
     }
 }
@@ -0,0 +1,340 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use futures::StreamExt;
+use reqwest::Response;
+use serde_json::{Value, json};
+
+use super::parse::read_usage;
+use crate::reasoning::{Segment, ThinkSplitter};
+use crate::stream::SseLines;
+use crate::{ChatStream, LlmResponse, ProviderError, StreamEvent, ToolCallRequest};
+
+pub(super) fn sse_stream(resp: Response) -> ChatStream {
+    let state = SseState {
+        resp,
+        lines: SseLines::default(),
+        assembler: ChunkAssembler::default(),
+        pending: VecDeque::new(),
+        finished: false,
+    };
+
+    futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                return Some((Ok(event), state));
+            }
+            if state.finished {
+                return None;
+            }
+
+            match state.resp.chunk().await {
+                Ok(Some(bytes)) => {
+                    for data in state.lines.push(&bytes) {
+                        state.pending.extend(state.assembler.handle(&data));
+                    }
+                    if state.assembler.done {
+                        state.finished = true;
+                        state.pending.extend(state.assembler.finish());
+                    }
+                }
+                Ok(None) => {
+                    state.finished = true;
+                    state.pending.extend(state.assembler.finish());
+                }
+                Err(err) => {
+                    state.finished = true;
+                    return Some((Err(ProviderError::from(err)), state));
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
+struct SseState {
+    resp: Response,
+    lines: SseLines,
+    assembler: ChunkAssembler,
+    pending: VecDeque<StreamEvent>,
+    finished: bool,
+}
+
+#[derive(Default)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+#[derive(Default)]
+pub(super) struct ChunkAssembler {
+    content: String,
+    reasoning: String,
+    splitter: ThinkSplitter,
+    tool_calls: BTreeMap<u64, PartialToolCall>,
+    finish_reason: Option<String>,
+    usage: BTreeMap<String, i64>,
+    pub(super) done: bool,
+    emitted: bool,
+}
+
+impl ChunkAssembler {
+    pub(super) fn handle(&mut self, data: &str) -> Vec<StreamEvent> {
+        if data == "[DONE]" {
+            self.done = true;
+            return Vec::new();
+        }
+
+        let Ok(chunk) = serde_json::from_str::<Value>(data) else {
+            tracing::debug!("skipping malformed stream chunk: {data}");
+            return Vec::new();
+        };
+
+        if let Some(obj) = chunk.get("usage").filter(|v| v.is_object()) {
+            read_usage(obj, &mut self.usage);
+        }
+
+        let mut events = Vec::new();
+        let Some(choice) = chunk
+            .get("choices")
+            .and_then(|v| v.as_array())
+            .and_then(|arr| arr.first())
+        else {
+            return events;
+        };
+
+        if let Some(reason) = choice.get("finish_reason").and_then(|v| v.as_str()) {
+            self.finish_reason = Some(reason.to_string());
+        }
+
+        let delta = choice.get("delta").cloned().unwrap_or_else(|| json!({}));
+        if let Some(text) = delta
+            .get("reasoning_content")
+            .or_else(|| delta.get("reasoning"))
+            .and_then(|v| v.as_str())
+            && !text.is_empty()
+        {
+            self.reasoning.push_str(text);
+            events.push(StreamEvent::ReasoningDelta(text.to_string()));
+        }
+        if let Some(text) = delta.get("content").and_then(|v| v.as_str()) {
+            let segments = self.splitter.push(text);
+            events.extend(self.apply(segments));
+        }
+
+        if let Some(items) = delta.get("tool_calls").and_then(|v| v.as_array()) {
+            for (pos, item) in items.iter().enumerate() {
+                let index = item
+                    .get("index")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(pos as u64);
+                let entry = self.tool_calls.entry(index).or_default();
+                if let Some(id) = item.get("id").and_then(|v| v.as_str()) {
+                    entry.id = id.to_string();
+                }
+                if let Some(function) = item.get("function") {
+                    if let Some(name) = function.get("name").and_then(|v| v.as_str()) {
+                        entry.name.push_str(name);
+                    }
+                    if let Some(args) = function.get("arguments").and_then(|v| v.as_str()) {
+                        entry.arguments.push_str(args);
+                    }
+                }
+            }
+        }
+
+        events
+    }
+
+    pub(super) fn finish(&mut self) -> Vec<StreamEvent> {
+        if self.emitted {
+            return Vec::new();
+        }
+        self.emitted = true;
+
+        let tool_calls: Vec<ToolCallRequest> = std::mem::take(&mut self.tool_calls)
+            .into_values()
+            .map(|partial| ToolCallRequest {
+                id: partial.id,
+                name: partial.name,
+                arguments: if partial.arguments.trim().is_empty() {
+                    json!({})
+                } else {
+                    serde_json::from_str(&partial.arguments)
+                        .unwrap_or_else(|_| json!({ "raw": partial.arguments }))
+                },
+            })
+            .collect();
+
+        let segments = self.splitter.finish();
+        let mut events = self.apply(segments);
+        events.extend(tool_calls.iter().cloned().map(StreamEvent::ToolCall));
+
+        let content = std::mem::take(&mut self.content);
+        let reasoning = std::mem::take(&mut self.reasoning);
+        let reasoning = reasoning.trim();
+        events.push(StreamEvent::Done(LlmResponse {
+            content: (!content.is_empty()).then_some(content),
+            tool_calls,
+            finish_reason: self
+                .finish_reason
+                .take()
+                .unwrap_or_else(|| "stop".to_string()),
+            usage: std::mem::take(&mut self.usage),
+            model: None,
+            reasoning: (!reasoning.is_empty()).then(|| reasoning.to_string()),
+            thinking_blocks: Vec::new(),
+        }));
+        events
+    }
+
+    fn apply(&mut self, segments: Vec<Segment>) -> Vec<StreamEvent> {
+        segments
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => {
+                    self.content.push_str(&text);
+                    StreamEvent::TextDelta(text)
+                }
+                Segment::Reasoning(text) => {
+                    self.reasoning.push_str(&text);
+                    StreamEvent::ReasoningDelta(text)
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use serde_json::json;
+
+    use ferrumbot_config::ProviderConfig;
+
+    use super::ChunkAssembler;
+    use crate::mock::{MockResponse, MockServer};
+    use crate::stream::SseLines;
+    use crate::{ChatOptions, LlmProvider, OpenAiCompatibleProvider, StreamEvent};
+
+    #[test]
+    fn sse_lines_handle_split_chunks() {
+        let mut lines = SseLines::default();
+        assert!(lines.push(b"data: {\"a\":").is_empty());
+        let out = lines.push(b"1}\n\ndata: [DONE]\n");
+        assert_eq!(out, vec!["{\"a\":1}".to_string(), "[DONE]".to_string()]);
+    }
+
+    #[test]
+    fn assembler_joins_tool_call_fragments() {
+        let mut assembler = ChunkAssembler::default();
+        let chunks = [
+            json!({"choices": [{"delta": {"content": "Hi"}}]}),
+            json!({"choices": [{"delta": {"tool_calls": [
+                {"index": 0, "id": "call_1", "function": {"name": "read_file", "arguments": "{\"pa"}}
+            ]}}]}),
+            json!({"choices": [{"delta": {"tool_calls": [
+                {"index": 0, "function": {"arguments": "th\":\"a.txt\"}"}}
+            ]}, "finish_reason": "tool_calls"}]}),
+            json!({"choices": [], "usage": {"prompt_tokens": 3, "completion_tokens": 4, "total_tokens": 7}}),
+        ];
+
+        let mut deltas = Vec::new();
+        for chunk in chunks {
+            deltas.extend(assembler.handle(&chunk.to_string()));
+        }
+        assert!(matches!(&deltas[..], [StreamEvent::TextDelta(t)] if t == "Hi"));
+
+        let events = assembler.finish();
+        let Some(StreamEvent::Done(resp)) = events.last() else {
+            panic!("expected final response");
+        };
+        assert_eq!(resp.content.as_deref(), Some("Hi"));
+        assert_eq!(resp.finish_reason, "tool_calls");
+        assert_eq!(resp.tool_calls[0].id, "call_1");
+        assert_eq!(resp.tool_calls[0].arguments, json!({"path": "a.txt"}));
+        assert_eq!(resp.usage.get("total_tokens"), Some(&7));
+    }
+
+    #[test]
+    fn assembler_separates_reasoning_from_content() {
+        let mut assembler = ChunkAssembler::default();
+        let chunks = [
+            json!({"choices": [{"delta": {"reasoning_content": "check units"}}]}),
+            json!({"choices": [{"delta": {"content": "<think>then add</th"}}]}),
+            json!({"choices": [{"delta": {"content": "ink>\n42"}, "finish_reason": "stop"}]}),
+        ];
+
+        let mut events = Vec::new();
+        for chunk in chunks {
+            events.extend(assembler.handle(&chunk.to_string()));
+        }
+        events.extend(assembler.finish());
+
+        let text: Vec<&str> = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::TextDelta(t) => Some(t.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, vec!["42"]);
+        let Some(StreamEvent::Done(resp)) = events.last() else {
+            panic!("expected final response");
+        };
+        assert_eq!(resp.content.as_deref(), Some("42"));
+        assert_eq!(resp.reasoning.as_deref(), Some("check unitsthen add"));
+    }
+
+    #[tokio::test]
+    async fn chat_stream_yields_deltas_from_sse_body() {
+        let body = [
+            json!({"choices": [{"delta": {"content": "Hel"}}]}),
+            json!({"choices": [{"delta": {"content": "lo"}, "finish_reason": "stop"}]}),
+        ]
+        .iter()
+        .map(|chunk| format!("data: {chunk}\n\n"))
+        .collect::<String>()
+            + "data: [DONE]\n\n";
+        let server = MockServer::start(vec![MockResponse {
+            status: 200,
+            content_type: "text/event-stream",
+            headers: Vec::new(),
+            body,
+        }])
+        .await;
+
+        let provider = OpenAiCompatibleProvider::from_provider(
+            "gpt-4o".to_string(),
+            &ProviderConfig {
+                api_key: "sk-test".to_string(),
+                ..Default::default()
+            },
+            Some(server.base_url.clone()),
+        )
+        .unwrap();
+        let mut stream = provider
+            .chat_stream(
+                vec![json!({"role": "user", "content": "hi"})],
+                None,
+                None,
+                &ChatOptions::default(),
+            )
+            .await
+            .expect("stream should open");
+
+        let mut deltas = Vec::new();
+        let mut done = None;
+        while let Some(event) = stream.next().await {
+            match event.expect("stream event") {
+                StreamEvent::TextDelta(text) => deltas.push(text),
+                StreamEvent::Done(resp) => done = Some(resp),
+                StreamEvent::ToolCall(_) | StreamEvent::ReasoningDelta(_) => {}
+            }
+        }
+
+        assert_eq!(deltas, vec!["Hel".to_string(), "lo".to_string()]);
+        assert_eq!(done.and_then(|r| r.content).as_deref(), Some("Hello"));
+        assert_eq!(server.requests()[0].json()["stream"], true);
+    }
+}
@@ -0,0 +1,60 @@
+use serde_json::Value;
+
+pub(crate) fn without_cache_markers(messages: Vec<Value>) -> Vec<Value> {
+    messages
+        .into_iter()
+        .map(|mut message| {
+            if let Some(parts) = message.get_mut("content").and_then(Value::as_array_mut) {
+                for part in parts.iter_mut() {
+                    if let Some(part) = part.as_object_mut() {
+                        part.remove("cache_control");
+                    }
+                }
+            }
+            if message["role"] == "system"
+                && let Some(text) = joined_text(&message["content"])
+            {
+                message["content"] = Value::String(text);
+            }
+            message
+        })
+        .collect()
+}
+
+fn joined_text(content: &Value) -> Option<String> {
+    let parts = content.as_array()?;
+    parts
+        .iter()
+        .map(|part| part.get("text").and_then(Value::as_str))
+        .collect::<Option<Vec<_>>>()
+        .map(|texts| texts.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn flattens_marked_system_parts() {
+        let messages = vec![
+            json!({"role": "system", "content": [
+                {"type": "text", "text": "static", "cache_control": {"type": "ephemeral"}},
+                {"type": "text", "text": "## Current Session"}
+            ]}),
+            json!({"role": "user", "content": [
+                {"type": "text", "text": "look", "cache_control": {"type": "ephemeral"}},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,AA=="}}
+            ]}),
+        ];
+
+        let out = without_cache_markers(messages);
+        assert_eq!(out[0]["content"], "static\n\n## Current Session");
+        assert_eq!(
+            out[1]["content"][0],
+            json!({"type": "text", "text": "look"})
+        );
+        assert_eq!(out[1]["content"].as_array().unwrap().len(), 2);
+    }
+}
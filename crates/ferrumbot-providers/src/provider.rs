@@ -1,8 +1,8 @@
-use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
 
-use crate::LlmResponse;
+use crate::stream::collect_stream;
+use crate::{ChatOptions, ChatStream, Embeddings, LlmResponse, ProviderError, ProviderResult};
 
 #[async_trait]
 pub trait LlmProvider: Send + Sync {
@@ -11,9 +11,45 @@ pub trait LlmProvider: Send + Sync {
         messages: Vec<Value>,
         tools: Option<Vec<Value>>,
         model: Option<&str>,
-        max_tokens: Option<usize>,
-        temperature: Option<f32>,
-    ) -> Result<LlmResponse>;
+        options: &ChatOptions,
+    ) -> ProviderResult<LlmResponse> {
+        let stream = self.chat_stream(messages, tools, model, options).await?;
+        collect_stream(stream).await
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<ChatStream>;
+
+    async fn embed(&self, texts: Vec<String>, model: Option<&str>) -> ProviderResult<Embeddings> {
+        let _ = (texts, model);
+        Err(ProviderError::Unsupported("embeddings".to_string()))
+    }
+
+    async fn list_models(&self) -> ProviderResult<Vec<String>> {
+        Err(ProviderError::Unsupported("model listing".to_string()))
+    }
 
     fn get_default_model(&self) -> &str;
 }
+
+pub(crate) fn parse_model_ids(payload: &Value) -> ProviderResult<Vec<String>> {
+    let data = payload
+        .get("data")
+        .or_else(|| payload.get("models"))
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| ProviderError::MalformedResponse("model list has no data".to_string()))?;
+    let mut ids: Vec<String> = data
+        .iter()
+        .filter_map(|m| m.get("id").or_else(|| m.get("name")))
+        .filter_map(|id| id.as_str())
+        .map(ToString::to_string)
+        .collect();
+    ids.sort();
+    ids.dedup();
+    Ok(ids)
+}
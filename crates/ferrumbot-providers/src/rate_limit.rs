@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde_json::Value;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use ferrumbot_config::{RateLimitConfig, ResolvedProvider};
+
+use crate::{
+    ChatOptions, ChatStream, Embeddings, LlmProvider, LlmResponse, ProviderResult, StreamEvent,
+};
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+struct TokenBucket {
+    capacity: f64,
+    per_sec: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    fn new(capacity: u32, period: Duration) -> Self {
+        let capacity = f64::from(capacity.max(1));
+        Self {
+            capacity,
+            per_sec: capacity / period.as_secs_f64(),
+            state: Mutex::new((capacity, Instant::now())),
+        }
+    }
+
+    async fn take(&self, amount: f64) {
+        let amount = amount.min(self.capacity);
+        loop {
+            let wait = {
+                let mut state = self.state.lock().expect("token bucket lock");
+                let (available, refilled) = &mut *state;
+                let now = Instant::now();
+                *available = (*available
+                    + now.duration_since(*refilled).as_secs_f64() * self.per_sec)
+                    .min(self.capacity);
+                *refilled = now;
+                if *available >= amount {
+                    *available -= amount;
+                    return;
+                }
+                Duration::from_secs_f64((amount - *available) / self.per_sec)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    fn adjust(&self, delta: f64) {
+        let mut state = self.state.lock().expect("token bucket lock");
+        state.0 = (state.0 - delta).min(self.capacity);
+    }
+}
+
+pub struct RateLimiter {
+    name: String,
+    config: RateLimitConfig,
+    requests: Option<TokenBucket>,
+    tokens: Option<TokenBucket>,
+    slots: Option<Arc<Semaphore>>,
+}
+
+impl RateLimiter {
+    pub fn new(name: impl Into<String>, config: &RateLimitConfig) -> Self {
+        Self {
+            name: name.into(),
+            config: config.clone(),
+            requests: config
+                .requests_per_minute
+                .map(|rpm| TokenBucket::new(rpm, MINUTE)),
+            tokens: config
+                .tokens_per_minute
+                .map(|tpm| TokenBucket::new(tpm, MINUTE)),
+            slots: config
+                .max_concurrent
+                .map(|max| Arc::new(Semaphore::new(max.max(1)))),
+        }
+    }
+
+    pub fn shared(provider: &ResolvedProvider<'_>) -> Option<Arc<Self>> {
+        type Key = (bool, String, Option<String>);
+        static REGISTRY: OnceLock<Mutex<HashMap<Key, Arc<RateLimiter>>>> = OnceLock::new();
+        let config = &provider.config.rate_limit;
+        if config.is_unlimited() {
+            return None;
+        }
+
+        let key = (
+            provider.custom,
+            provider.name.to_string(),
+            provider.api_base(),
+        );
+        let mut registry = REGISTRY
+            .get_or_init(Default::default)
+            .lock()
+            .expect("rate limiter registry lock");
+        match registry.get(&key) {
+            Some(limiter) if limiter.config == *config => Some(limiter.clone()),
+            _ => {
+                let limiter = Arc::new(Self::new(provider.name, config));
+                registry.insert(key, limiter.clone());
+                Some(limiter)
+            }
+        }
+    }
+
+    async fn acquire(&self, estimated_tokens: usize) -> Option<OwnedSemaphorePermit> {
+        let started = Instant::now();
+        let permit = match &self.slots {
+            Some(slots) => slots.clone().acquire_owned().await.ok(),
+            None => None,
+        };
+        if let Some(bucket) = &self.requests {
+            bucket.take(1.0).await;
+        }
+        if let Some(bucket) = &self.tokens {
+            bucket.take(estimated_tokens as f64).await;
+        }
+
+        let waited = started.elapsed();
+        if waited >= Duration::from_millis(100) {
+            tracing::info!(
+                "{}: request queued {} ms by rate limits",
+                self.name,
+                waited.as_millis()
+            );
+        } else {
+            tracing::debug!("{}: request admitted after {:?}", self.name, waited);
+        }
+        permit
+    }
+
+    fn settle(&self, estimated_tokens: usize, resp: &LlmResponse) {
+        if let (Some(bucket), Some(total)) = (&self.tokens, resp.usage.get("total_tokens")) {
+            bucket.adjust(*total as f64 - estimated_tokens as f64);
+        }
+    }
+}
+
+pub struct RateLimitedProvider {
+    inner: Arc<dyn LlmProvider>,
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitedProvider {
+    pub fn new(inner: Arc<dyn LlmProvider>, limiter: Arc<RateLimiter>) -> Self {
+        Self { inner, limiter }
+    }
+}
+
+fn estimate_tokens(messages: &[Value], tools: Option<&Vec<Value>>, options: &ChatOptions) -> usize {
+    let chars: usize = messages
+        .iter()
+        .chain(tools.into_iter().flatten())
+        .map(|value| value.to_string().len())
+        .sum();
+    chars / 4 + options.max_tokens.unwrap_or(0)
+}
+
+#[async_trait]
+impl LlmProvider for RateLimitedProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<LlmResponse> {
+        let estimate = estimate_tokens(&messages, tools.as_ref(), options);
+        let _permit = self.limiter.acquire(estimate).await;
+        let resp = self.inner.chat(messages, tools, model, options).await?;
+        self.limiter.settle(estimate, &resp);
+        Ok(resp)
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<ChatStream> {
+        let estimate = estimate_tokens(&messages, tools.as_ref(), options);
+        let permit = self.limiter.acquire(estimate).await;
+        let stream = self
+            .inner
+            .chat_stream(messages, tools, model, options)
+            .await?;
+
+        let limiter = self.limiter.clone();
+        Ok(stream
+            .map(move |event| {
+                let _held = &permit;
+                if let Ok(StreamEvent::Done(resp)) = &event {
+                    limiter.settle(estimate, resp);
+                }
+                event
+            })
+            .boxed())
+    }
+
+    async fn embed(&self, texts: Vec<String>, model: Option<&str>) -> ProviderResult<Embeddings> {
+        let estimate = texts.iter().map(|text| text.len() / 4).sum();
+        let _permit = self.limiter.acquire(estimate).await;
+        self.inner.embed(texts, model).await
+    }
+
+    async fn list_models(&self) -> ProviderResult<Vec<String>> {
+        self.inner.list_models().await
+    }
+
+    fn get_default_model(&self) -> &str {
+        self.inner.get_default_model()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumbot_config::ProviderConfig;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn bucket_waits_for_refill() {
+        tokio::time::pause();
+        let bucket = TokenBucket::new(2, Duration::from_millis(200));
+        let started = Instant::now();
+        bucket.take(1.0).await;
+        bucket.take(1.0).await;
+        assert_eq!(started.elapsed(), Duration::ZERO);
+
+        bucket.take(1.0).await;
+        let waited = started.elapsed();
+        assert!(
+            waited >= Duration::from_millis(100) && waited < Duration::from_millis(110),
+            "{waited:?}"
+        );
+    }
+
+    #[test]
+    fn registry_shares_limiters_per_provider() {
+        let limited = |rate_limit: RateLimitConfig, api_base: &str| ProviderConfig {
+            api_base: Some(api_base.to_string()),
+            rate_limit,
+            ..Default::default()
+        };
+        let resolved = |name, config, custom| ResolvedProvider {
+            name,
+            config,
+            custom,
+        };
+        let config = RateLimitConfig {
+            requests_per_minute: Some(60),
+            ..Default::default()
+        };
+        let first = limited(config.clone(), "http://gpu-1:8000/v1");
+        let a = RateLimiter::shared(&resolved("test-shared", &first, true)).unwrap();
+        let b = RateLimiter::shared(&resolved("test-shared", &first, true)).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+
+        let builtin = RateLimiter::shared(&resolved("test-shared", &first, false)).unwrap();
+        assert!(!Arc::ptr_eq(&a, &builtin));
+        let second = limited(config.clone(), "http://gpu-2:8000/v1");
+        let other_host = RateLimiter::shared(&resolved("test-shared", &second, true)).unwrap();
+        assert!(!Arc::ptr_eq(&a, &other_host));
+
+        let changed = limited(
+            RateLimitConfig {
+                max_concurrent: Some(2),
+                ..config
+            },
+            "http://gpu-1:8000/v1",
+        );
+        let c = RateLimiter::shared(&resolved("test-shared", &changed, true)).unwrap();
+        assert!(!Arc::ptr_eq(&a, &c));
+        let unlimited = limited(RateLimitConfig::default(), "http://gpu-1:8000/v1");
+        assert!(RateLimiter::shared(&resolved("test-shared", &unlimited, true)).is_none());
+    }
+}
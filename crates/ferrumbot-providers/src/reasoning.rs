@@ -0,0 +1,136 @@
+use crate::LlmResponse;
+
+const OPEN_TAG: &str = "<think>";
+const CLOSE_TAG: &str = "</think>";
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Segment {
+    Text(String),
+    Reasoning(String),
+}
+
+#[derive(Default)]
+pub(crate) struct ThinkSplitter {
+    buf: String,
+    in_think: bool,
+    trim_text: bool,
+}
+
+impl ThinkSplitter {
+    pub(crate) fn push(&mut self, text: &str) -> Vec<Segment> {
+        self.buf.push_str(text);
+        let mut out = Vec::new();
+        loop {
+            let tag = if self.in_think { CLOSE_TAG } else { OPEN_TAG };
+            if let Some(pos) = self.buf.find(tag) {
+                let before: String = self.buf.drain(..pos).collect();
+                self.buf.drain(..tag.len());
+                self.emit(before, &mut out);
+                self.in_think = !self.in_think;
+                self.trim_text = !self.in_think;
+                continue;
+            }
+
+            let keep = partial_tag_len(&self.buf, tag);
+            let ready: String = self.buf.drain(..self.buf.len() - keep).collect();
+            self.emit(ready, &mut out);
+            return out;
+        }
+    }
+
+    pub(crate) fn finish(&mut self) -> Vec<Segment> {
+        let rest = std::mem::take(&mut self.buf);
+        let mut out = Vec::new();
+        self.emit(rest, &mut out);
+        out
+    }
+
+    fn emit(&mut self, mut text: String, out: &mut Vec<Segment>) {
+        if self.in_think {
+            if !text.is_empty() {
+                out.push(Segment::Reasoning(text));
+            }
+            return;
+        }
+        if self.trim_text {
+            text = text.trim_start().to_string();
+            self.trim_text = text.is_empty();
+        }
+        if !text.is_empty() {
+            out.push(Segment::Text(text));
+        }
+    }
+}
+
+fn partial_tag_len(buf: &str, tag: &str) -> usize {
+    (1..tag.len())
+        .rev()
+        .find(|&n| buf.ends_with(&tag[..n]))
+        .unwrap_or(0)
+}
+
+pub(crate) fn split_think(resp: &mut LlmResponse) {
+    let Some(content) = resp.content.take() else {
+        return;
+    };
+
+    let mut splitter = ThinkSplitter::default();
+    let mut segments = splitter.push(&content);
+    segments.extend(splitter.finish());
+
+    let mut text = String::new();
+    let mut reasoning = resp.reasoning.take().unwrap_or_default();
+    for segment in segments {
+        match segment {
+            Segment::Text(t) => text.push_str(&t),
+            Segment::Reasoning(r) => reasoning.push_str(&r),
+        }
+    }
+    let reasoning = reasoning.trim();
+    resp.reasoning = (!reasoning.is_empty()).then(|| reasoning.to_string());
+    resp.content = (!text.is_empty()).then_some(text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splitter_handles_tags_across_chunks() {
+        let mut splitter = ThinkSplitter::default();
+        let mut segments = Vec::new();
+        for chunk in [
+            "<thi",
+            "nk>weigh",
+            " options</th",
+            "ink>\n\nAnswer",
+            " is 4.",
+        ] {
+            segments.extend(splitter.push(chunk));
+        }
+        segments.extend(splitter.finish());
+
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Reasoning("weigh".to_string()),
+                Segment::Reasoning(" options".to_string()),
+                Segment::Text("Answer".to_string()),
+                Segment::Text(" is 4.".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_think_moves_inline_reasoning_out_of_content() {
+        let mut resp = LlmResponse::text("<think>2 + 2</think>\nIt is 4. a < b");
+        split_think(&mut resp);
+        assert_eq!(resp.content.as_deref(), Some("It is 4. a < b"));
+        assert_eq!(resp.reasoning.as_deref(), Some("2 + 2"));
+
+        let mut plain = LlmResponse::text("no tags here");
+        split_think(&mut plain);
+        assert_eq!(plain.content.as_deref(), Some("no tags here"));
+        assert_eq!(plain.reasoning, None);
+    }
+}
@@ -0,0 +1,43 @@
+use anyhow::Result;
+use ferrumbot_config::{ModelRewrite, ProviderConfig};
+use reqwest::Client;
+
+use super::OpenAiResponsesProvider;
+use crate::openai::DEFAULT_API_BASE;
+
+impl OpenAiResponsesProvider {
+    pub fn from_provider(
+        model: String,
+        provider: &ProviderConfig,
+        api_base: Option<String>,
+    ) -> Result<Self> {
+        Ok(Self {
+            client: provider.http.client()?,
+            api_key: provider.api_key.clone(),
+            api_base: api_base.unwrap_or_else(|| DEFAULT_API_BASE.to_string()),
+            default_model: model,
+            extra_headers: provider.extra_headers.clone().unwrap_or_default(),
+            rewrite: ModelRewrite::default(),
+            key_optional: false,
+        })
+    }
+
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn with_model_rewrite(mut self, rewrite: ModelRewrite) -> Self {
+        self.rewrite = rewrite;
+        self
+    }
+
+    pub fn allow_missing_key(mut self) -> Self {
+        self.key_optional = true;
+        self
+    }
+
+    pub(super) fn wire_model(&self, model: &str) -> String {
+        self.rewrite.apply(model)
+    }
+}
@@ -0,0 +1,137 @@
+use async_trait::async_trait;
+use reqwest::{Method, RequestBuilder, Response};
+use serde_json::{Value, json};
+
+use crate::provider::parse_model_ids;
+use crate::{
+    ChatOptions, ChatStream, LlmProvider, LlmResponse, ProviderError, ProviderResult, tag_model,
+};
+
+use super::OpenAiResponsesProvider;
+use super::convert::{to_responses_input, to_responses_tools};
+use super::parse::parse_responses_payload;
+use super::stream::sse_stream;
+
+#[async_trait]
+impl LlmProvider for OpenAiResponsesProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<LlmResponse> {
+        let model = model.unwrap_or(&self.default_model).to_string();
+        let body = self.build_body(messages, tools, &model, options);
+        tracing::debug!("Request body: {:?}", body);
+
+        let req = self.request(Method::POST, "responses")?.json(&body);
+        let resp = Self::execute(req).await?;
+        let payload: Value = resp.json().await?;
+        let mut resp = parse_responses_payload(payload)?;
+        resp.model = Some(model);
+        Ok(resp)
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<ChatStream> {
+        let model = model.unwrap_or(&self.default_model).to_string();
+        let mut body = self.build_body(messages, tools, &model, options);
+        body["stream"] = Value::Bool(true);
+
+        let req = self.request(Method::POST, "responses")?.json(&body);
+        let resp = Self::execute(req).await?;
+        Ok(tag_model(sse_stream(resp), model))
+    }
+
+    async fn list_models(&self) -> ProviderResult<Vec<String>> {
+        let req = self.request(Method::GET, "models")?;
+        let resp = Self::execute(req).await?;
+        let payload: Value = resp.json().await?;
+        parse_model_ids(&payload)
+    }
+
+    fn get_default_model(&self) -> &str {
+        &self.default_model
+    }
+}
+
+impl OpenAiResponsesProvider {
+    fn build_body(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: &str,
+        options: &ChatOptions,
+    ) -> Value {
+        let (instructions, input) = to_responses_input(messages);
+        let mut body = json!({
+            "model": self.wire_model(model),
+            "input": input,
+            "store": false,
+        });
+        if let Some(instructions) = instructions {
+            body["instructions"] = json!(instructions);
+        }
+        if let Some(max_tokens) = options.max_tokens {
+            body["max_output_tokens"] = json!(max_tokens);
+        }
+        if let Some(temperature) = options.temperature {
+            body["temperature"] = json!(temperature);
+        }
+        if let Some(top_p) = options.top_p {
+            body["top_p"] = json!(top_p);
+        }
+        if let Some(effort) = &options.reasoning_effort {
+            body["reasoning"] = json!({"effort": effort});
+        }
+        if let Some(format) = &options.response_format {
+            body["text"] = json!({
+                "format": {"type": "json_schema", "name": format.name, "schema": format.schema},
+            });
+        }
+        if !options.stop.is_empty() {
+            tracing::debug!("stop sequences are not supported by the responses api, ignoring");
+        }
+
+        if let Some(tools) = tools.filter(|t| !t.is_empty()) {
+            body["tools"] = Value::Array(to_responses_tools(tools));
+            body["tool_choice"] = json!("auto");
+            if let Some(parallel) = options.parallel_tool_calls {
+                body["parallel_tool_calls"] = Value::Bool(parallel);
+            }
+        }
+        body
+    }
+
+    fn request(&self, method: Method, endpoint: &str) -> ProviderResult<RequestBuilder> {
+        if self.api_key.is_empty() && !self.key_optional {
+            return Err(ProviderError::Auth("API key not configured".to_string()));
+        }
+
+        let url = format!("{}/{endpoint}", self.api_base.trim_end_matches('/'));
+        tracing::debug!("Request URL: {}", url);
+        let mut req = self.client.request(method, url);
+        if !self.api_key.is_empty() {
+            req = req.bearer_auth(&self.api_key);
+        }
+        for (k, v) in &self.extra_headers {
+            req = req.header(k, v);
+        }
+        Ok(req)
+    }
+
+    async fn execute(req: RequestBuilder) -> ProviderResult<Response> {
+        let resp = req.send().await?;
+        tracing::debug!("Response status: {:?}", resp.status());
+        if !resp.status().is_success() {
+            return Err(ProviderError::from_response(resp).await);
+        }
+        Ok(resp)
+    }
+}
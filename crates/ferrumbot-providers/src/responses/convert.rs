@@ -0,0 +1,118 @@
+use serde_json::{Value, json};
+
+use crate::prompt_cache::without_cache_markers;
+
+pub(super) fn to_responses_input(messages: Vec<Value>) -> (Option<String>, Vec<Value>) {
+    let mut instructions = Vec::new();
+    let mut input = Vec::new();
+
+    for message in without_cache_markers(messages) {
+        match message
+            .get("role")
+            .and_then(Value::as_str)
+            .unwrap_or("user")
+        {
+            "system" => instructions.extend(text_content(&message)),
+            "assistant" => {
+                if let Some(text) = text_content(&message) {
+                    input.push(json!({"role": "assistant", "content": text}));
+                }
+                for call in message
+                    .get("tool_calls")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    let arguments = match call.pointer("/function/arguments") {
+                        Some(Value::String(raw)) => raw.clone(),
+                        Some(other) => other.to_string(),
+                        None => "{}".to_string(),
+                    };
+                    input.push(json!({
+                        "type": "function_call",
+                        "call_id": call.get("id").cloned().unwrap_or(Value::Null),
+                        "name": call.pointer("/function/name").cloned().unwrap_or(Value::Null),
+                        "arguments": arguments,
+                    }));
+                }
+            }
+            "tool" => input.push(json!({
+                "type": "function_call_output",
+                "call_id": message.get("tool_call_id").cloned().unwrap_or(Value::Null),
+                "output": text_content(&message).unwrap_or_default(),
+            })),
+            _ => match message.get("content") {
+                Some(Value::Array(parts)) => {
+                    let content: Vec<Value> = parts.iter().filter_map(input_part).collect();
+                    if !content.is_empty() {
+                        input.push(json!({"role": "user", "content": content}));
+                    }
+                }
+                _ => {
+                    if let Some(text) = text_content(&message) {
+                        input.push(json!({"role": "user", "content": text}));
+                    }
+                }
+            },
+        }
+    }
+
+    let instructions = (!instructions.is_empty()).then(|| instructions.join("\n\n"));
+    (instructions, input)
+}
+
+pub(super) fn to_responses_tools(tools: Vec<Value>) -> Vec<Value> {
+    tools
+        .into_iter()
+        .filter_map(|tool| {
+            let function = tool.get("function")?;
+            Some(json!({
+                "type": "function",
+                "name": function.get("name").cloned().unwrap_or(Value::Null),
+                "description": function.get("description").cloned().unwrap_or(Value::Null),
+                "parameters": function
+                    .get("parameters")
+                    .cloned()
+                    .unwrap_or_else(|| json!({"type": "object"})),
+                "strict": false,
+            }))
+        })
+        .collect()
+}
+
+fn text_content(message: &Value) -> Option<String> {
+    match message.get("content") {
+        Some(Value::String(text)) if !text.is_empty() => Some(text.clone()),
+        Some(Value::Array(parts)) => {
+            let text = parts
+                .iter()
+                .filter_map(|part| part.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n");
+            (!text.is_empty()).then_some(text)
+        }
+        _ => None,
+    }
+}
+
+fn input_part(part: &Value) -> Option<Value> {
+    match part.get("type").and_then(Value::as_str)? {
+        "text" => {
+            let text = part.get("text").and_then(Value::as_str)?;
+            (!text.is_empty()).then(|| json!({"type": "input_text", "text": text}))
+        }
+        "image_url" => {
+            let url = part.pointer("/image_url/url").and_then(Value::as_str)?;
+            Some(json!({"type": "input_image", "image_url": url}))
+        }
+        "file" => {
+            let data = part.pointer("/file/file_data").and_then(Value::as_str)?;
+            let filename = part
+                .pointer("/file/filename")
+                .and_then(Value::as_str)
+                .unwrap_or("attachment");
+            Some(json!({"type": "input_file", "file_data": data, "filename": filename}))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,23 @@
+use std::collections::BTreeMap;
+
+use ferrumbot_config::ModelRewrite;
+use reqwest::Client;
+
+pub struct OpenAiResponsesProvider {
+    pub(super) client: Client,
+    pub(super) api_key: String,
+    pub(super) api_base: String,
+    pub(super) default_model: String,
+    pub(super) extra_headers: BTreeMap<String, String>,
+    pub(super) rewrite: ModelRewrite,
+    pub(super) key_optional: bool,
+}
+
+mod build;
+mod call;
+mod convert;
+mod parse;
+mod stream;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Value, json};
+
+use crate::reasoning::split_think;
+use crate::{LlmResponse, ProviderError, ProviderResult, ToolCallRequest};
+
+pub(super) fn parse_responses_payload(payload: Value) -> ProviderResult<LlmResponse> {
+    tracing::debug!("parse_responses_payload payload: {:?}", payload);
+
+    if payload.get("status").and_then(Value::as_str) == Some("failed") {
+        let message = payload
+            .pointer("/error/message")
+            .and_then(Value::as_str)
+            .unwrap_or("response failed");
+        return Err(ProviderError::MalformedResponse(message.to_string()));
+    }
+    let Some(items) = payload.get("output").and_then(Value::as_array) else {
+        return Err(ProviderError::MalformedResponse(format!(
+            "missing output in response: {payload}"
+        )));
+    };
+
+    let mut text = Vec::new();
+    let mut reasoning = Vec::new();
+    let mut tool_calls = Vec::new();
+    for item in items {
+        match item.get("type").and_then(Value::as_str) {
+            Some("message") => {
+                for part in item
+                    .get("content")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    let key = match part.get("type").and_then(Value::as_str) {
+                        Some("output_text") => "text",
+                        Some("refusal") => "refusal",
+                        _ => continue,
+                    };
+                    if let Some(t) = part.get(key).and_then(Value::as_str) {
+                        text.push(t.to_string());
+                    }
+                }
+            }
+            Some("reasoning") => {
+                for part in item
+                    .get("summary")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    if let Some(t) = part.get("text").and_then(Value::as_str) {
+                        reasoning.push(t.to_string());
+                    }
+                }
+            }
+            Some("function_call") => {
+                let raw = item
+                    .get("arguments")
+                    .and_then(Value::as_str)
+                    .unwrap_or("{}");
+                tool_calls.push(ToolCallRequest {
+                    id: item
+                        .get("call_id")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    name: item
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    arguments: serde_json::from_str(raw).unwrap_or_else(|_| json!({ "raw": raw })),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let mut usage = BTreeMap::new();
+    if let Some(obj) = payload.get("usage") {
+        for (from, to) in [
+            ("input_tokens", "prompt_tokens"),
+            ("output_tokens", "completion_tokens"),
+            ("total_tokens", "total_tokens"),
+        ] {
+            if let Some(v) = obj.get(from).and_then(Value::as_i64) {
+                usage.insert(to.to_string(), v);
+            }
+        }
+        if let Some(cached) = obj
+            .pointer("/input_tokens_details/cached_tokens")
+            .and_then(Value::as_i64)
+            .filter(|v| *v > 0)
+        {
+            usage.insert("cached_tokens".to_string(), cached);
+        }
+    }
+
+    let finish_reason = if !tool_calls.is_empty() {
+        "tool_calls"
+    } else {
+        match payload
+            .pointer("/incomplete_details/reason")
+            .and_then(Value::as_str)
+        {
+            Some("max_output_tokens") => "length",
+            Some("content_filter") => "content_filter",
+            _ => "stop",
+        }
+    };
+
+    let mut resp = LlmResponse {
+        content: (!text.is_empty()).then(|| text.join("")),
+        tool_calls,
+        finish_reason: finish_reason.to_string(),
+        usage,
+        model: None,
+        reasoning: (!reasoning.is_empty()).then(|| reasoning.join("\n")),
+        thinking_blocks: Vec::new(),
+    };
+    split_think(&mut resp);
+    Ok(resp)
+}
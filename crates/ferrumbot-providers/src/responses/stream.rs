@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+
+use futures::StreamExt;
+use reqwest::Response;
+use serde_json::Value;
+
+use crate::stream::SseLines;
+use crate::{ChatStream, ProviderError, ProviderResult, StreamEvent};
+
+use super::parse::parse_responses_payload;
+
+pub(super) fn sse_stream(resp: Response) -> ChatStream {
+    let state = SseState {
+        resp,
+        lines: SseLines::default(),
+        assembler: EventAssembler::default(),
+        pending: VecDeque::new(),
+    };
+
+    futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                return Some((event, state));
+            }
+            if state.assembler.done {
+                return None;
+            }
+
+            match state.resp.chunk().await {
+                Ok(Some(bytes)) => {
+                    for data in state.lines.push(&bytes) {
+                        state.pending.extend(state.assembler.handle(&data));
+                    }
+                }
+                Ok(None) => state.pending.extend(state.assembler.finish()),
+                Err(err) => {
+                    state.assembler.done = true;
+                    return Some((Err(ProviderError::from(err)), state));
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
+struct SseState {
+    resp: Response,
+    lines: SseLines,
+    assembler: EventAssembler,
+    pending: VecDeque<ProviderResult<StreamEvent>>,
+}
+
+#[derive(Default)]
+pub(super) struct EventAssembler {
+    pub(super) done: bool,
+}
+
+impl EventAssembler {
+    pub(super) fn handle(&mut self, data: &str) -> Vec<ProviderResult<StreamEvent>> {
+        if self.done {
+            return Vec::new();
+        }
+        let Ok(event) = serde_json::from_str::<Value>(data) else {
+            tracing::debug!("skipping malformed stream event: {data}");
+            return Vec::new();
+        };
+        let delta = || {
+            event
+                .get("delta")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        match event.get("type").and_then(Value::as_str) {
+            Some("response.output_text.delta") => vec![Ok(StreamEvent::TextDelta(delta()))],
+            Some("response.reasoning_summary_text.delta" | "response.reasoning_text.delta") => {
+                vec![Ok(StreamEvent::ReasoningDelta(delta()))]
+            }
+            Some("response.completed" | "response.incomplete" | "response.failed") => {
+                self.done = true;
+                let payload = event.get("response").cloned().unwrap_or_default();
+                match parse_responses_payload(payload) {
+                    Ok(resp) => {
+                        let mut events: Vec<_> = resp
+                            .tool_calls
+                            .iter()
+                            .cloned()
+                            .map(|call| Ok(StreamEvent::ToolCall(call)))
+                            .collect();
+                        events.push(Ok(StreamEvent::Done(resp)));
+                        events
+                    }
+                    Err(err) => vec![Err(err)],
+                }
+            }
+            Some("error") => {
+                self.done = true;
+                let message = event
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("response failed");
+                vec![Err(ProviderError::MalformedResponse(message.to_string()))]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub(super) fn finish(&mut self) -> Vec<ProviderResult<StreamEvent>> {
+        if self.done {
+            return Vec::new();
+        }
+        self.done = true;
+        vec![Err(ProviderError::MalformedResponse(
+            "stream ended before the response completed".to_string(),
+        ))]
+    }
+}
@@ -0,0 +1,217 @@
+use futures::StreamExt;
+use serde_json::json;
+
+use ferrumbot_config::ProviderConfig;
+
+use crate::mock::{MockResponse, MockServer};
+use crate::{ChatOptions, LlmProvider, StreamEvent};
+
+use super::OpenAiResponsesProvider;
+
+fn provider(base_url: &str) -> OpenAiResponsesProvider {
+    let config = ProviderConfig {
+        api_key: "sk-test".to_string(),
+        ..Default::default()
+    };
+    OpenAiResponsesProvider::from_provider(
+        "gpt-5-codex".to_string(),
+        &config,
+        Some(format!("{base_url}/v1")),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn chat_sends_instructions_and_parses_text_and_usage() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        json!({
+            "id": "resp_1",
+            "status": "completed",
+            "output": [
+                {"type": "reasoning", "summary": [{"type": "summary_text", "text": "Greeting."}]},
+                {
+                    "type": "message",
+                    "role": "assistant",
+                    "content": [{"type": "output_text", "text": "Hello there.", "annotations": []}]
+                }
+            ],
+            "usage": {
+                "input_tokens": 120,
+                "input_tokens_details": {"cached_tokens": 100},
+                "output_tokens": 8,
+                "total_tokens": 128
+            }
+        }),
+    )])
+    .await;
+
+    let messages = vec![
+        json!({"role": "system", "content": [
+            {"type": "text", "text": "You are ferrum-bot.", "cache_control": {"type": "ephemeral"}},
+            {"type": "text", "text": "Current Time: noon"}
+        ]}),
+        json!({"role": "user", "content": "hi"}),
+    ];
+    let resp = provider(&server.base_url)
+        .chat(
+            messages,
+            None,
+            None,
+            &ChatOptions {
+                max_tokens: Some(256),
+                reasoning_effort: Some("low".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("chat should succeed");
+
+    assert_eq!(resp.content.as_deref(), Some("Hello there."));
+    assert_eq!(resp.reasoning.as_deref(), Some("Greeting."));
+    assert_eq!(resp.finish_reason, "stop");
+    assert_eq!(resp.usage.get("prompt_tokens"), Some(&120));
+    assert_eq!(resp.usage.get("completion_tokens"), Some(&8));
+    assert_eq!(resp.usage.get("cached_tokens"), Some(&100));
+    assert_eq!(resp.model.as_deref(), Some("gpt-5-codex"));
+
+    let request = &server.requests()[0];
+    assert_eq!(request.path, "/v1/responses");
+    assert_eq!(request.header("authorization"), Some("Bearer sk-test"));
+    let body = request.json();
+    assert_eq!(
+        body["instructions"],
+        "You are ferrum-bot.\n\nCurrent Time: noon"
+    );
+    assert_eq!(body["input"], json!([{"role": "user", "content": "hi"}]));
+    assert_eq!(body["max_output_tokens"], 256);
+    assert_eq!(body["reasoning"]["effort"], "low");
+    assert_eq!(body["store"], false);
+    assert!(body.get("temperature").is_none());
+}
+
+#[tokio::test]
+async fn chat_round_trips_function_calls() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        json!({
+            "status": "completed",
+            "output": [{
+                "type": "function_call",
+                "id": "fc_2",
+                "call_id": "call_2",
+                "name": "list_dir",
+                "arguments": "{\"path\":\".\"}"
+            }],
+            "usage": {"input_tokens": 40, "output_tokens": 12, "total_tokens": 52}
+        }),
+    )])
+    .await;
+
+    let messages = vec![
+        json!({"role": "user", "content": "read the file"}),
+        json!({
+            "role": "assistant",
+            "content": "",
+            "tool_calls": [{
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "read_file", "arguments": "{\"path\":\"a.txt\"}"}
+            }]
+        }),
+        json!({"role": "tool", "tool_call_id": "call_1", "name": "read_file", "content": "hello"}),
+    ];
+    let tools = vec![json!({
+        "type": "function",
+        "function": {"name": "list_dir", "description": "List", "parameters": {"type": "object"}}
+    })];
+
+    let resp = provider(&server.base_url)
+        .chat(messages, Some(tools), None, &ChatOptions::default())
+        .await
+        .expect("chat should succeed");
+
+    assert_eq!(resp.content, None);
+    assert_eq!(resp.finish_reason, "tool_calls");
+    assert_eq!(resp.tool_calls.len(), 1);
+    assert_eq!(resp.tool_calls[0].id, "call_2");
+    assert_eq!(resp.tool_calls[0].name, "list_dir");
+    assert_eq!(resp.tool_calls[0].arguments, json!({"path": "."}));
+    assert_eq!(resp.usage.get("total_tokens"), Some(&52));
+
+    let body = server.requests()[0].json();
+    assert_eq!(
+        body["input"],
+        json!([
+            {"role": "user", "content": "read the file"},
+            {
+                "type": "function_call",
+                "call_id": "call_1",
+                "name": "read_file",
+                "arguments": "{\"path\":\"a.txt\"}"
+            },
+            {"type": "function_call_output", "call_id": "call_1", "output": "hello"}
+        ])
+    );
+    assert_eq!(body["tools"][0]["name"], "list_dir");
+    assert_eq!(body["tools"][0]["type"], "function");
+    assert_eq!(body["tool_choice"], "auto");
+}
+
+#[tokio::test]
+async fn chat_stream_reads_deltas_and_the_completed_response() {
+    let body = [
+        json!({"type": "response.created", "response": {"id": "resp_1", "status": "in_progress"}}),
+        json!({"type": "response.output_text.delta", "delta": "Reading"}),
+        json!({"type": "response.output_text.delta", "delta": " it."}),
+        json!({"type": "response.completed", "response": {
+            "id": "resp_1",
+            "status": "completed",
+            "output": [
+                {"type": "message", "content": [{"type": "output_text", "text": "Reading it."}]},
+                {"type": "function_call", "call_id": "call_1", "name": "read_file", "arguments": "{\"path\":\"a.txt\"}"}
+            ],
+            "usage": {"input_tokens": 12, "output_tokens": 6, "total_tokens": 18}
+        }}),
+    ]
+    .iter()
+    .map(|event| format!("event: {}\ndata: {event}\n\n", event["type"].as_str().unwrap()))
+    .collect::<String>();
+    let server = MockServer::start(vec![MockResponse {
+        status: 200,
+        content_type: "text/event-stream",
+        headers: Vec::new(),
+        body,
+    }])
+    .await;
+
+    let mut stream = provider(&server.base_url)
+        .chat_stream(
+            vec![json!({"role": "user", "content": "read a.txt"})],
+            None,
+            None,
+            &ChatOptions::default(),
+        )
+        .await
+        .expect("stream should open");
+
+    let mut deltas = Vec::new();
+    let mut calls = Vec::new();
+    let mut done = None;
+    while let Some(event) = stream.next().await {
+        match event.expect("stream event") {
+            StreamEvent::TextDelta(text) => deltas.push(text),
+            StreamEvent::ToolCall(call) => calls.push(call),
+            StreamEvent::Done(resp) => done = Some(resp),
+            StreamEvent::ReasoningDelta(_) => {}
+        }
+    }
+
+    assert_eq!(deltas, ["Reading", " it."]);
+    assert_eq!(calls[0].arguments, json!({"path": "a.txt"}));
+    let done = done.expect("final response");
+    assert_eq!(done.finish_reason, "tool_calls");
+    assert_eq!(done.usage.get("total_tokens"), Some(&18));
+    assert_eq!(done.model.as_deref(), Some("gpt-5-codex"));
+    assert_eq!(server.requests()[0].json()["stream"], true);
+}
@@ -0,0 +1,160 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use ferrumbot_config::RetryConfig;
+
+use crate::{
+    ChatOptions, ChatStream, Embeddings, LlmProvider, LlmResponse, ProviderError, ProviderResult,
+};
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+    pub jitter: f64,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &RetryConfig) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            initial_backoff: Duration::from_millis(config.initial_backoff_ms),
+            max_backoff: Duration::from_millis(config.max_backoff_ms),
+            multiplier: config.multiplier.max(1.0),
+            jitter: config.jitter.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn backoff(&self, attempt: u32, err: &ProviderError) -> Duration {
+        if let Some(retry_after) = err.retry_after() {
+            return retry_after.min(self.max_backoff);
+        }
+
+        let base = self.initial_backoff.as_secs_f64() * self.multiplier.powi(attempt as i32);
+        let base = base.min(self.max_backoff.as_secs_f64());
+        let spread = base * self.jitter;
+        let jittered = base - spread + fastrand::f64() * spread * 2.0;
+        Duration::from_secs_f64(jittered.clamp(0.0, self.max_backoff.as_secs_f64()))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::from_config(&RetryConfig::default())
+    }
+}
+
+pub struct RetryingProvider {
+    inner: Arc<dyn LlmProvider>,
+    policy: RetryPolicy,
+}
+
+impl RetryingProvider {
+    pub fn new(inner: Arc<dyn LlmProvider>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    async fn retry<T, F, Fut>(&self, mut call: F) -> ProviderResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = ProviderResult<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let err = match call().await {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+            if !err.is_retryable() || attempt >= self.policy.max_retries {
+                return Err(err);
+            }
+            let delay = self.policy.backoff(attempt, &err);
+            tracing::warn!(
+                "provider call failed ({err}), retrying in {}ms (attempt {}/{})",
+                delay.as_millis(),
+                attempt + 1,
+                self.policy.max_retries
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for RetryingProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<LlmResponse> {
+        self.retry(|| {
+            self.inner
+                .chat(messages.clone(), tools.clone(), model, options)
+        })
+        .await
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<ChatStream> {
+        self.retry(|| {
+            self.inner
+                .chat_stream(messages.clone(), tools.clone(), model, options)
+        })
+        .await
+    }
+
+    async fn embed(&self, texts: Vec<String>, model: Option<&str>) -> ProviderResult<Embeddings> {
+        self.retry(|| self.inner.embed(texts.clone(), model)).await
+    }
+
+    async fn list_models(&self) -> ProviderResult<Vec<String>> {
+        self.retry(|| self.inner.list_models()).await
+    }
+
+    fn get_default_model(&self) -> &str {
+        self.inner.get_default_model()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RetryPolicy;
+    use crate::ProviderError;
+
+    #[test]
+    fn backoff_grows_and_respects_retry_after() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            multiplier: 2.0,
+            jitter: 0.0,
+        };
+        let err = ProviderError::Network("reset".to_string());
+        assert_eq!(policy.backoff(0, &err), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2, &err), Duration::from_millis(400));
+        assert_eq!(policy.backoff(8, &err), Duration::from_secs(1));
+
+        let limited = ProviderError::RateLimited {
+            retry_after: Some(Duration::from_millis(250)),
+            message: "slow down".to_string(),
+        };
+        assert_eq!(policy.backoff(0, &limited), Duration::from_millis(250));
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::{
+    ChatOptions, ChatStream, LlmProvider, LlmResponse, ProviderError, ProviderResult,
+    response_stream,
+};
+
+pub struct ScriptedProvider {
+    default_model: String,
+    responses: Mutex<VecDeque<ProviderResult<LlmResponse>>>,
+    requests: Mutex<Vec<Vec<Value>>>,
+    options: Mutex<Vec<ChatOptions>>,
+    tools: Mutex<Vec<Option<Vec<Value>>>>,
+}
+
+impl ScriptedProvider {
+    pub fn new(responses: Vec<LlmResponse>) -> Self {
+        Self::with_results(responses.into_iter().map(Ok).collect())
+    }
+
+    pub fn with_results(results: Vec<ProviderResult<LlmResponse>>) -> Self {
+        Self {
+            default_model: "scripted".to_string(),
+            responses: Mutex::new(results.into()),
+            requests: Mutex::new(Vec::new()),
+            options: Mutex::new(Vec::new()),
+            tools: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn requests(&self) -> Vec<Vec<Value>> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    pub fn options(&self) -> Vec<ChatOptions> {
+        self.options
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    pub fn tools(&self) -> Vec<Option<Vec<Value>>> {
+        self.tools
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.responses
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .len()
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ScriptedProvider {
+    async fn chat_stream(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<ChatStream> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(messages);
+        self.options
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(options.clone());
+        self.tools
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(tools);
+        let next = self
+            .responses
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .pop_front();
+        let mut response = next.unwrap_or_else(|| {
+            Err(ProviderError::InvalidRequest {
+                status: 0,
+                message: "scripted provider has no responses left".to_string(),
+            })
+        })?;
+        response
+            .model
+            .get_or_insert_with(|| model.unwrap_or(&self.default_model).to_string());
+        Ok(response_stream(response))
+    }
+
+    fn get_default_model(&self) -> &str {
+        &self.default_model
+    }
+}
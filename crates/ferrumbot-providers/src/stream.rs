@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use futures::StreamExt;
+
+use crate::{ChatStream, LlmResponse, ProviderResult, StreamEvent};
+
+#[derive(Default)]
+pub struct StreamAccumulator {
+    content: String,
+    reasoning: String,
+    tool_calls: Vec<crate::ToolCallRequest>,
+    done: Option<LlmResponse>,
+}
+
+impl StreamAccumulator {
+    pub fn push(&mut self, event: &StreamEvent) {
+        match event {
+            StreamEvent::TextDelta(text) => self.content.push_str(text),
+            StreamEvent::ReasoningDelta(text) => self.reasoning.push_str(text),
+            StreamEvent::ToolCall(call) => self.tool_calls.push(call.clone()),
+            StreamEvent::Done(resp) => self.done = Some(resp.clone()),
+        }
+    }
+
+    pub fn finish(self) -> LlmResponse {
+        if let Some(done) = self.done {
+            return done;
+        }
+        let finish_reason = if self.tool_calls.is_empty() {
+            "stop"
+        } else {
+            "tool_calls"
+        };
+        LlmResponse {
+            content: (!self.content.is_empty()).then_some(self.content),
+            tool_calls: self.tool_calls,
+            finish_reason: finish_reason.to_string(),
+            usage: BTreeMap::new(),
+            model: None,
+            reasoning: (!self.reasoning.is_empty()).then_some(self.reasoning),
+            thinking_blocks: Vec::new(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct SseLines {
+    buf: Vec<u8>,
+}
+
+impl SseLines {
+    pub(crate) fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buf.extend_from_slice(bytes);
+        let mut out = Vec::new();
+        while let Some(pos) = self.buf.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            if let Some(data) = line.trim_end().strip_prefix("data:") {
+                out.push(data.trim_start().to_string());
+            }
+        }
+        out
+    }
+}
+
+pub async fn collect_stream(mut stream: ChatStream) -> ProviderResult<LlmResponse> {
+    let mut acc = StreamAccumulator::default();
+    while let Some(event) = stream.next().await {
+        acc.push(&event?);
+    }
+    Ok(acc.finish())
+}
+
+pub fn tag_model(stream: ChatStream, model: String) -> ChatStream {
+    stream
+        .map(move |event| match event {
+            Ok(StreamEvent::Done(mut resp)) => {
+                resp.model.get_or_insert_with(|| model.clone());
+                Ok(StreamEvent::Done(resp))
+            }
+            other => other,
+        })
+        .boxed()
+}
+
+pub fn response_stream(resp: LlmResponse) -> ChatStream {
+    let mut events = Vec::new();
+    if let Some(reasoning) = resp.reasoning.clone().filter(|r| !r.is_empty()) {
+        events.push(Ok(StreamEvent::ReasoningDelta(reasoning)));
+    }
+    if let Some(content) = resp.content.clone().filter(|c| !c.is_empty()) {
+        events.push(Ok(StreamEvent::TextDelta(content)));
+    }
+    for call in &resp.tool_calls {
+        events.push(Ok(StreamEvent::ToolCall(call.clone())));
+    }
+    events.push(Ok(StreamEvent::Done(resp)));
+    futures::stream::iter(events).boxed()
+}
@@ -0,0 +1,203 @@
+use jsonschema::validator_for;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use crate::{ChatOptions, LlmProvider, ProviderError, ProviderResult, ResponseFormat};
+
+pub async fn chat_structured(
+    provider: &dyn LlmProvider,
+    messages: Vec<Value>,
+    model: Option<&str>,
+    options: &ChatOptions,
+    format: ResponseFormat,
+) -> ProviderResult<Value> {
+    chat_checked(provider, messages, model, options, format, Ok).await
+}
+
+pub async fn chat_typed<T: DeserializeOwned>(
+    provider: &dyn LlmProvider,
+    messages: Vec<Value>,
+    model: Option<&str>,
+    options: &ChatOptions,
+    format: ResponseFormat,
+) -> ProviderResult<T> {
+    chat_checked(provider, messages, model, options, format, |value| {
+        serde_json::from_value(value).map_err(|err| vec![err.to_string()])
+    })
+    .await
+}
+
+async fn chat_checked<T>(
+    provider: &dyn LlmProvider,
+    mut messages: Vec<Value>,
+    model: Option<&str>,
+    options: &ChatOptions,
+    format: ResponseFormat,
+    convert: impl Fn(Value) -> Result<T, Vec<String>>,
+) -> ProviderResult<T> {
+    let validator = validator_for(&format.schema)
+        .map_err(|err| ProviderError::InvalidSchema(format!("{}: {err}", format.name)))?;
+    let mut options = options.clone();
+    options.response_format = Some(format);
+
+    let mut repaired = false;
+    loop {
+        let resp = provider
+            .chat(messages.clone(), None, model, &options)
+            .await?;
+        let content = resp.content.unwrap_or_default();
+        let errors = match extract_json(&content) {
+            Ok(value) => {
+                let errors: Vec<String> = validator
+                    .iter_errors(&value)
+                    .map(|e| format!("{} {e}", e.instance_path).trim().to_string())
+                    .collect();
+                if errors.is_empty() {
+                    match convert(value) {
+                        Ok(out) => return Ok(out),
+                        Err(errors) => errors,
+                    }
+                } else {
+                    errors
+                }
+            }
+            Err(err) => vec![format!("not valid JSON: {err}")],
+        };
+
+        if repaired {
+            return Err(ProviderError::MalformedResponse(format!(
+                "structured response failed validation: {}",
+                errors.join("; ")
+            )));
+        }
+        tracing::debug!("structured response invalid, asking for a repair: {errors:?}");
+        messages.push(json!({"role": "assistant", "content": content}));
+        messages.push(json!({
+            "role": "user",
+            "content": format!(
+                "Your reply did not match the required JSON schema: {}. Reply again with only the corrected JSON.",
+                errors.join("; ")
+            ),
+        }));
+        repaired = true;
+    }
+}
+
+pub(crate) fn with_system_text(mut messages: Vec<Value>, text: &str) -> Vec<Value> {
+    match messages.first_mut() {
+        Some(system) if system["role"] == "system" => match &mut system["content"] {
+            Value::Array(parts) => parts.push(json!({"type": "text", "text": text})),
+            content => {
+                let current = content.as_str().unwrap_or_default();
+                *content = Value::String(format!("{current}\n\n{text}"));
+            }
+        },
+        _ => messages.insert(0, json!({"role": "system", "content": text})),
+    }
+    messages
+}
+
+fn extract_json(content: &str) -> serde_json::Result<Value> {
+    let trimmed = content.trim();
+    let unfenced = trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|block| block.trim_start_matches("json").trim())
+        .unwrap_or(trimmed);
+    serde_json::from_str(unfenced)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{LlmResponse, ScriptedProvider};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Triage {
+        category: String,
+        urgent: bool,
+    }
+
+    fn triage_format() -> ResponseFormat {
+        ResponseFormat::new(
+            "triage",
+            json!({
+                "type": "object",
+                "properties": {
+                    "category": {"type": "string", "enum": ["question", "task", "chatter"]},
+                    "urgent": {"type": "boolean"}
+                },
+                "required": ["category", "urgent"]
+            }),
+        )
+    }
+
+    #[tokio::test]
+    async fn repairs_once_then_deserializes() {
+        let provider = ScriptedProvider::new(vec![
+            LlmResponse::text("{\"category\": \"errand\", \"urgent\": \"no\"}"),
+            LlmResponse::text("```json\n{\"category\": \"task\", \"urgent\": false}\n```"),
+        ]);
+        let messages = vec![json!({"role": "user", "content": "buy milk later"})];
+
+        let triage: Triage = chat_typed(
+            &provider,
+            messages,
+            None,
+            &ChatOptions::default(),
+            triage_format(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            triage,
+            Triage {
+                category: "task".to_string(),
+                urgent: false
+            }
+        );
+
+        let retry = &provider.requests()[1];
+        assert_eq!(retry.len(), 3);
+        assert!(retry[2]["content"].as_str().unwrap().contains("/urgent"));
+        assert_eq!(
+            provider.options()[0].response_format.as_ref().unwrap().name,
+            "triage"
+        );
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_one_repair() {
+        let provider = ScriptedProvider::new(vec![
+            LlmResponse::text("not json"),
+            LlmResponse::text("still not json"),
+        ]);
+        let err = chat_structured(
+            &provider,
+            vec![json!({"role": "user", "content": "hi"})],
+            None,
+            &ChatOptions::default(),
+            triage_format(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ProviderError::MalformedResponse(_)));
+        assert_eq!(provider.remaining(), 0);
+
+        let bad_schema = ResponseFormat::new("broken", json!({"type": "nonsense"}));
+        let err = chat_structured(
+            &provider,
+            Vec::new(),
+            None,
+            &ChatOptions::default(),
+            bad_schema,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ProviderError::InvalidSchema(_)), "{err:?}");
+        assert!(!err.is_retryable());
+    }
+}
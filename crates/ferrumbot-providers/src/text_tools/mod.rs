@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ferrumbot_config::ToolCallMode;
+use futures::StreamExt;
+use serde_json::Value;
+
+use crate::{ChatOptions, ChatStream, Embeddings, LlmProvider, LlmResponse, ProviderResult};
+
+mod parse;
+mod prompt;
+mod stream;
+
+use parse::parse_tool_calls;
+use prompt::{to_text_messages, tools_prompt};
+use stream::HoldBack;
+
+pub struct TextToolProvider {
+    inner: Arc<dyn LlmProvider>,
+    mode: ToolCallMode,
+}
+
+impl TextToolProvider {
+    pub fn new(inner: Arc<dyn LlmProvider>, mode: ToolCallMode) -> Self {
+        Self { inner, mode }
+    }
+
+    fn prepare(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+    ) -> (Vec<String>, Vec<Value>, Option<Vec<Value>>) {
+        let names: Vec<String> = tools
+            .iter()
+            .flatten()
+            .filter_map(|tool| tool["function"]["name"].as_str())
+            .map(ToString::to_string)
+            .collect();
+        match tools {
+            Some(tools) if self.mode == ToolCallMode::Prompt && !tools.is_empty() => (
+                names,
+                to_text_messages(messages, &tools_prompt(&tools)),
+                None,
+            ),
+            other => (names, messages, other),
+        }
+    }
+}
+
+fn extract_calls(resp: &mut LlmResponse, names: &[String]) {
+    if let Some(content) = &resp.content {
+        let (text, calls) = parse_tool_calls(content, names);
+        if !calls.is_empty() {
+            resp.content = (!text.is_empty()).then_some(text);
+            resp.tool_calls = calls;
+            resp.finish_reason = "tool_calls".to_string();
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for TextToolProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<LlmResponse> {
+        let (names, messages, tools) = self.prepare(messages, tools);
+        let mut resp = self.inner.chat(messages, tools, model, options).await?;
+        if !resp.has_tool_calls() && !names.is_empty() {
+            extract_calls(&mut resp, &names);
+        }
+        Ok(resp)
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: Vec<Value>,
+        tools: Option<Vec<Value>>,
+        model: Option<&str>,
+        options: &ChatOptions,
+    ) -> ProviderResult<ChatStream> {
+        let (names, messages, tools) = self.prepare(messages, tools);
+        let stream = self
+            .inner
+            .chat_stream(messages, tools, model, options)
+            .await?;
+        if names.is_empty() {
+            return Ok(stream);
+        }
+
+        let mut hold = HoldBack::new(names);
+        Ok(stream
+            .map(move |event| futures::stream::iter(hold.push(event)))
+            .flatten()
+            .boxed())
+    }
+
+    async fn embed(&self, texts: Vec<String>, model: Option<&str>) -> ProviderResult<Embeddings> {
+        self.inner.embed(texts, model).await
+    }
+
+    async fn list_models(&self) -> ProviderResult<Vec<String>> {
+        self.inner.list_models().await
+    }
+
+    fn get_default_model(&self) -> &str {
+        self.inner.get_default_model()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{ScriptedProvider, StreamEvent};
+
+    #[tokio::test]
+    async fn prompt_mode_describes_tools_and_parses_replies() {
+        let scripted = Arc::new(ScriptedProvider::new(vec![LlmResponse::text(
+            "<tool_call>{\"name\": \"read_file\", \"arguments\": {\"path\": \"a.txt\"}}</tool_call>",
+        )]));
+        let provider = TextToolProvider::new(scripted.clone(), ToolCallMode::Prompt);
+        let tools = vec![json!({
+            "type": "function",
+            "function": {"name": "read_file", "description": "Read a file", "parameters": {"type": "object"}}
+        })];
+        let messages = vec![
+            json!({"role": "system", "content": "You are ferrum-bot."}),
+            json!({"role": "user", "content": "open a.txt"}),
+            json!({"role": "assistant", "content": "", "tool_calls": [{
+                "id": "call_1", "type": "function",
+                "function": {"name": "read_file", "arguments": "{\"path\":\"b.txt\"}"}
+            }]}),
+            json!({"role": "tool", "tool_call_id": "call_1", "name": "read_file", "content": "bee"}),
+        ];
+
+        let resp = provider
+            .chat(messages, Some(tools), None, &ChatOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(resp.finish_reason, "tool_calls");
+        assert_eq!(resp.tool_calls[0].arguments, json!({"path": "a.txt"}));
+        assert_eq!(resp.content, None);
+
+        let sent = &scripted.requests()[0];
+        assert!(
+            sent[0]["content"]
+                .as_str()
+                .unwrap()
+                .contains("### read_file")
+        );
+        assert!(sent[2]["content"].as_str().unwrap().contains("<tool_call>"));
+        assert_eq!(sent[3]["role"], "user");
+        assert!(sent[3]["content"].as_str().unwrap().contains("bee"));
+    }
+
+    fn replay(hold: &mut HoldBack, deltas: &[&str]) -> (Vec<String>, Vec<StreamEvent>) {
+        let mut events: Vec<StreamEvent> = deltas
+            .iter()
+            .flat_map(|delta| hold.push(Ok(StreamEvent::TextDelta(delta.to_string()))))
+            .map(Result::unwrap)
+            .collect();
+        events.extend(
+            hold.push(Ok(StreamEvent::Done(LlmResponse::text(deltas.concat()))))
+                .into_iter()
+                .map(Result::unwrap),
+        );
+        let text = events
+            .iter()
+            .filter_map(|event| match event {
+                StreamEvent::TextDelta(text) => Some(text.clone()),
+                _ => None,
+            })
+            .collect();
+        (text, events)
+    }
+
+    #[test]
+    fn stream_holds_back_tool_call_markup() {
+        let names = vec!["read_file".to_string()];
+        let mut hold = HoldBack::new(names.clone());
+        let (text, events) = replay(
+            &mut hold,
+            &[
+                "Let me ",
+                "look.\n<tool",
+                "_call>{\"name\": \"read_file\", \"arguments\": {\"path\": \"a\"}}</tool_call>",
+            ],
+        );
+        assert_eq!(text, ["Let me ", "look.\n"]);
+        assert!(matches!(&events[2], StreamEvent::ToolCall(call) if call.name == "read_file"));
+        let StreamEvent::Done(resp) = &events[3] else {
+            panic!("expected the final response");
+        };
+        assert_eq!(resp.finish_reason, "tool_calls");
+        assert_eq!(resp.content.as_deref(), Some("Let me look."));
+
+        let mut hold = HoldBack::new(names.clone());
+        let (text, _) = replay(
+            &mut hold,
+            &["  {\"name\": \"read_file\",", " \"arguments\": {}}"],
+        );
+        assert!(text.is_empty());
+
+        let mut hold = HoldBack::new(names);
+        let (text, events) = replay(&mut hold, &["Here:\n```", "rust\nfn main() {}\n```"]);
+        assert_eq!(text, ["Here:\n", "```rust\nfn main() {}\n```"]);
+        assert!(
+            matches!(events.last(), Some(StreamEvent::Done(resp)) if resp.tool_calls.is_empty())
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use serde_json::{Value, json};
+
+use crate::ToolCallRequest;
+
+const HERMES_OPEN: &str = "<tool_call>";
+const HERMES_CLOSE: &str = "</tool_call>";
+const MISTRAL_MARKER: &str = "[TOOL_CALLS]";
+const LLAMA_MARKER: &str = "<|python_tag|>";
+pub(super) const OPENERS: [&str; 4] = [HERMES_OPEN, "```", MISTRAL_MARKER, LLAMA_MARKER];
+
+pub(crate) fn parse_tool_calls(content: &str, known: &[String]) -> (String, Vec<ToolCallRequest>) {
+    if content.contains(HERMES_OPEN) {
+        return parse_hermes(content, known);
+    }
+    if let Some((before, rest)) = content
+        .split_once(MISTRAL_MARKER)
+        .or_else(|| content.split_once(LLAMA_MARKER))
+    {
+        let calls = calls_from_json(rest.trim(), known);
+        if !calls.is_empty() {
+            return (before.trim().to_string(), calls);
+        }
+    }
+    if let Some(parsed) = parse_fenced(content, known) {
+        return parsed;
+    }
+
+    let calls = calls_from_json(content.trim(), known);
+    if calls.is_empty() {
+        (content.to_string(), calls)
+    } else {
+        (String::new(), calls)
+    }
+}
+
+fn parse_hermes(content: &str, known: &[String]) -> (String, Vec<ToolCallRequest>) {
+    let mut text = String::new();
+    let mut calls = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find(HERMES_OPEN) {
+        text.push_str(&rest[..start]);
+        let body = &rest[start + HERMES_OPEN.len()..];
+        let (inner, after) = match body.find(HERMES_CLOSE) {
+            Some(end) => (&body[..end], &body[end + HERMES_CLOSE.len()..]),
+            None => (body, ""),
+        };
+        let parsed = calls_from_json(inner.trim(), known);
+        if parsed.is_empty() {
+            text.push_str(&rest[start..rest.len() - after.len()]);
+        }
+        calls.extend(parsed);
+        rest = after;
+    }
+    text.push_str(rest);
+    (text.trim().to_string(), calls)
+}
+
+fn parse_fenced(content: &str, known: &[String]) -> Option<(String, Vec<ToolCallRequest>)> {
+    let mut text = String::new();
+    let mut calls = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("```") {
+        let after_fence = &rest[start + 3..];
+        let Some(end) = after_fence.find("```") else {
+            break;
+        };
+        let block = &after_fence[..end];
+        let body = block
+            .split_once('\n')
+            .filter(|(lang, _)| !lang.trim_start().starts_with(['{', '[']))
+            .map_or(block, |(_, body)| body);
+        let parsed = calls_from_json(body.trim(), known);
+        text.push_str(&rest[..start]);
+        if parsed.is_empty() {
+            text.push_str(&rest[start..start + 3 + end + 3]);
+        }
+        calls.extend(parsed);
+        rest = &after_fence[end + 3..];
+    }
+    if calls.is_empty() {
+        return None;
+    }
+    text.push_str(rest);
+    Some((text.trim().to_string(), calls))
+}
+
+fn calls_from_json(raw: &str, known: &[String]) -> Vec<ToolCallRequest> {
+    let Ok(value) = serde_json::from_str::<Value>(raw) else {
+        return Vec::new();
+    };
+    let items = match value {
+        Value::Array(items) => items,
+        other => vec![other],
+    };
+    items
+        .iter()
+        .filter_map(|item| call_from_value(item, known))
+        .collect()
+}
+
+fn call_from_value(item: &Value, known: &[String]) -> Option<ToolCallRequest> {
+    let call = item.get("function").unwrap_or(item);
+    let name = call
+        .get("name")
+        .or_else(|| call.get("tool"))
+        .and_then(Value::as_str)?;
+    if !known.iter().any(|tool| tool == name) {
+        return None;
+    }
+    let arguments = match call
+        .get("arguments")
+        .or_else(|| call.get("parameters"))
+        .or_else(|| call.get("args"))
+    {
+        Some(Value::String(raw)) => {
+            serde_json::from_str(raw).unwrap_or_else(|_| json!({ "raw": raw }))
+        }
+        Some(value) => value.clone(),
+        None => json!({}),
+    };
+    Some(ToolCallRequest {
+        id: format!("call_{:08x}", fastrand::u32(..)),
+        name: name.to_string(),
+        arguments,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::parse_tool_calls;
+
+    fn known() -> Vec<String> {
+        vec!["read_file".to_string(), "web_search".to_string()]
+    }
+
+    #[test]
+    fn parses_common_text_formats() {
+        let hermes = "Let me look.\n<tool_call>\n{\"name\": \"read_file\", \"arguments\": {\"path\": \"a.txt\"}}\n</tool_call>";
+        let (text, calls) = parse_tool_calls(hermes, &known());
+        assert_eq!(text, "Let me look.");
+        assert_eq!(calls[0].name, "read_file");
+        assert_eq!(calls[0].arguments, json!({"path": "a.txt"}));
+
+        let mistral = "[TOOL_CALLS] [{\"name\": \"web_search\", \"arguments\": {\"query\": \"rust\"}}, {\"name\": \"read_file\", \"arguments\": \"{\\\"path\\\": \\\"b\\\"}\"}]";
+        let (_, calls) = parse_tool_calls(mistral, &known());
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].arguments, json!({"path": "b"}));
+
+        let fenced = "```json\n{\"name\": \"read_file\", \"parameters\": {\"path\": \"c\"}}\n```";
+        let (text, calls) = parse_tool_calls(fenced, &known());
+        assert!(text.is_empty());
+        assert_eq!(calls[0].arguments, json!({"path": "c"}));
+
+        let bare = "{\"function\": {\"name\": \"web_search\", \"arguments\": {\"query\": \"x\"}}}";
+        assert_eq!(parse_tool_calls(bare, &known()).1[0].name, "web_search");
+    }
+
+    #[test]
+    fn ignores_json_that_is_not_a_known_tool() {
+        let content =
+            "Here is the config:\n```json\n{\"name\": \"ferrum\", \"arguments\": []}\n```";
+        let (text, calls) = parse_tool_calls(content, &known());
+        assert!(calls.is_empty());
+        assert_eq!(text, content);
+    }
+}
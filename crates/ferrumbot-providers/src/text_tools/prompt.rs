@@ -0,0 +1,67 @@
+use serde_json::{Value, json};
+
+use crate::prompt_cache::without_cache_markers;
+
+pub(crate) fn tools_prompt(tools: &[Value]) -> String {
+    let mut prompt = String::from(
+        "## Tools\n\nYou can call the tools below. To call one, reply with one block per call and nothing else:\n<tool_call>\n{\"name\": \"<tool name>\", \"arguments\": {<arguments as JSON>}}\n</tool_call>\nResults are returned in <tool_response> blocks. When you have the answer, reply normally without a tool call.\n",
+    );
+    for tool in tools {
+        let function = tool.get("function").unwrap_or(tool);
+        prompt.push_str(&format!(
+            "\n### {}\n{}\nParameters: {}\n",
+            function["name"].as_str().unwrap_or_default(),
+            function["description"].as_str().unwrap_or_default(),
+            function.get("parameters").unwrap_or(&json!({})),
+        ));
+    }
+    prompt
+}
+
+pub(crate) fn to_text_messages(messages: Vec<Value>, tools_prompt: &str) -> Vec<Value> {
+    let mut out: Vec<Value> = Vec::with_capacity(messages.len() + 1);
+    let mut prompted = false;
+    for mut message in without_cache_markers(messages) {
+        match message["role"].as_str() {
+            Some("system") if !prompted => {
+                let content = message["content"].as_str().unwrap_or_default();
+                message["content"] = Value::String(format!("{content}\n\n{tools_prompt}"));
+                prompted = true;
+            }
+            Some("assistant") if message.get("tool_calls").is_some() => {
+                let mut content = message["content"].as_str().unwrap_or_default().to_string();
+                for call in message["tool_calls"].as_array().into_iter().flatten() {
+                    let arguments = match &call["function"]["arguments"] {
+                        Value::String(raw) => serde_json::from_str(raw).unwrap_or(json!({})),
+                        other => other.clone(),
+                    };
+                    let call = json!({"name": call["function"]["name"], "arguments": arguments});
+                    content.push_str(&format!("\n<tool_call>\n{call}\n</tool_call>"));
+                }
+                message = json!({"role": "assistant", "content": content.trim()});
+            }
+            Some("tool") => {
+                let result = json!({"name": message["name"], "content": message["content"]});
+                let block = format!("<tool_response>\n{result}\n</tool_response>");
+                if let Some(last) = out.last_mut()
+                    && last["role"] == "user"
+                    && last["content"]
+                        .as_str()
+                        .is_some_and(|c| c.starts_with("<tool_response>"))
+                {
+                    let merged =
+                        format!("{}\n{block}", last["content"].as_str().unwrap_or_default());
+                    last["content"] = Value::String(merged);
+                    continue;
+                }
+                message = json!({"role": "user", "content": block});
+            }
+            _ => {}
+        }
+        out.push(message);
+    }
+    if !prompted {
+        out.insert(0, json!({"role": "system", "content": tools_prompt}));
+    }
+    out
+}
@@ -0,0 +1,112 @@
+use crate::{LlmResponse, ProviderResult, StreamEvent};
+
+use super::extract_calls;
+use super::parse::OPENERS;
+
+pub(super) struct HoldBack {
+    names: Vec<String>,
+    content: String,
+    shown: usize,
+    held: bool,
+}
+
+impl HoldBack {
+    pub(super) fn new(names: Vec<String>) -> Self {
+        Self {
+            names,
+            content: String::new(),
+            shown: 0,
+            held: false,
+        }
+    }
+
+    pub(super) fn push(
+        &mut self,
+        event: ProviderResult<StreamEvent>,
+    ) -> Vec<ProviderResult<StreamEvent>> {
+        match event {
+            Ok(StreamEvent::TextDelta(text)) => {
+                self.content.push_str(&text);
+                self.release()
+                    .map(StreamEvent::TextDelta)
+                    .map(Ok)
+                    .into_iter()
+                    .collect()
+            }
+            Ok(StreamEvent::Done(resp)) => self.finish(resp),
+            other => vec![other],
+        }
+    }
+
+    fn release(&mut self) -> Option<String> {
+        if self.held {
+            return None;
+        }
+        let pending = &self.content[self.shown..];
+        if self.shown == 0 {
+            let trimmed = pending.trim_start();
+            if trimmed.is_empty() {
+                return None;
+            }
+            self.shown = self.content.len() - trimmed.len();
+            if trimmed.starts_with(['{', '[']) {
+                self.held = true;
+                return None;
+            }
+        }
+
+        let pending = &self.content[self.shown..];
+        let end = match OPENERS
+            .iter()
+            .filter_map(|opener| pending.find(opener))
+            .min()
+        {
+            Some(start) => {
+                self.held = true;
+                start
+            }
+            None => pending.len() - partial_opener(pending),
+        };
+        let text = pending[..end].to_string();
+        self.shown += end;
+        (!text.is_empty()).then_some(text)
+    }
+
+    fn finish(&mut self, mut resp: LlmResponse) -> Vec<ProviderResult<StreamEvent>> {
+        let native = resp.has_tool_calls();
+        if !native {
+            extract_calls(&mut resp, &self.names);
+        }
+
+        let mut events = Vec::new();
+        let shown = self.content[..self.shown].trim_start();
+        let text = resp.content.as_deref().unwrap_or_default().trim_start();
+        let rest = text
+            .strip_prefix(shown)
+            .or_else(|| text.strip_prefix(shown.trim_end()))
+            .unwrap_or_default();
+        if !rest.is_empty() {
+            events.push(Ok(StreamEvent::TextDelta(rest.to_string())));
+        }
+        if !native {
+            events.extend(
+                resp.tool_calls
+                    .iter()
+                    .cloned()
+                    .map(|call| Ok(StreamEvent::ToolCall(call))),
+            );
+        }
+        events.push(Ok(StreamEvent::Done(resp)));
+        events
+    }
+}
+
+fn partial_opener(text: &str) -> usize {
+    OPENERS
+        .iter()
+        .flat_map(|opener| (1..opener.len()).rev().map(move |len| &opener[..len]))
+        .filter(|prefix| text.ends_with(prefix))
+        .map(str::len)
+        .max()
+        .unwrap_or(0)
+}
@@ -1,8 +1,63 @@
 use std::collections::BTreeMap;
 
+use futures::stream::BoxStream;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use ferrumbot_config::GenerationProfile;
+
+use crate::ProviderResult;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatOptions {
+    pub max_tokens: Option<usize>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub stop: Vec<String>,
+    pub seed: Option<u64>,
+    pub reasoning_effort: Option<String>,
+    pub parallel_tool_calls: Option<bool>,
+    pub response_format: Option<ResponseFormat>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseFormat {
+    pub name: String,
+    pub schema: Value,
+}
+
+impl ResponseFormat {
+    pub fn new(name: impl Into<String>, schema: Value) -> Self {
+        Self {
+            name: name.into(),
+            schema,
+        }
+    }
+
+    pub fn instructions(&self) -> String {
+        format!(
+            "Respond only with a JSON value that matches this JSON schema, with no other text:\n{}",
+            self.schema
+        )
+    }
+}
+
+impl From<&GenerationProfile> for ChatOptions {
+    fn from(profile: &GenerationProfile) -> Self {
+        Self {
+            max_tokens: profile.max_tokens,
+            temperature: profile.temperature,
+            top_p: profile.top_p,
+            stop: profile.stop.clone(),
+            seed: profile.seed,
+            reasoning_effort: profile.reasoning_effort.clone(),
+            parallel_tool_calls: None,
+            response_format: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCallRequest {
     pub id: String,
@@ -16,10 +71,78 @@ pub struct LlmResponse {
     pub tool_calls: Vec<ToolCallRequest>,
     pub finish_reason: String,
     pub usage: BTreeMap<String, i64>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thinking_blocks: Vec<Value>,
 }
 
 impl LlmResponse {
+    pub fn text(content: impl Into<String>) -> Self {
+        Self {
+            content: Some(content.into()),
+            tool_calls: Vec::new(),
+            finish_reason: "stop".to_string(),
+            usage: BTreeMap::new(),
+            model: None,
+            reasoning: None,
+            thinking_blocks: Vec::new(),
+        }
+    }
+
+    pub fn with_tool_calls(tool_calls: Vec<ToolCallRequest>) -> Self {
+        Self {
+            content: None,
+            tool_calls,
+            finish_reason: "tool_calls".to_string(),
+            usage: BTreeMap::new(),
+            model: None,
+            reasoning: None,
+            thinking_blocks: Vec::new(),
+        }
+    }
+
     pub fn has_tool_calls(&self) -> bool {
         !self.tool_calls.is_empty()
     }
 }
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Embeddings {
+    pub model: String,
+    pub vectors: Vec<Vec<f32>>,
+    #[serde(default)]
+    pub usage: BTreeMap<String, i64>,
+}
+
+impl Embeddings {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.vectors.first().map(Vec::len).unwrap_or_default()
+    }
+
+    pub fn extend(&mut self, other: Embeddings) {
+        self.vectors.extend(other.vectors);
+        for (key, value) in other.usage {
+            *self.usage.entry(key).or_default() += value;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    TextDelta(String),
+    ReasoningDelta(String),
+    ToolCall(ToolCallRequest),
+    Done(LlmResponse),
+}
+
+pub type ChatStream = BoxStream<'static, ProviderResult<StreamEvent>>;
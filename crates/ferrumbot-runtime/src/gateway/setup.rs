@@ -19,17 +19,25 @@ pub(super) async fn build_runtime(config: Config) -> Result<GatewayRuntime> {
         &config,
         Some(cron.clone()),
     )?);
+    agent.enable_subagents();
 
     let callback_agent = agent.clone();
     cron.set_on_job(boxed_callback(move |job| {
         let agent = callback_agent.clone();
         async move {
+            let metadata = job
+                .payload
+                .profile
+                .iter()
+                .map(|profile| ("profile".to_string(), profile.clone().into()))
+                .collect();
             let response = agent
-                .process_direct(
+                .process_direct_with_metadata(
                     &job.payload.message,
                     &format!("cron:{}", job.id),
                     job.payload.channel.as_deref().unwrap_or("cli"),
                     job.payload.to.as_deref().unwrap_or("direct"),
+                    metadata,
                 )
                 .await?;
             Ok(Some(response))
@@ -37,7 +45,7 @@ pub(super) async fn build_runtime(config: Config) -> Result<GatewayRuntime> {
     }))
     .await;
 
-    let channels = Arc::new(ChannelManager::new(&config, bus));
+    let channels = Arc::new(ChannelManager::new(&config, bus)?);
 
     Ok(GatewayRuntime {
         agent,
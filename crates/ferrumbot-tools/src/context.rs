@@ -3,6 +3,8 @@ use std::path::PathBuf;
 use ferrumbot_core::MessageBus;
 use ferrumbot_cron::CronService;
 
+use crate::SubagentManager;
+
 #[derive(Clone)]
 pub struct ToolContext {
     pub workspace: PathBuf,
@@ -10,4 +12,6 @@ pub struct ToolContext {
     pub current_chat_id: Option<String>,
     pub bus: Option<MessageBus>,
     pub cron: Option<CronService>,
+    pub session_key: Option<String>,
+    pub subagents: Option<SubagentManager>,
 }
@@ -59,6 +59,10 @@ async fn add_job(args: &Value, cron: &CronService) -> Result<String> {
         deliver: false,
         channel: None,
         to: None,
+        profile: args
+            .get("profile")
+            .and_then(|v| v.as_str())
+            .map(ToString::to_string),
     };
 
     let job = cron.add_job(name, schedule, payload, false).await?;
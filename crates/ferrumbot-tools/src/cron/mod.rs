@@ -28,7 +28,8 @@ impl Tool for CronTool {
                 "every": { "type": "integer" },
                 "cron": { "type": "string" },
                 "at": { "type": "integer" },
-                "id": { "type": "string" }
+                "id": { "type": "string" },
+                "profile": { "type": "string" }
             },
             "required": ["action"]
         })
@@ -1,8 +1,12 @@
 use std::path::PathBuf;
 
+use anyhow::Result;
+use ferrumbot_config::{MemoryToolConfig, WebToolsConfig};
+
 use crate::cron::CronTool;
 use crate::exec::ExecTool;
 use crate::file::{EditFileTool, ListDirTool, ReadFileTool, WriteFileTool};
+use crate::memory::MemoryTool;
 use crate::message::MessageTool;
 use crate::registry::ToolRegistry;
 use crate::spawn::SpawnTool;
@@ -28,13 +32,15 @@ pub(super) fn register_runtime(
     ));
 }
 
-pub(super) fn register_web(
-    reg: &mut ToolRegistry,
-    brave_api_key: Option<String>,
-    max_results: usize,
-) {
-    reg.register(WebSearchTool::new(brave_api_key, max_results));
-    reg.register(WebFetchTool::new(50_000));
+pub(super) fn register_web(reg: &mut ToolRegistry, web: &WebToolsConfig) -> Result<()> {
+    let brave_api_key = Some(web.search.api_key.clone()).filter(|key| !key.is_empty());
+    reg.register(WebSearchTool::new(
+        brave_api_key,
+        web.search.max_results as usize,
+        &web.http,
+    )?);
+    reg.register(WebFetchTool::new(50_000, &web.http)?);
+    Ok(())
 }
 
 pub(super) fn register_message_and_spawn(reg: &mut ToolRegistry) {
@@ -46,6 +52,10 @@ pub(super) fn register_cron(reg: &mut ToolRegistry) {
     reg.register(CronTool);
 }
 
+pub(super) fn register_memory(reg: &mut ToolRegistry, memory: &MemoryToolConfig) {
+    reg.register(MemoryTool::new(memory.max_chars));
+}
+
 pub(super) fn prune_unavailable(reg: &mut ToolRegistry, has_bus: bool, has_cron: bool) {
     if !has_bus {
         reg.unregister("message");
@@ -1,3 +1,4 @@
+use ferrumbot_config::{MemoryToolConfig, WebToolsConfig};
 use serde_json::json;
 
 use crate::ToolContext;
@@ -7,7 +8,16 @@ use super::default_registry;
 #[tokio::test]
 async fn registry_returns_validation_error() {
     let workspace = std::env::temp_dir().join("ferrumbot-tools-test");
-    let reg = default_registry(workspace.clone(), None, None, None, 5, 5, false);
+    let reg = default_registry(
+        workspace.clone(),
+        None,
+        None,
+        &WebToolsConfig::default(),
+        &MemoryToolConfig::default(),
+        5,
+        false,
+    )
+    .unwrap();
     let out = reg
         .execute(
             "write_file",
@@ -18,6 +28,8 @@ async fn registry_returns_validation_error() {
                 current_chat_id: None,
                 bus: None,
                 cron: None,
+                session_key: None,
+                subagents: None,
             },
         )
         .await;
@@ -3,16 +3,21 @@ mod cron;
 mod defaults;
 mod exec;
 mod file;
+mod memory;
 mod message;
 mod path;
 mod registry;
 mod spawn;
+mod subagents;
 mod tool;
 mod web;
 
 pub use context::ToolContext;
 pub use defaults::default_registry;
 pub use registry::ToolRegistry;
+pub use subagents::{
+    SubagentInfo, SubagentManager, SubagentRunner, SubagentStatus, SubagentTask, boxed_runner,
+};
 pub use tool::Tool;
 
-pub(crate) use path::resolve_path;
+pub use path::resolve_path;
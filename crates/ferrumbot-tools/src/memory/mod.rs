@@ -0,0 +1,74 @@
+mod store;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use crate::{Tool, ToolContext};
+
+use store::MemoryStore;
+
+pub struct MemoryTool {
+    max_chars: usize,
+}
+
+impl MemoryTool {
+    pub fn new(max_chars: usize) -> Self {
+        Self { max_chars }
+    }
+}
+
+#[async_trait]
+impl Tool for MemoryTool {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn description(&self) -> &'static str {
+        "Curate your persistent memory. remember notes a fact in today's notes, promote moves a lasting fact into MEMORY.md (shown in every conversation) under a section, forget removes a fact, list shows what is stored. Use this instead of editing memory files directly."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": { "type": "string", "enum": ["remember", "promote", "forget", "list"] },
+                "fact": { "type": "string", "description": "One short, self-contained fact" },
+                "section": { "type": "string", "description": "MEMORY.md section for promote, e.g. Preferences" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, args: Value, ctx: ToolContext) -> Result<String> {
+        let store = MemoryStore::new(&ctx.workspace, self.max_chars);
+        let action = args
+            .get("action")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        let fact = args
+            .get("fact")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        match action {
+            "remember" | "promote" | "forget" if fact.is_empty() => {
+                Ok(format!("Error: fact is required for {action}"))
+            }
+            "remember" => store.remember(&fact),
+            "promote" => {
+                let section = args
+                    .get("section")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default();
+                store.promote(&fact, section)
+            }
+            "forget" => store.forget(&fact),
+            "list" => store.list(),
+            other => Ok(format!("Error: unsupported action {other}")),
+        }
+    }
+}
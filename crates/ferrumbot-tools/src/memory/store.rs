@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+
+const LONG_TERM_HEADER: &str = "# Long-term Memory\n\n";
+const DEFAULT_SECTION: &str = "General";
+
+pub(super) struct MemoryStore {
+    dir: PathBuf,
+    max_chars: usize,
+    lock: Arc<Mutex<()>>,
+}
+
+impl MemoryStore {
+    pub(super) fn new(workspace: &Path, max_chars: usize) -> Self {
+        static LOCKS: OnceLock<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> = OnceLock::new();
+        let dir = workspace.join("memory");
+        let lock = LOCKS
+            .get_or_init(Default::default)
+            .lock()
+            .expect("memory lock registry")
+            .entry(dir.clone())
+            .or_default()
+            .clone();
+        Self {
+            dir,
+            max_chars,
+            lock,
+        }
+    }
+
+    fn exclusive(&self) -> MutexGuard<'_, ()> {
+        self.lock.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn long_term(&self) -> PathBuf {
+        self.dir.join("MEMORY.md")
+    }
+
+    fn today(&self) -> (String, PathBuf) {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let path = self.dir.join(format!("{today}.md"));
+        (today, path)
+    }
+
+    fn daily_notes(&self) -> Vec<PathBuf> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut notes: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension().is_some_and(|ext| ext == "md")
+                    && path
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .is_some_and(|stem| NaiveDate::parse_from_str(stem, "%Y-%m-%d").is_ok())
+            })
+            .collect();
+        notes.sort();
+        notes
+    }
+
+    fn read(path: &Path, default: &str) -> Result<String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(content),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(default.to_string()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn write(&self, path: &Path, content: &str) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let tmp = self.dir.join(format!(".{name}.{}.tmp", std::process::id()));
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    pub(super) fn remember(&self, fact: &str) -> Result<String> {
+        let _guard = self.exclusive();
+        let (today, path) = self.today();
+        let mut note = Self::read(&path, &format!("# {today}\n\n"))?;
+        let long_term = Self::read(&self.long_term(), LONG_TERM_HEADER)?;
+        if contains_fact(&note, fact) || contains_fact(&long_term, fact) {
+            return Ok(format!("Already remembered: {fact}"));
+        }
+
+        if !note.is_empty() && !note.ends_with('\n') {
+            note.push('\n');
+        }
+        note.push_str(&format!("- {} {fact}\n", Local::now().format("%H:%M")));
+        self.write(&path, &note)?;
+        Ok(format!("Remembered in memory/{today}.md"))
+    }
+
+    pub(super) fn promote(&self, fact: &str, section: &str) -> Result<String> {
+        let _guard = self.exclusive();
+        let section = match section.trim() {
+            "" => DEFAULT_SECTION,
+            section => section,
+        };
+        let long_term = Self::read(&self.long_term(), LONG_TERM_HEADER)?;
+        let reply = if contains_fact(&long_term, fact) {
+            "Already in long-term memory.".to_string()
+        } else {
+            let updated = insert_under_section(&long_term, section, fact);
+            let size = updated.chars().count();
+            if size > self.max_chars {
+                return Ok(format!(
+                    "Error: MEMORY.md would grow to {size} chars (limit {}). Forget or merge older entries first.",
+                    self.max_chars
+                ));
+            }
+            self.write(&self.long_term(), &updated)?;
+            format!(
+                "Promoted to MEMORY.md under '{section}' ({size}/{} chars).",
+                self.max_chars
+            )
+        };
+
+        for path in self.daily_notes() {
+            let note = Self::read(&path, "")?;
+            let (kept, removed) = remove_entries(&note, &normalize(fact));
+            if removed > 0 {
+                self.write(&path, &kept)?;
+            }
+        }
+        Ok(reply)
+    }
+
+    pub(super) fn forget(&self, query: &str) -> Result<String> {
+        let _guard = self.exclusive();
+        let mut files = vec![(self.long_term(), Self::read(&self.long_term(), "")?)];
+        for path in self.daily_notes() {
+            let note = Self::read(&path, "")?;
+            files.push((path, note));
+        }
+
+        let wanted = normalize(query);
+        let entries: Vec<(String, &str)> = files
+            .iter()
+            .flat_map(|(_, content)| content.lines().filter_map(entry_text))
+            .map(|entry| (normalize(entry), entry))
+            .collect();
+        let (target, label) = match entries.iter().find(|(normalized, _)| *normalized == wanted) {
+            Some((normalized, entry)) => (normalized.clone(), entry.to_string()),
+            None => {
+                let mut candidates: Vec<&(String, &str)> = entries
+                    .iter()
+                    .filter(|(normalized, _)| normalized.contains(&wanted))
+                    .collect();
+                candidates.sort_by(|a, b| a.0.cmp(&b.0));
+                candidates.dedup_by(|a, b| a.0 == b.0);
+                match candidates.as_slice() {
+                    [] => return Ok(format!("No memory matching '{query}'.")),
+                    [(normalized, entry)] => (normalized.clone(), entry.to_string()),
+                    several => {
+                        let list: Vec<String> = several
+                            .iter()
+                            .map(|(_, entry)| format!("- {entry}"))
+                            .collect();
+                        return Ok(format!(
+                            "Error: '{query}' matches several entries, be more specific:\n{}",
+                            list.join("\n")
+                        ));
+                    }
+                }
+            }
+        };
+
+        let mut touched = Vec::new();
+        for (path, content) in files {
+            let (kept, removed) = remove_entries(&content, &target);
+            if removed > 0 {
+                self.write(&path, &kept)?;
+                touched.push(
+                    path.file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .into_owned(),
+                );
+            }
+        }
+        Ok(format!("Forgot '{label}' (from {}).", touched.join(", ")))
+    }
+
+    pub(super) fn list(&self) -> Result<String> {
+        let long_term = Self::read(&self.long_term(), LONG_TERM_HEADER)?;
+        let (today, path) = self.today();
+        let note = Self::read(&path, "")?;
+        let note = match note
+            .lines()
+            .filter(|line| entry_text(line).is_some())
+            .count()
+        {
+            0 => "(nothing yet)".to_string(),
+            _ => note.trim().to_string(),
+        };
+        Ok(format!(
+            "MEMORY.md ({}/{} chars):\n{}\n\nToday's notes (memory/{today}.md):\n{note}",
+            long_term.chars().count(),
+            self.max_chars,
+            long_term.trim()
+        ))
+    }
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches('.')
+        .to_lowercase()
+}
+
+fn entry_text(line: &str) -> Option<&str> {
+    let text = line.trim_start().strip_prefix("- ")?;
+    let bytes = text.as_bytes();
+    let timed = bytes.len() > 6
+        && bytes[..5].iter().enumerate().all(|(i, b)| match i {
+            2 => *b == b':',
+            _ => b.is_ascii_digit(),
+        })
+        && bytes[5] == b' ';
+    Some(if timed { &text[6..] } else { text })
+}
+
+fn contains_fact(content: &str, fact: &str) -> bool {
+    let wanted = normalize(fact);
+    content
+        .lines()
+        .filter_map(entry_text)
+        .any(|entry| normalize(entry) == wanted)
+}
+
+fn remove_entries(content: &str, normalized: &str) -> (String, usize) {
+    let mut removed = 0;
+    let mut kept = String::with_capacity(content.len());
+    for line in content.lines() {
+        if entry_text(line).is_some_and(|entry| normalize(entry) == normalized) {
+            removed += 1;
+            continue;
+        }
+        kept.push_str(line);
+        kept.push('\n');
+    }
+    (kept, removed)
+}
+
+fn insert_under_section(content: &str, section: &str, fact: &str) -> String {
+    let mut lines: Vec<String> = content.lines().map(ToString::to_string).collect();
+    let heading = lines.iter().position(|line| {
+        line.strip_prefix("## ")
+            .is_some_and(|title| title.trim().eq_ignore_ascii_case(section))
+    });
+
+    match heading {
+        Some(start) => {
+            let mut end = lines[start + 1..]
+                .iter()
+                .position(|line| line.starts_with('#'))
+                .map_or(lines.len(), |offset| start + 1 + offset);
+            while end > start + 1 && lines[end - 1].trim().is_empty() {
+                end -= 1;
+            }
+            lines.insert(end, format!("- {fact}"));
+        }
+        None => {
+            while lines.last().is_some_and(|line| line.trim().is_empty()) {
+                lines.pop();
+            }
+            lines.push(String::new());
+            lines.push(format!("## {section}"));
+            lines.push(format!("- {fact}"));
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::*;
+
+    fn unique_temp_dir(prefix: &str) -> PathBuf {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock drift")
+            .as_nanos();
+        std::env::temp_dir().join(format!("{prefix}-{}-{now}", std::process::id()))
+    }
+
+    fn store(name: &str, max_chars: usize) -> MemoryStore {
+        MemoryStore::new(
+            &unique_temp_dir(&format!("ferrumbot-memory-{name}")),
+            max_chars,
+        )
+    }
+
+    #[test]
+    fn facts_move_from_daily_notes_into_sections() {
+        let store = store("promote", 6000);
+        assert!(
+            store
+                .remember("User prefers metric units")
+                .unwrap()
+                .starts_with("Remembered")
+        );
+        assert!(
+            store
+                .remember("user prefers  metric units.")
+                .unwrap()
+                .starts_with("Already")
+        );
+        store.remember("Project deadline is Friday").unwrap();
+
+        store
+            .promote("User prefers metric units", "Preferences")
+            .unwrap();
+        store.promote("Likes dark mode", "preferences").unwrap();
+        store.promote("Works on ferrum-bot", "").unwrap();
+        let long_term = std::fs::read_to_string(store.long_term()).unwrap();
+        assert_eq!(
+            long_term,
+            "# Long-term Memory\n\n## Preferences\n- User prefers metric units\n- Likes dark mode\n\n## General\n- Works on ferrum-bot\n"
+        );
+        let (_, today) = store.today();
+        let note = std::fs::read_to_string(today).unwrap();
+        assert!(!note.contains("metric") && note.contains("deadline"));
+
+        assert!(store.forget("dark").unwrap().contains("Likes dark mode"));
+        assert!(store.forget("e").unwrap().starts_with("Error"));
+        assert!(
+            store
+                .forget("nothing like this")
+                .unwrap()
+                .starts_with("No memory")
+        );
+        assert!(
+            !std::fs::read_to_string(store.long_term())
+                .unwrap()
+                .contains("dark")
+        );
+
+        std::fs::remove_dir_all(store.dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn promotion_stops_at_the_size_cap() {
+        let store = store("cap", 60);
+        assert!(
+            store
+                .promote("short fact", "")
+                .unwrap()
+                .starts_with("Promoted")
+        );
+        let reply = store.promote(&"long ".repeat(10), "").unwrap();
+        assert!(reply.starts_with("Error: MEMORY.md would grow"));
+        assert!(
+            !std::fs::read_to_string(store.long_term())
+                .unwrap()
+                .contains("long")
+        );
+
+        std::fs::remove_dir_all(store.dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn concurrent_writers_keep_every_fact() {
+        let workspace = unique_temp_dir("ferrumbot-memory-concurrent");
+        std::thread::scope(|scope| {
+            for n in 0..8 {
+                let workspace = &workspace;
+                scope.spawn(move || {
+                    let store = MemoryStore::new(workspace, 6000);
+                    store.remember(&format!("fact number {n}")).unwrap();
+                    store.promote(&format!("lasting fact {n}"), "").unwrap();
+                });
+            }
+        });
+
+        let store = MemoryStore::new(&workspace, 6000);
+        let (_, today) = store.today();
+        let note = std::fs::read_to_string(today).unwrap();
+        let long_term = std::fs::read_to_string(store.long_term()).unwrap();
+        for n in 0..8 {
+            assert!(note.contains(&format!("fact number {n}")), "{note}");
+            assert!(
+                long_term.contains(&format!("lasting fact {n}")),
+                "{long_term}"
+            );
+        }
+
+        std::fs::remove_dir_all(workspace).unwrap();
+    }
+}
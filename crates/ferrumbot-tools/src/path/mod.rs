@@ -1,3 +1,3 @@
 mod resolve;
 
-pub(crate) use resolve::resolve_path;
+pub use resolve::resolve_path;
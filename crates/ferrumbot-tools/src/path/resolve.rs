@@ -2,7 +2,7 @@ use std::path::{Component, Path, PathBuf};
 
 use anyhow::{Result, anyhow};
 
-pub(crate) fn resolve_path(path: &str, allowed_dir: Option<&Path>) -> Result<PathBuf> {
+pub fn resolve_path(path: &str, allowed_dir: Option<&Path>) -> Result<PathBuf> {
     let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
     let expanded = ferrumbot_config::expand_tilde(path);
     let absolute = if expanded.is_absolute() {
@@ -24,6 +24,10 @@ impl ToolRegistry {
         self.tools.remove(name);
     }
 
+    pub fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.tools.retain(|name, _| keep(name));
+    }
+
     pub fn definitions(&self) -> Vec<Value> {
         self.tools
             .values()
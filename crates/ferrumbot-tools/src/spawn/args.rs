@@ -4,14 +4,24 @@ pub(super) fn parameters() -> Value {
     json!({
         "type": "object",
         "properties": {
-            "task": { "type": "string" }
-        },
-        "required": ["task"]
+            "action": {
+                "type": "string",
+                "enum": ["spawn", "list", "cancel"],
+                "description": "spawn (default) starts a background task, list shows tasks, cancel stops one"
+            },
+            "task": {
+                "type": "string",
+                "description": "Complete instructions for the subagent; it does not see this conversation"
+            },
+            "label": { "type": "string", "description": "Short name for the task" },
+            "id": { "type": "string", "description": "Subagent id to cancel" }
+        }
     })
 }
 
-pub(super) fn parse_task(args: &Value) -> &str {
-    args.get("task")
+pub(super) fn str_arg<'a>(args: &'a Value, key: &str) -> &'a str {
+    args.get(key)
         .and_then(|v| v.as_str())
+        .map(str::trim)
         .unwrap_or_default()
 }
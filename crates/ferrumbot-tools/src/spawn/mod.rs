@@ -4,7 +4,7 @@ use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
 
-use crate::{Tool, ToolContext};
+use crate::{SubagentManager, Tool, ToolContext};
 
 pub struct SpawnTool;
 
@@ -15,15 +15,75 @@ impl Tool for SpawnTool {
     }
 
     fn description(&self) -> &'static str {
-        "Spawn a background subagent task."
+        "Run a task in a background subagent with its own session. The result is posted to this chat when it finishes. Also lists or cancels running subagents."
     }
 
     fn parameters(&self) -> Value {
         args::parameters()
     }
 
-    async fn execute(&self, args: Value, _ctx: ToolContext) -> Result<String> {
-        let task = args::parse_task(&args);
-        Ok(format!("spawn scheduled (placeholder): {task}"))
+    async fn execute(&self, args: Value, ctx: ToolContext) -> Result<String> {
+        let Some(subagents) = ctx.subagents.clone() else {
+            return Ok("Error: background subagents are not available".to_string());
+        };
+
+        match args::str_arg(&args, "action") {
+            "list" => Ok(list(&subagents, ctx.session_key.as_deref())),
+            "cancel" => {
+                let id = args::str_arg(&args, "id");
+                if subagents.cancel(id) {
+                    Ok(format!("Cancelled subagent {id}"))
+                } else {
+                    Ok(format!("Error: no running subagent with id '{id}'"))
+                }
+            }
+            "" | "spawn" => spawn(&subagents, &args, &ctx),
+            other => Ok(format!("Error: unknown action '{other}'")),
+        }
+    }
+}
+
+fn spawn(subagents: &SubagentManager, args: &Value, ctx: &ToolContext) -> Result<String> {
+    let task = args::str_arg(args, "task");
+    if task.is_empty() {
+        return Ok("Error: task is required".to_string());
+    }
+    let label = match args::str_arg(args, "label") {
+        "" => task.chars().take(40).collect(),
+        label => label.to_string(),
+    };
+    let channel = ctx.current_channel.as_deref().unwrap_or("cli");
+    let chat_id = ctx.current_chat_id.as_deref().unwrap_or("direct");
+    let session_key = ctx
+        .session_key
+        .clone()
+        .unwrap_or_else(|| format!("{channel}:{chat_id}"));
+
+    match subagents.spawn(&label, task, channel, chat_id, &session_key) {
+        Ok(info) => Ok(format!(
+            "Started subagent {} ({label}). Its result will be posted to this chat when it finishes; do not wait for it.",
+            info.task.id
+        )),
+        Err(err) => Ok(format!("Error: {err}")),
+    }
+}
+
+fn list(subagents: &SubagentManager, session_key: Option<&str>) -> String {
+    let infos = subagents.list(session_key);
+    if infos.is_empty() {
+        return "No subagents.".to_string();
     }
+    infos
+        .iter()
+        .map(|info| {
+            format!(
+                "{} [{}] {}s {}",
+                info.task.id,
+                info.status,
+                info.elapsed().as_secs(),
+                info.task.label
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
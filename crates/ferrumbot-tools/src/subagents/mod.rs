@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use futures::future::BoxFuture;
+use tokio::sync::Notify;
+
+const FINISHED_HISTORY: usize = 20;
+
+pub type SubagentRunner =
+    Arc<dyn Fn(SubagentTask) -> BoxFuture<'static, Result<String>> + Send + Sync>;
+
+pub fn boxed_runner<F, Fut>(f: F) -> SubagentRunner
+where
+    F: Fn(SubagentTask) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<String>> + Send + 'static,
+{
+    Arc::new(move |task| Box::pin(f(task)))
+}
+
+#[derive(Debug, Clone)]
+pub struct SubagentTask {
+    pub id: String,
+    pub label: String,
+    pub task: String,
+    pub channel: String,
+    pub chat_id: String,
+    pub session_key: String,
+    /// Notified on cancel; runners stop the work and still report back.
+    pub cancel: Arc<Notify>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubagentStatus {
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+impl fmt::Display for SubagentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Running => write!(f, "running"),
+            Self::Done => write!(f, "done"),
+            Self::Failed(err) => write!(f, "failed: {err}"),
+            Self::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SubagentInfo {
+    pub task: SubagentTask,
+    pub status: SubagentStatus,
+    pub started: Instant,
+    pub finished: Option<Instant>,
+}
+
+impl SubagentInfo {
+    pub fn elapsed(&self) -> Duration {
+        self.finished
+            .unwrap_or_else(Instant::now)
+            .duration_since(self.started)
+    }
+}
+
+#[derive(Clone)]
+pub struct SubagentManager {
+    entries: Arc<Mutex<HashMap<String, SubagentInfo>>>,
+    runner: Arc<Mutex<Option<SubagentRunner>>>,
+    max_concurrent: usize,
+    history: usize,
+}
+
+impl SubagentManager {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            entries: Arc::default(),
+            runner: Arc::default(),
+            max_concurrent: max_concurrent.max(1),
+            history: FINISHED_HISTORY,
+        }
+    }
+
+    pub fn set_runner(&self, runner: SubagentRunner) {
+        *self.runner.lock().expect("subagent runner lock") = Some(runner);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.runner.lock().expect("subagent runner lock").is_some()
+    }
+
+    pub fn spawn(
+        &self,
+        label: &str,
+        task: &str,
+        channel: &str,
+        chat_id: &str,
+        session_key: &str,
+    ) -> Result<SubagentInfo> {
+        let Some(runner) = self.runner.lock().expect("subagent runner lock").clone() else {
+            bail!("background subagents are not available here");
+        };
+
+        let mut entries = self.entries.lock().expect("subagent entries lock");
+        let running = entries
+            .values()
+            .filter(|info| info.status == SubagentStatus::Running)
+            .count();
+        if running >= self.max_concurrent {
+            bail!(
+                "{running} subagents are already running (limit {}); wait for one to finish or cancel one",
+                self.max_concurrent
+            );
+        }
+
+        let info = SubagentInfo {
+            task: SubagentTask {
+                id: format!("{:08x}", fastrand::u32(..)),
+                label: label.to_string(),
+                task: task.to_string(),
+                channel: channel.to_string(),
+                chat_id: chat_id.to_string(),
+                session_key: session_key.to_string(),
+                cancel: Arc::new(Notify::new()),
+            },
+            status: SubagentStatus::Running,
+            started: Instant::now(),
+            finished: None,
+        };
+        let id = info.task.id.clone();
+
+        let manager = self.clone();
+        let fut = runner(info.task.clone());
+        let task_id = id.clone();
+        tokio::spawn(async move {
+            let status = match fut.await {
+                Ok(_) => SubagentStatus::Done,
+                Err(err) => SubagentStatus::Failed(format!("{err:#}")),
+            };
+            manager.finish(&task_id, status);
+        });
+        entries.insert(id, info.clone());
+        Ok(info)
+    }
+
+    pub fn list(&self, session_key: Option<&str>) -> Vec<SubagentInfo> {
+        let entries = self.entries.lock().expect("subagent entries lock");
+        let mut out: Vec<SubagentInfo> = entries
+            .values()
+            .filter(|info| session_key.is_none_or(|key| info.task.session_key == key))
+            .cloned()
+            .collect();
+        out.sort_by_key(|info| info.started);
+        out
+    }
+
+    pub fn cancel(&self, id: &str) -> bool {
+        let mut entries = self.entries.lock().expect("subagent entries lock");
+        let Some(info) = entries
+            .get_mut(id)
+            .filter(|info| info.status == SubagentStatus::Running)
+        else {
+            return false;
+        };
+        info.task.cancel.notify_one();
+        info.status = SubagentStatus::Cancelled;
+        info.finished = Some(Instant::now());
+        self.prune(&mut entries);
+        true
+    }
+
+    fn finish(&self, id: &str, status: SubagentStatus) {
+        let mut entries = self.entries.lock().expect("subagent entries lock");
+        if let Some(info) = entries
+            .get_mut(id)
+            .filter(|info| info.status == SubagentStatus::Running)
+        {
+            info.status = status;
+            info.finished = Some(Instant::now());
+        }
+        self.prune(&mut entries);
+    }
+
+    fn prune(&self, entries: &mut HashMap<String, SubagentInfo>) {
+        let mut finished: Vec<(Instant, String)> = entries
+            .values()
+            .filter_map(|info| Some((info.finished?, info.task.id.clone())))
+            .collect();
+        if finished.len() <= self.history {
+            return;
+        }
+        finished.sort();
+        let excess = finished.len() - self.history;
+        for (_, id) in finished.into_iter().take(excess) {
+            entries.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn spawn_list_and_cancel() {
+        let manager = SubagentManager::new(1);
+        assert!(
+            manager
+                .spawn("a", "task", "cli", "direct", "cli:test")
+                .is_err()
+        );
+
+        let (reports, mut reported) = tokio::sync::mpsc::unbounded_channel();
+        manager.set_runner(boxed_runner(move |task: SubagentTask| {
+            let reports = reports.clone();
+            async move {
+                if task.task == "quick" {
+                    return Ok("done".to_string());
+                }
+                tokio::select! {
+                    _ = task.cancel.notified() => {
+                        let _ = reports.send(format!("{} cancelled", task.label));
+                        bail!("cancelled")
+                    }
+                    _ = tokio::time::sleep(Duration::from_secs(60)) => Ok(String::new()),
+                }
+            }
+        }));
+        let slow = manager
+            .spawn("slow", "wait", "cli", "direct", "cli:test")
+            .unwrap();
+        let err = manager
+            .spawn("quick", "quick", "cli", "direct", "cli:test")
+            .unwrap_err();
+        assert!(err.to_string().contains("limit 1"));
+
+        assert!(manager.cancel(&slow.task.id));
+        assert!(!manager.cancel(&slow.task.id));
+        let report = tokio::time::timeout(Duration::from_secs(5), reported.recv()).await;
+        assert_eq!(report.unwrap().as_deref(), Some("slow cancelled"));
+        let quick = manager
+            .spawn("quick", "quick", "cli", "direct", "cli:test")
+            .unwrap();
+        for _ in 0..50 {
+            if manager.list(None)[1].status != SubagentStatus::Running {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let listed = manager.list(None);
+        assert_eq!(listed[0].status, SubagentStatus::Cancelled);
+        assert_eq!(listed[1].task.id, quick.task.id);
+        assert_eq!(listed[1].status, SubagentStatus::Done);
+    }
+
+    #[tokio::test]
+    async fn lists_by_session_and_keeps_bounded_history() {
+        let mut manager = SubagentManager::new(4);
+        manager.history = 2;
+        manager.set_runner(boxed_runner(|_task: SubagentTask| async {
+            Ok(String::new())
+        }));
+
+        for session in ["cli:a", "cli:a", "cli:b", "cli:b"] {
+            manager
+                .spawn("quick", "quick", "cli", "direct", session)
+                .unwrap();
+            for _ in 0..50 {
+                if manager
+                    .list(None)
+                    .iter()
+                    .all(|info| info.finished.is_some())
+                {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+
+        let kept = manager.list(None);
+        assert_eq!(kept.len(), 2);
+        assert!(kept.iter().all(|info| info.task.session_key == "cli:b"));
+        assert!(manager.list(Some("cli:a")).is_empty());
+        assert_eq!(manager.list(Some("cli:b")).len(), 2);
+    }
+}
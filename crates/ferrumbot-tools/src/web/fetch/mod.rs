@@ -1,10 +1,9 @@
 mod args;
 mod validate;
 
-use std::time::Duration;
-
 use anyhow::Result;
 use async_trait::async_trait;
+use ferrumbot_config::HttpConfig;
 use reqwest::Client;
 use serde_json::{Value, json};
 
@@ -16,14 +15,11 @@ pub struct WebFetchTool {
 }
 
 impl WebFetchTool {
-    pub fn new(max_chars: usize) -> Self {
-        Self {
+    pub fn new(max_chars: usize, http: &HttpConfig) -> Result<Self> {
+        Ok(Self {
             max_chars,
-            client: Client::builder()
-                .timeout(Duration::from_secs(20))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
-        }
+            client: super::web_client(http)?,
+        })
     }
 }
 
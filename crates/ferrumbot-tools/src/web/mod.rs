@@ -1,5 +1,18 @@
 mod fetch;
 mod search;
 
+use std::time::Duration;
+
+use anyhow::Result;
+use ferrumbot_config::HttpConfig;
+use reqwest::Client;
+
 pub use fetch::WebFetchTool;
 pub use search::WebSearchTool;
+
+fn web_client(http: &HttpConfig) -> Result<Client> {
+    Ok(http
+        .client_builder()?
+        .timeout(Duration::from_secs(20))
+        .build()?)
+}
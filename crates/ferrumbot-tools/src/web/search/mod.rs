@@ -1,10 +1,9 @@
 mod args;
 mod render;
 
-use std::time::Duration;
-
 use anyhow::Result;
 use async_trait::async_trait;
+use ferrumbot_config::HttpConfig;
 use reqwest::Client;
 use serde_json::Value;
 
@@ -17,15 +16,12 @@ pub struct WebSearchTool {
 }
 
 impl WebSearchTool {
-    pub fn new(api_key: Option<String>, max_results: usize) -> Self {
-        Self {
+    pub fn new(api_key: Option<String>, max_results: usize, http: &HttpConfig) -> Result<Self> {
+        Ok(Self {
             api_key,
             max_results,
-            client: Client::builder()
-                .timeout(Duration::from_secs(20))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
-        }
+            client: super::web_client(http)?,
+        })
     }
 }
 